@@ -1,7 +1,7 @@
-//! The `bytes` module contains simple code for normalizing a byte size into a human-readable format.
+//! The `bytes` module contains simple code for normalizing a byte size into a human-readable format
+//! and for parsing a human-readable byte size back into a number of bytes.
 
 use crate::constants::*;
-use std::collections::HashMap;
 
 /// The `ByteMetricBase` enum represents the base to use when converting bytes to a human-readable
 /// format.
@@ -12,6 +12,9 @@ pub enum ByteMetricBase {
 
     /// Use 1000 for decimal prefixes.
     Decimal,
+
+    /// Use 1024 with the IEC binary prefixes (`KiB`, `MiB`, ...).
+    Binary,
 }
 
 /// Normalize a byte size into a human-readable format.
@@ -25,8 +28,60 @@ pub enum ByteMetricBase {
 ///
 /// A string representing the normalized byte size.
 pub fn normalize_byte_size(size: u128, metric_base: ByteMetricBase) -> String {
+    normalize_byte_size_with_precision(size, metric_base, 2)
+}
+
+/// Normalize a byte size into a human-readable format with the given number of fractional digits.
+///
+/// # Arguments
+///
+/// * `size` - The size in bytes to normalize.
+/// * `metric_base` - The base to use when converting bytes to a human-readable format.
+/// * `precision` - The number of digits to print after the decimal point.
+///
+/// # Returns
+///
+/// A string representing the normalized byte size.
+pub fn normalize_byte_size_with_precision(
+    size: u128,
+    metric_base: ByteMetricBase,
+    precision: usize,
+) -> String {
     let (divisor, suffix) = normalize_size_for_divisor_and_suffix(size, metric_base);
-    format!("{:.2} {}", (size as f64) / (divisor as f64), suffix)
+    format!(
+        "{:.*} {}",
+        precision,
+        (size as f64) / (divisor as f64),
+        suffix
+    )
+}
+
+/// Normalize a byte size into a human-readable format with at most the given number of fractional
+/// digits. Unlike `normalize_byte_size_with_precision`, trailing zeros (and a trailing decimal
+/// point) are removed, so `1.50 Kb` prints as `1.5 Kb` and `1.00 Kb` prints as `1 Kb`.
+///
+/// # Arguments
+///
+/// * `size` - The size in bytes to normalize.
+/// * `metric_base` - The base to use when converting bytes to a human-readable format.
+/// * `precision` - The maximum number of digits to print after the decimal point.
+///
+/// # Returns
+///
+/// A string representing the normalized byte size.
+pub fn normalize_byte_size_trimmed(
+    size: u128,
+    metric_base: ByteMetricBase,
+    precision: usize,
+) -> String {
+    let (divisor, suffix) = normalize_size_for_divisor_and_suffix(size, metric_base);
+    let value = format!("{:.*}", precision, (size as f64) / (divisor as f64));
+    let value = if value.contains('.') {
+        value.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        value.as_str()
+    };
+    format!("{} {}", value, suffix)
 }
 
 /// Convert a byte size into a normalized size and suffix.
@@ -58,121 +113,261 @@ fn normalize_size_for_divisor_and_suffix(
     size: u128,
     metric_base: ByteMetricBase,
 ) -> (u128, String) {
-    let (suffix_map, divisor_map): (HashMap<u128, String>, HashMap<u128, u128>) = match metric_base
-    {
-        ByteMetricBase::Metric => (
-            vec![
-                (YOTTA, "Yb".to_string()),
-                (ZETTA, "Zb".to_string()),
-                (EXA as u128, "Eb".to_string()),
-                (PETA as u128, "Pb".to_string()),
-                (TERA as u128, "Tb".to_string()),
-                (GIGA as u128, "Gb".to_string()),
-                (MEGA as u128, "Mb".to_string()),
-                (KILO as u128, "Kb".to_string()),
-            ]
-            .into_iter()
-            .collect(),
-            vec![
-                (YOTTA, YOTTA),
-                (ZETTA, ZETTA),
-                (EXA as u128, EXA as u128),
-                (PETA as u128, PETA as u128),
-                (TERA as u128, TERA as u128),
-                (GIGA as u128, GIGA as u128),
-                (MEGA as u128, MEGA as u128),
-                (KILO as u128, KILO as u128),
-            ]
-            .into_iter()
-            .collect(),
-        ),
-        ByteMetricBase::Decimal => (
-            vec![
-                (YOTTA, "YB".to_string()),
-                (ZETTA, "ZB".to_string()),
-                (EXA as u128, "EB".to_string()),
-                (PETA as u128, "PB".to_string()),
-                (TERA as u128, "TB".to_string()),
-                (GIGA as u128, "GB".to_string()),
-                (MEGA as u128, "MB".to_string()),
-                (KILO as u128, "KB".to_string()),
-            ]
-            .into_iter()
-            .collect(),
-            vec![
-                (YOTTA, MYOTTA),
-                (ZETTA, MZETTA),
-                (EXA as u128, MEXA as u128),
-                (PETA as u128, MPETA as u128),
-                (TERA as u128, MTERA as u128),
-                (GIGA as u128, MGIGA as u128),
-                (MEGA as u128, MMEGA as u128),
-                (KILO as u128, MKILO as u128),
-            ]
-            .into_iter()
-            .collect(),
-        ),
+    let units = match metric_base {
+        ByteMetricBase::Metric => &METRIC_BYTE_UNITS,
+        ByteMetricBase::Decimal => &DECIMAL_BYTE_UNITS,
+        ByteMetricBase::Binary => &BINARY_BYTE_UNITS,
     };
 
-    let (suffix, divisor) = if size < *divisor_map.get(&YOTTA).unwrap() {
-        if size < *divisor_map.get(&ZETTA).unwrap() {
-            if size < *divisor_map.get(&(EXA as u128)).unwrap() {
-                if size < *divisor_map.get(&(PETA as u128)).unwrap() {
-                    if size < *divisor_map.get(&(TERA as u128)).unwrap() {
-                        if size < *divisor_map.get(&(GIGA as u128)).unwrap() {
-                            if size < *divisor_map.get(&(MEGA as u128)).unwrap() {
-                                if size < *divisor_map.get(&(KILO as u128)).unwrap() {
-                                    ("bytes".to_string(), 1u128)
-                                } else {
-                                    (
-                                        suffix_map.get(&(KILO as u128)).unwrap().to_string(),
-                                        *divisor_map.get(&(KILO as u128)).unwrap(),
-                                    )
-                                }
-                            } else {
-                                (
-                                    suffix_map.get(&(MEGA as u128)).unwrap().to_string(),
-                                    *divisor_map.get(&(MEGA as u128)).unwrap(),
-                                )
-                            }
-                        } else {
-                            (
-                                suffix_map.get(&(GIGA as u128)).unwrap().to_string(),
-                                *divisor_map.get(&(GIGA as u128)).unwrap(),
-                            )
-                        }
-                    } else {
-                        (
-                            suffix_map.get(&(TERA as u128)).unwrap().to_string(),
-                            *divisor_map.get(&(TERA as u128)).unwrap(),
-                        )
-                    }
-                } else {
-                    (
-                        suffix_map.get(&(PETA as u128)).unwrap().to_string(),
-                        *divisor_map.get(&(PETA as u128)).unwrap(),
-                    )
-                }
-            } else {
-                (
-                    suffix_map.get(&(EXA as u128)).unwrap().to_string(),
-                    *divisor_map.get(&(EXA as u128)).unwrap(),
-                )
-            }
-        } else {
-            (
-                suffix_map.get(&ZETTA).unwrap().to_string(),
-                *divisor_map.get(&ZETTA).unwrap(),
-            )
-        }
+    select_divisor_and_suffix(size, "bytes", units)
+}
+
+/// Select the divisor and suffix for a size from a unit table.
+///
+/// # Arguments
+///
+/// * `size` - The size to normalize.
+/// * `base_suffix` - The suffix to use for sizes smaller than the smallest threshold.
+/// * `units` - A unit table from the `constants` module, ordered from the largest threshold to the
+///   smallest.
+///
+/// # Returns
+///
+/// A tuple containing the divisor and suffix of the first unit whose threshold `size` reaches, or
+/// a divisor of 1 and `base_suffix` if `size` is below every threshold.
+fn select_divisor_and_suffix(
+    size: u128,
+    base_suffix: &str,
+    units: &[(u128, u128, &str)],
+) -> (u128, String) {
+    units
+        .iter()
+        .find(|(threshold, _, _)| size >= *threshold)
+        .map(|(_, divisor, suffix)| (*divisor, suffix.to_string()))
+        .unwrap_or_else(|| (1, base_suffix.to_string()))
+}
+
+/// Convert a human-readable byte size, such as `"1.5 GB"` or `"2GiB"`, into a number of bytes.
+///
+/// The unit suffixes match the suffixes used by `normalize_byte_size`, so `Kb`, `Mb`, ... use
+/// 1024 and `KB`, `MB`, ... use 1000. The IEC suffixes `KiB`, `MiB`, ... also use 1024 and are
+/// matched without regard to case. A missing suffix, `b`, `B`, or `bytes` means bytes. Whitespace
+/// may separate the number from the suffix.
+///
+/// The value is computed with integer arithmetic, so even Yotta-scale sizes convert exactly. A
+/// fractional number of bytes is truncated.
+///
+/// # Arguments
+///
+/// * `text` - The string to convert.
+///
+/// # Returns
+///
+/// The number of bytes, or `None` if the string does not contain a valid number or the suffix is
+/// unknown or ambiguous (for example `kb`).
+pub fn bytes_from_string(text: &str) -> Option<u128> {
+    let (number, unit) = split_number_and_unit(text);
+    let multiplier = multiplier_for_unit(unit)?;
+    scale_number(number, multiplier)
+}
+
+/// Convert a human-readable number of bits, such as `"100 Mbit"` or `"1 Gbit/s"`, into a number
+/// of bits.
+///
+/// The bit suffixes `bit`, `Kbit`, `Mbit`, ... use powers of 1000 as is usual for network
+/// bandwidth, are matched without regard to case, and may be followed by `/s`. A missing suffix
+/// means bits. Any suffix accepted by `bytes_from_string` is also accepted and converted from
+/// bytes to bits, so `"1b"` is one byte (8 bits) while `"1 bit"` is one bit.
+///
+/// # Arguments
+///
+/// * `text` - The string to convert.
+///
+/// # Returns
+///
+/// The number of bits, or `None` if the string does not contain a valid number or the suffix is
+/// unknown or ambiguous.
+pub fn bits_from_string(text: &str) -> Option<u128> {
+    let text = text.trim();
+    let text = text.strip_suffix("/s").unwrap_or(text);
+    let (number, unit) = split_number_and_unit(text);
+    let multiplier = match bit_multiplier_for_unit(unit) {
+        Some(multiplier) => multiplier,
+        None => multiplier_for_unit(unit)?.checked_mul(8)?,
+    };
+    scale_number(number, multiplier)
+}
+
+/// Normalize a bit rate into a human-readable format such as `"100.00 Mbit/s"`. Bit rates use
+/// powers of 1000.
+///
+/// # Arguments
+///
+/// * `bits_per_sec` - The bit rate in bits per second.
+///
+/// # Returns
+///
+/// A string representing the normalized bit rate.
+pub fn normalize_bit_rate(bits_per_sec: u128) -> String {
+    let (divisor, suffix) = select_divisor_and_suffix(bits_per_sec, "bit", &BIT_RATE_UNITS);
+    format!(
+        "{:.2} {}/s",
+        (bits_per_sec as f64) / (divisor as f64),
+        suffix
+    )
+}
+
+/// Split a human-readable size into its number and its unit suffix. Whitespace around the number
+/// and the suffix is removed.
+///
+/// # Arguments
+///
+/// * `text` - The string to split.
+///
+/// # Returns
+///
+/// A tuple containing the number and the unit suffix.
+fn split_number_and_unit(text: &str) -> (&str, &str) {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    (number, unit.trim())
+}
+
+/// Multiply a decimal number by a unit multiplier.
+///
+/// The number is split into its whole and fractional digits and multiplied with `u128` arithmetic,
+/// so that large multipliers do not lose precision the way they would in an `f64`. Floating point
+/// is only used when the fractional part has too many digits to scale with integers.
+///
+/// # Arguments
+///
+/// * `number` - The number, containing only ASCII digits and at most one decimal point.
+/// * `multiplier` - The number of bytes in one unit.
+///
+/// # Returns
+///
+/// The number of bytes, or `None` if the number is not valid or the result does not fit in a
+/// `u128`.
+fn scale_number(number: &str, multiplier: u128) -> Option<u128> {
+    let (whole, fraction) = match number.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (number, ""),
+    };
+
+    if (whole.is_empty() && fraction.is_empty()) || fraction.contains('.') {
+        return None;
+    }
+
+    let whole_value = if whole.is_empty() {
+        0
     } else {
-        (
-            suffix_map.get(&YOTTA).unwrap().to_string(),
-            *divisor_map.get(&YOTTA).unwrap(),
-        )
+        whole.parse::<u128>().ok()?
     };
+    let whole_bytes = whole_value.checked_mul(multiplier)?;
 
-    (divisor, suffix)
+    if fraction.is_empty() {
+        return Some(whole_bytes);
+    }
+
+    let fraction_value = fraction.parse::<u128>().ok();
+    let fraction_scale = u32::try_from(fraction.len())
+        .ok()
+        .and_then(|digits| 10u128.checked_pow(digits));
+    let fraction_bytes = match (fraction_value, fraction_scale) {
+        (Some(value), Some(scale)) => match value.checked_mul(multiplier) {
+            Some(scaled) => scaled / scale,
+            None => scale_fraction_with_float(fraction, multiplier)?,
+        },
+        _ => scale_fraction_with_float(fraction, multiplier)?,
+    };
+
+    whole_bytes.checked_add(fraction_bytes)
+}
+
+/// Multiply the fractional digits of a decimal number by a unit multiplier using floating point.
+///
+/// # Arguments
+///
+/// * `fraction` - The digits after the decimal point.
+/// * `multiplier` - The number of bytes in one unit.
+///
+/// # Returns
+///
+/// The number of bytes, or `None` if the digits are not valid.
+fn scale_fraction_with_float(fraction: &str, multiplier: u128) -> Option<u128> {
+    let value = format!("0.{}", fraction).parse::<f64>().ok()?;
+    Some((value * multiplier as f64) as u128)
+}
+
+/// Look up the number of bits represented by a bit unit suffix.
+///
+/// # Arguments
+///
+/// * `unit` - The unit suffix, without surrounding whitespace.
+///
+/// # Returns
+///
+/// The number of bits in one unit, or `None` if the unit is not a bit unit.
+fn bit_multiplier_for_unit(unit: &str) -> Option<u128> {
+    let multiplier = match unit.to_lowercase().as_str() {
+        "" | "bit" | "bits" => 1,
+        "kbit" | "kbits" => MKILO as u128,
+        "mbit" | "mbits" => MMEGA as u128,
+        "gbit" | "gbits" => MGIGA as u128,
+        "tbit" | "tbits" => MTERA as u128,
+        "pbit" | "pbits" => MPETA as u128,
+        "ebit" | "ebits" => MEXA as u128,
+        "zbit" | "zbits" => MZETTA,
+        "ybit" | "ybits" => MYOTTA,
+        _ => return None,
+    };
+    Some(multiplier)
+}
+
+/// Look up the number of bytes represented by a unit suffix.
+///
+/// # Arguments
+///
+/// * `unit` - The unit suffix, without surrounding whitespace.
+///
+/// # Returns
+///
+/// The number of bytes in one unit, or `None` if the unit is unknown or ambiguous.
+fn multiplier_for_unit(unit: &str) -> Option<u128> {
+    let multiplier = match unit {
+        "" | "b" | "B" | "byte" | "bytes" => 1,
+        "Kb" => KILO as u128,
+        "Mb" => MEGA as u128,
+        "Gb" => GIGA as u128,
+        "Tb" => TERA as u128,
+        "Pb" => PETA as u128,
+        "Eb" => EXA as u128,
+        "Zb" => ZETTA,
+        "Yb" => YOTTA,
+        "KB" => MKILO as u128,
+        "MB" => MMEGA as u128,
+        "GB" => MGIGA as u128,
+        "TB" => MTERA as u128,
+        "PB" => MPETA as u128,
+        "EB" => MEXA as u128,
+        "ZB" => MZETTA,
+        "YB" => MYOTTA,
+        _ => match unit.to_lowercase().as_str() {
+            "kib" => KILO as u128,
+            "mib" => MEGA as u128,
+            "gib" => GIGA as u128,
+            "tib" => TERA as u128,
+            "pib" => PETA as u128,
+            "eib" => EXA as u128,
+            "zib" => ZETTA,
+            "yib" => YOTTA,
+            _ => return None,
+        },
+    };
+    Some(multiplier)
 }
 
 #[cfg(test)]
@@ -320,6 +515,112 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalization_of_binary() {
+        assert_eq!(
+            normalize_byte_size(10, ByteMetricBase::Binary),
+            "10.00 bytes"
+        );
+        assert_eq!(
+            normalize_byte_size(1024, ByteMetricBase::Binary),
+            "1.00 KiB"
+        );
+        assert_eq!(
+            normalize_byte_size(1048576, ByteMetricBase::Binary),
+            "1.00 MiB"
+        );
+        assert_eq!(
+            normalize_byte_size(1000000000, ByteMetricBase::Binary),
+            "953.67 MiB"
+        );
+        assert_eq!(
+            normalize_byte_size(1099511627776, ByteMetricBase::Binary),
+            "1.00 TiB"
+        );
+        assert_eq!(
+            normalize_byte_size(1208925819614629174706176, ByteMetricBase::Binary),
+            "1.00 YiB"
+        );
+        assert_eq!(
+            normalize_size(1073741824, ByteMetricBase::Binary),
+            (1.0, "GiB".to_string())
+        );
+
+        // The binary suffixes parse back to the same size.
+        assert_eq!(
+            bytes_from_string(&normalize_byte_size(1048576, ByteMetricBase::Binary)),
+            Some(1048576)
+        );
+    }
+
+    #[test]
+    fn test_normalization_with_precision() {
+        assert_eq!(
+            normalize_byte_size_with_precision(10, ByteMetricBase::Metric, 0),
+            "10 bytes"
+        );
+        assert_eq!(
+            normalize_byte_size_with_precision(1073741824, ByteMetricBase::Metric, 0),
+            "1 Gb"
+        );
+        assert_eq!(
+            normalize_byte_size_with_precision(1000000, ByteMetricBase::Metric, 0),
+            "977 Kb"
+        );
+        assert_eq!(
+            normalize_byte_size_with_precision(1000000, ByteMetricBase::Metric, 2),
+            "976.56 Kb"
+        );
+        assert_eq!(
+            normalize_byte_size_with_precision(1000000, ByteMetricBase::Metric, 4),
+            "976.5625 Kb"
+        );
+        assert_eq!(
+            normalize_byte_size_with_precision(1099511627776, ByteMetricBase::Decimal, 0),
+            "1 TB"
+        );
+        assert_eq!(
+            normalize_byte_size_with_precision(1099511627776, ByteMetricBase::Decimal, 2),
+            "1.10 TB"
+        );
+        assert_eq!(
+            normalize_byte_size_with_precision(1099511627776, ByteMetricBase::Decimal, 4),
+            "1.0995 TB"
+        );
+        assert_eq!(
+            normalize_byte_size_with_precision(1234567, ByteMetricBase::Decimal, 4),
+            "1.2346 MB"
+        );
+    }
+
+    #[test]
+    fn test_normalization_trimmed() {
+        assert_eq!(
+            normalize_byte_size_trimmed(10, ByteMetricBase::Metric, 2),
+            "10 bytes"
+        );
+        assert_eq!(
+            normalize_byte_size_trimmed(1024, ByteMetricBase::Metric, 2),
+            "1 Kb"
+        );
+        assert_eq!(
+            normalize_byte_size_trimmed(1536, ByteMetricBase::Metric, 2),
+            "1.5 Kb"
+        );
+        assert_eq!(
+            normalize_byte_size_trimmed(1000000, ByteMetricBase::Metric, 4),
+            "976.5625 Kb"
+        );
+        assert_eq!(
+            normalize_byte_size_trimmed(1000000000, ByteMetricBase::Decimal, 0),
+            "1 GB"
+        );
+        assert_eq!(
+            normalize_byte_size_trimmed(1100000000, ByteMetricBase::Decimal, 4),
+            "1.1 GB"
+        );
+    }
+
     #[test]
     fn test_normalize_size_of_metric() {
         assert_eq!(
@@ -463,4 +764,522 @@ mod tests {
             (1.0, "YB".to_string())
         );
     }
+
+    #[test]
+    fn test_bytes_from_string() {
+        assert_eq!(bytes_from_string("10"), Some(10));
+        assert_eq!(bytes_from_string("10 bytes"), Some(10));
+        assert_eq!(bytes_from_string("1Kb"), Some(1024));
+        assert_eq!(bytes_from_string("1KB"), Some(1000));
+        assert_eq!(bytes_from_string("1.5 GB"), Some(1_500_000_000));
+        assert_eq!(bytes_from_string("  1.5   GB  "), Some(1_500_000_000));
+        assert_eq!(bytes_from_string("2 GiB"), Some(2 * GIGA as u128));
+        assert_eq!(bytes_from_string("10 kib"), Some(10 * KILO as u128));
+        assert_eq!(bytes_from_string("3MiB"), Some(3 * MEGA as u128));
+    }
+
+    #[test]
+    fn test_bytes_from_string_precision() {
+        assert_eq!(
+            bytes_from_string("1YB"),
+            Some(1_000_000_000_000_000_000_000_000)
+        );
+        assert_eq!(bytes_from_string("1Yb"), Some(1208925819614629174706176));
+        assert_eq!(
+            bytes_from_string("1.5 YB"),
+            Some(1_500_000_000_000_000_000_000_000)
+        );
+        assert_eq!(
+            bytes_from_string("1.21 YB"),
+            Some(1_210_000_000_000_000_000_000_000)
+        );
+        assert_eq!(bytes_from_string("2 YiB"), Some(2 * YOTTA));
+        assert_eq!(bytes_from_string("1.5"), Some(1));
+        assert_eq!(bytes_from_string(".5 KB"), Some(500));
+        assert_eq!(bytes_from_string("0.0000001 KB"), Some(0));
+        assert_eq!(
+            bytes_from_string("1.0000000000000000000000000000000000000001 YB"),
+            Some(1_000_000_000_000_000_000_000_000)
+        );
+        assert_eq!(bytes_from_string("1000000000000000 YB"), None);
+    }
+
+    #[test]
+    fn test_bytes_from_string_invalid() {
+        assert_eq!(bytes_from_string(""), None);
+        assert_eq!(bytes_from_string("GB"), None);
+        assert_eq!(bytes_from_string("1,024"), None);
+        assert_eq!(bytes_from_string("1.2.3 KB"), None);
+        assert_eq!(bytes_from_string(". KB"), None);
+        assert_eq!(bytes_from_string("-1 KB"), None);
+        assert_eq!(bytes_from_string("1 kb"), None);
+        assert_eq!(bytes_from_string("1 XB"), None);
+    }
+
+    #[test]
+    fn test_bits_from_string() {
+        assert_eq!(bits_from_string("100"), Some(100));
+        assert_eq!(bits_from_string("8 bit"), Some(8));
+        assert_eq!(bits_from_string("1 bit"), Some(1));
+        assert_eq!(bits_from_string("1b"), Some(8));
+        assert_eq!(bits_from_string("1KB"), Some(8000));
+        assert_eq!(bits_from_string("100 Mbit"), Some(100_000_000));
+        assert_eq!(bits_from_string("1 Gbit"), Some(1_000_000_000));
+        assert_eq!(bits_from_string("1.5 gbit/s"), Some(1_500_000_000));
+        assert_eq!(bits_from_string("1 Gbyte"), None);
+        assert_eq!(bits_from_string("bit"), None);
+    }
+
+    #[test]
+    fn test_normalize_bit_rate() {
+        assert_eq!(normalize_bit_rate(8), "8.00 bit/s");
+        assert_eq!(normalize_bit_rate(1000), "1.00 Kbit/s");
+        assert_eq!(normalize_bit_rate(100_000_000), "100.00 Mbit/s");
+        assert_eq!(normalize_bit_rate(1_000_000_000), "1.00 Gbit/s");
+        assert_eq!(normalize_bit_rate(2_500_000_000_000), "2.50 Tbit/s");
+
+        for rate in [8, 1000, 100_000_000, 1_000_000_000, 2_500_000_000_000] {
+            assert_eq!(bits_from_string(&normalize_bit_rate(rate)), Some(rate));
+        }
+    }
+
+    #[test]
+    fn test_normalization_across_magnitudes() {
+        // The expected strings were produced by the implementation that built the unit tables
+        // on every call, just below, at, and above each unit boundary.
+        let cases: [(u128, &str, &str, &str, &str); 50] = [
+            (0, "0.00 bytes", "0.00 bytes", "0.00 bytes", "0.00 bit/s"),
+            (
+                999,
+                "999.00 bytes",
+                "999.00 bytes",
+                "999.00 bytes",
+                "999.00 bit/s",
+            ),
+            (
+                1000,
+                "1000.00 bytes",
+                "1.00 KB",
+                "1000.00 bytes",
+                "1.00 Kbit/s",
+            ),
+            (
+                1023,
+                "1023.00 bytes",
+                "1.02 KB",
+                "1023.00 bytes",
+                "1.02 Kbit/s",
+            ),
+            (1024, "1.00 Kb", "1.02 KB", "1.00 KiB", "1.02 Kbit/s"),
+            (1500, "1.46 Kb", "1.50 KB", "1.46 KiB", "1.50 Kbit/s"),
+            (1536, "1.50 Kb", "1.54 KB", "1.50 KiB", "1.54 Kbit/s"),
+            (
+                999999,
+                "976.56 Kb",
+                "1000.00 KB",
+                "976.56 KiB",
+                "1000.00 Kbit/s",
+            ),
+            (1000000, "976.56 Kb", "1.00 MB", "976.56 KiB", "1.00 Mbit/s"),
+            (
+                1048575,
+                "1024.00 Kb",
+                "1.05 MB",
+                "1024.00 KiB",
+                "1.05 Mbit/s",
+            ),
+            (1048576, "1.00 Mb", "1.05 MB", "1.00 MiB", "1.05 Mbit/s"),
+            (1500000, "1.43 Mb", "1.50 MB", "1.43 MiB", "1.50 Mbit/s"),
+            (1572864, "1.50 Mb", "1.57 MB", "1.50 MiB", "1.57 Mbit/s"),
+            (
+                999999999,
+                "953.67 Mb",
+                "1000.00 MB",
+                "953.67 MiB",
+                "1000.00 Mbit/s",
+            ),
+            (
+                1000000000,
+                "953.67 Mb",
+                "1.00 GB",
+                "953.67 MiB",
+                "1.00 Gbit/s",
+            ),
+            (
+                1073741823,
+                "1024.00 Mb",
+                "1.07 GB",
+                "1024.00 MiB",
+                "1.07 Gbit/s",
+            ),
+            (1073741824, "1.00 Gb", "1.07 GB", "1.00 GiB", "1.07 Gbit/s"),
+            (1500000000, "1.40 Gb", "1.50 GB", "1.40 GiB", "1.50 Gbit/s"),
+            (1610612736, "1.50 Gb", "1.61 GB", "1.50 GiB", "1.61 Gbit/s"),
+            (
+                999999999999,
+                "931.32 Gb",
+                "1000.00 GB",
+                "931.32 GiB",
+                "1000.00 Gbit/s",
+            ),
+            (
+                1000000000000,
+                "931.32 Gb",
+                "1.00 TB",
+                "931.32 GiB",
+                "1.00 Tbit/s",
+            ),
+            (
+                1099511627775,
+                "1024.00 Gb",
+                "1.10 TB",
+                "1024.00 GiB",
+                "1.10 Tbit/s",
+            ),
+            (
+                1099511627776,
+                "1.00 Tb",
+                "1.10 TB",
+                "1.00 TiB",
+                "1.10 Tbit/s",
+            ),
+            (
+                1500000000000,
+                "1.36 Tb",
+                "1.50 TB",
+                "1.36 TiB",
+                "1.50 Tbit/s",
+            ),
+            (
+                1649267441664,
+                "1.50 Tb",
+                "1.65 TB",
+                "1.50 TiB",
+                "1.65 Tbit/s",
+            ),
+            (
+                999999999999999,
+                "909.49 Tb",
+                "1000.00 TB",
+                "909.49 TiB",
+                "1000.00 Tbit/s",
+            ),
+            (
+                1000000000000000,
+                "909.49 Tb",
+                "1.00 PB",
+                "909.49 TiB",
+                "1.00 Pbit/s",
+            ),
+            (
+                1125899906842623,
+                "1024.00 Tb",
+                "1.13 PB",
+                "1024.00 TiB",
+                "1.13 Pbit/s",
+            ),
+            (
+                1125899906842624,
+                "1.00 Pb",
+                "1.13 PB",
+                "1.00 PiB",
+                "1.13 Pbit/s",
+            ),
+            (
+                1500000000000000,
+                "1.33 Pb",
+                "1.50 PB",
+                "1.33 PiB",
+                "1.50 Pbit/s",
+            ),
+            (
+                1688849860263936,
+                "1.50 Pb",
+                "1.69 PB",
+                "1.50 PiB",
+                "1.69 Pbit/s",
+            ),
+            (
+                999999999999999999,
+                "888.18 Pb",
+                "1000.00 PB",
+                "888.18 PiB",
+                "1000.00 Pbit/s",
+            ),
+            (
+                1000000000000000000,
+                "888.18 Pb",
+                "1.00 EB",
+                "888.18 PiB",
+                "1.00 Ebit/s",
+            ),
+            (
+                1152921504606846975,
+                "1024.00 Pb",
+                "1.15 EB",
+                "1024.00 PiB",
+                "1.15 Ebit/s",
+            ),
+            (
+                1152921504606846976,
+                "1.00 Eb",
+                "1.15 EB",
+                "1.00 EiB",
+                "1.15 Ebit/s",
+            ),
+            (
+                1500000000000000000,
+                "1.30 Eb",
+                "1.50 EB",
+                "1.30 EiB",
+                "1.50 Ebit/s",
+            ),
+            (
+                1729382256910270464,
+                "1.50 Eb",
+                "1.73 EB",
+                "1.50 EiB",
+                "1.73 Ebit/s",
+            ),
+            (
+                999999999999999999999,
+                "867.36 Eb",
+                "1000.00 EB",
+                "867.36 EiB",
+                "1000.00 Ebit/s",
+            ),
+            (
+                1000000000000000000000,
+                "867.36 Eb",
+                "1.00 ZB",
+                "867.36 EiB",
+                "1.00 Zbit/s",
+            ),
+            (
+                1180591620717411303423,
+                "1024.00 Eb",
+                "1.18 ZB",
+                "1024.00 EiB",
+                "1.18 Zbit/s",
+            ),
+            (
+                1180591620717411303424,
+                "1.00 Zb",
+                "1.18 ZB",
+                "1.00 ZiB",
+                "1.18 Zbit/s",
+            ),
+            (
+                1500000000000000000000,
+                "1.27 Zb",
+                "1.50 ZB",
+                "1.27 ZiB",
+                "1.50 Zbit/s",
+            ),
+            (
+                1770887431076116955136,
+                "1.50 Zb",
+                "1.77 ZB",
+                "1.50 ZiB",
+                "1.77 Zbit/s",
+            ),
+            (
+                999999999999999999999999,
+                "847.03 Zb",
+                "1000.00 ZB",
+                "847.03 ZiB",
+                "1000.00 Zbit/s",
+            ),
+            (
+                1000000000000000000000000,
+                "847.03 Zb",
+                "1.00 YB",
+                "847.03 ZiB",
+                "1.00 Ybit/s",
+            ),
+            (
+                1208925819614629174706175,
+                "1024.00 Zb",
+                "1.21 YB",
+                "1024.00 ZiB",
+                "1.21 Ybit/s",
+            ),
+            (
+                1208925819614629174706176,
+                "1.00 Yb",
+                "1.21 YB",
+                "1.00 YiB",
+                "1.21 Ybit/s",
+            ),
+            (
+                1500000000000000000000000,
+                "1.24 Yb",
+                "1.50 YB",
+                "1.24 YiB",
+                "1.50 Ybit/s",
+            ),
+            (
+                1813388729421943762059264,
+                "1.50 Yb",
+                "1.81 YB",
+                "1.50 YiB",
+                "1.81 Ybit/s",
+            ),
+            (
+                340282366920938463463374607431768211455,
+                "281474976710656.00 Yb",
+                "340282366920938.50 YB",
+                "281474976710656.00 YiB",
+                "340282366920938.50 Ybit/s",
+            ),
+        ];
+
+        for (size, metric, decimal, binary, bit_rate) in cases {
+            assert_eq!(normalize_byte_size(size, ByteMetricBase::Metric), metric);
+            assert_eq!(normalize_byte_size(size, ByteMetricBase::Decimal), decimal);
+            assert_eq!(normalize_byte_size(size, ByteMetricBase::Binary), binary);
+            assert_eq!(normalize_bit_rate(size), bit_rate);
+        }
+    }
+
+    // The nested comparison ladder that the table scan in `select_divisor_and_suffix` replaced,
+    // kept to check that the scan selects the same units. `units` lists the suffix and divisor
+    // from the largest unit to the smallest.
+    fn legacy_divisor_and_suffix(
+        size: u128,
+        base_suffix: &str,
+        units: [(&str, u128); 8],
+    ) -> (u128, String) {
+        let [yotta, zetta, exa, peta, tera, giga, mega, kilo] = units;
+        let (suffix, divisor) = if size < yotta.1 {
+            if size < zetta.1 {
+                if size < exa.1 {
+                    if size < peta.1 {
+                        if size < tera.1 {
+                            if size < giga.1 {
+                                if size < mega.1 {
+                                    if size < kilo.1 {
+                                        (base_suffix, 1u128)
+                                    } else {
+                                        kilo
+                                    }
+                                } else {
+                                    mega
+                                }
+                            } else {
+                                giga
+                            }
+                        } else {
+                            tera
+                        }
+                    } else {
+                        peta
+                    }
+                } else {
+                    exa
+                }
+            } else {
+                zetta
+            }
+        } else {
+            yotta
+        };
+
+        (divisor, suffix.to_string())
+    }
+
+    #[test]
+    fn test_table_scan_matches_legacy_ladder() {
+        let binary_divisors = [
+            YOTTA,
+            ZETTA,
+            EXA as u128,
+            PETA as u128,
+            TERA as u128,
+            GIGA as u128,
+            MEGA as u128,
+            KILO as u128,
+        ];
+        let decimal_divisors = [
+            MYOTTA,
+            MZETTA,
+            MEXA as u128,
+            MPETA as u128,
+            MTERA as u128,
+            MGIGA as u128,
+            MMEGA as u128,
+            MKILO as u128,
+        ];
+        let with_suffixes = |suffixes: [&'static str; 8], divisors: [u128; 8]| {
+            let mut units = [("", 0u128); 8];
+            for (i, unit) in units.iter_mut().enumerate() {
+                *unit = (suffixes[i], divisors[i]);
+            }
+            units
+        };
+
+        let bases = [
+            (
+                "bytes",
+                &METRIC_BYTE_UNITS,
+                with_suffixes(
+                    ["Yb", "Zb", "Eb", "Pb", "Tb", "Gb", "Mb", "Kb"],
+                    binary_divisors,
+                ),
+            ),
+            (
+                "bytes",
+                &DECIMAL_BYTE_UNITS,
+                with_suffixes(
+                    ["YB", "ZB", "EB", "PB", "TB", "GB", "MB", "KB"],
+                    decimal_divisors,
+                ),
+            ),
+            (
+                "bytes",
+                &BINARY_BYTE_UNITS,
+                with_suffixes(
+                    ["YiB", "ZiB", "EiB", "PiB", "TiB", "GiB", "MiB", "KiB"],
+                    binary_divisors,
+                ),
+            ),
+            (
+                "bit",
+                &BIT_RATE_UNITS,
+                with_suffixes(
+                    [
+                        "Ybit", "Zbit", "Ebit", "Pbit", "Tbit", "Gbit", "Mbit", "Kbit",
+                    ],
+                    decimal_divisors,
+                ),
+            ),
+        ];
+
+        // Every unit boundary, and the sizes on either side of it.
+        let mut sizes = vec![0, 1, u128::MAX];
+        for divisor in binary_divisors.iter().chain(decimal_divisors.iter()) {
+            sizes.extend([divisor - 1, *divisor, divisor + 1]);
+        }
+
+        // Pseudo-random sizes spread across all magnitudes, from a fixed xorshift seed so
+        // failures are reproducible.
+        let mut state: u128 = 0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C834;
+        for _ in 0..10_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            sizes.push(state >> (state % 128));
+        }
+
+        for (base_suffix, units, legacy_units) in bases {
+            for size in &sizes {
+                assert_eq!(
+                    select_divisor_and_suffix(*size, base_suffix, units),
+                    legacy_divisor_and_suffix(*size, base_suffix, legacy_units),
+                    "size {}",
+                    size
+                );
+            }
+        }
+    }
 }
@@ -19,3 +19,55 @@ pub const MPETA: u64 = 1000 * MTERA;
 pub const MEXA: u64 = 1000 * MPETA;
 pub const MZETTA: u128 = 1000 * (MEXA as u128);
 pub const MYOTTA: u128 = 1000 * MZETTA;
+
+// Unit tables used to normalize sizes for display. Each entry is `(threshold, divisor, suffix)`:
+// a size of at least `threshold` is divided by `divisor` and printed with `suffix`. The entries
+// are ordered from the largest threshold to the smallest.
+
+// Sizes in powers of 1024 with the `Kb`, `Mb`, ... suffixes.
+pub const METRIC_BYTE_UNITS: [(u128, u128, &str); 8] = [
+    (YOTTA, YOTTA, "Yb"),
+    (ZETTA, ZETTA, "Zb"),
+    (EXA as u128, EXA as u128, "Eb"),
+    (PETA as u128, PETA as u128, "Pb"),
+    (TERA as u128, TERA as u128, "Tb"),
+    (GIGA as u128, GIGA as u128, "Gb"),
+    (MEGA as u128, MEGA as u128, "Mb"),
+    (KILO as u128, KILO as u128, "Kb"),
+];
+
+// Sizes in powers of 1000 with the `KB`, `MB`, ... suffixes.
+pub const DECIMAL_BYTE_UNITS: [(u128, u128, &str); 8] = [
+    (MYOTTA, MYOTTA, "YB"),
+    (MZETTA, MZETTA, "ZB"),
+    (MEXA as u128, MEXA as u128, "EB"),
+    (MPETA as u128, MPETA as u128, "PB"),
+    (MTERA as u128, MTERA as u128, "TB"),
+    (MGIGA as u128, MGIGA as u128, "GB"),
+    (MMEGA as u128, MMEGA as u128, "MB"),
+    (MKILO as u128, MKILO as u128, "KB"),
+];
+
+// Sizes in powers of 1024 with the IEC `KiB`, `MiB`, ... suffixes.
+pub const BINARY_BYTE_UNITS: [(u128, u128, &str); 8] = [
+    (YOTTA, YOTTA, "YiB"),
+    (ZETTA, ZETTA, "ZiB"),
+    (EXA as u128, EXA as u128, "EiB"),
+    (PETA as u128, PETA as u128, "PiB"),
+    (TERA as u128, TERA as u128, "TiB"),
+    (GIGA as u128, GIGA as u128, "GiB"),
+    (MEGA as u128, MEGA as u128, "MiB"),
+    (KILO as u128, KILO as u128, "KiB"),
+];
+
+// Bit rates in powers of 1000 with the `Kbit`, `Mbit`, ... suffixes.
+pub const BIT_RATE_UNITS: [(u128, u128, &str); 8] = [
+    (MYOTTA, MYOTTA, "Ybit"),
+    (MZETTA, MZETTA, "Zbit"),
+    (MEXA as u128, MEXA as u128, "Ebit"),
+    (MPETA as u128, MPETA as u128, "Pbit"),
+    (MTERA as u128, MTERA as u128, "Tbit"),
+    (MGIGA as u128, MGIGA as u128, "Gbit"),
+    (MMEGA as u128, MMEGA as u128, "Mbit"),
+    (MKILO as u128, MKILO as u128, "Kbit"),
+];
@@ -27,7 +27,7 @@
 /// In using `Defer`, the programmer must often explicitly write a drop() statement in order
 /// to force the compiler to not optimize away the deferred object.
 pub struct Defer {
-    action: Box<dyn FnMut() -> () + Send + Sync + 'static>,
+    action: Option<Box<dyn FnMut() -> () + Send + Sync + 'static>>,
 }
 
 impl Defer {
@@ -45,14 +45,100 @@ impl Defer {
         F: FnMut() -> () + Send + Sync + 'static,
     {
         Defer {
-            action: Box::new(action),
+            action: Some(Box::new(action)),
         }
     }
+
+    /// The `cancel` function consumes the `Defer` object without running its action. Use it to
+    /// skip cleanup once the work it guards has succeeded, such as keeping a temporary file
+    /// after the work is committed.
+    pub fn cancel(mut self) {
+        self.action = None;
+    }
 }
 
 impl Drop for Defer {
     fn drop(&mut self) {
-        (self.action)();
+        if let Some(action) = self.action.as_mut() {
+            action();
+        }
+    }
+}
+
+/// The `DeferValue` struct holds a value and runs an action with the value when the
+/// `DeferValue` object goes out of scope. Code in the scope can read and change the value
+/// through `value` and `value_mut`, and the action receives the final value.
+///
+/// # Example
+///
+/// ```rust
+/// use foundation::defer::DeferValue;
+/// use std::sync::{Arc, Mutex};
+///
+/// let result = Arc::new(Mutex::new(Vec::new()));
+/// let result_c = result.clone();
+/// {
+///     let mut steps = DeferValue::new(Vec::new(), move |steps: Vec<&str>| {
+///         *result_c.lock().unwrap() = steps;
+///     });
+///     steps.value_mut().push("first");
+///     steps.value_mut().push("second");
+/// }
+/// assert_eq!(*result.lock().unwrap(), vec!["first", "second"]);
+/// ```
+pub struct DeferValue<T> {
+    value: Option<T>,
+    action: Option<Box<dyn FnOnce(T) + Send + Sync + 'static>>,
+}
+
+impl<T> DeferValue<T> {
+    /// The `new` function creates a new `DeferValue` object with the given value and action.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to pass to the action.
+    /// * `action` - The action to run with the value when the `DeferValue` object goes out of
+    ///   scope.
+    ///
+    /// # Returns
+    ///
+    /// A new `DeferValue` object with the given value and action.
+    pub fn new<F>(value: T, action: F) -> DeferValue<T>
+    where
+        F: FnOnce(T) + Send + Sync + 'static,
+    {
+        DeferValue {
+            value: Some(value),
+            action: Some(Box::new(action)),
+        }
+    }
+
+    /// The `value` function returns a reference to the held value.
+    pub fn value(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+
+    /// The `value_mut` function returns a mutable reference to the held value.
+    pub fn value_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+
+    /// The `cancel` function consumes the `DeferValue` object without running its action.
+    ///
+    /// # Returns
+    ///
+    /// The held value.
+    pub fn cancel(mut self) -> T {
+        self.action = None;
+        self.value.take().unwrap()
+    }
+}
+
+impl<T> Drop for DeferValue<T> {
+    fn drop(&mut self) {
+        if let (Some(action), Some(value)) = (self.action.take(), self.value.take()) {
+            action(value);
+        }
     }
 }
 
@@ -70,4 +156,36 @@ mod tests {
         }
         assert_eq!(*x.read().unwrap(), 1);
     }
+
+    #[test]
+    fn test_defer_cancel() {
+        let x = Arc::new(RwLock::new(0));
+        let x_c = x.clone();
+        {
+            let defer = Defer::new(move || *x_c.write().unwrap() = 1);
+            defer.cancel();
+        }
+        assert_eq!(*x.read().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_defer_value() {
+        let x = Arc::new(RwLock::new(0));
+        let x_c = x.clone();
+        {
+            let mut defer = DeferValue::new(1, move |value| *x_c.write().unwrap() = value);
+            *defer.value_mut() += 41;
+            assert_eq!(*defer.value(), 42);
+        }
+        assert_eq!(*x.read().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_defer_value_cancel() {
+        let x = Arc::new(RwLock::new(0));
+        let x_c = x.clone();
+        let defer = DeferValue::new(7, move |value| *x_c.write().unwrap() = value);
+        assert_eq!(defer.cancel(), 7);
+        assert_eq!(*x.read().unwrap(), 0);
+    }
 }
@@ -13,20 +13,53 @@
 
 use crate::error::FoundationError;
 use crate::threadpool::{ThreadJob, ThreadPool, WorkerId};
+use log::error;
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
 
 /// The handler is a function or closure that takes the data and implements any functionality
 /// needed to process the data.
 pub type Handler<T> = Box<dyn Fn(T) -> () + Send + Sync + 'static>;
 
+/// A handler and its data waiting to run.
+type ActionSlot<T> = Arc<Mutex<Option<(Handler<T>, T)>>>;
+
+/// A handler scheduled to run after a delay.
+struct PendingAction<T> {
+    /// The handler and data. The slot is emptied when the handler is sent to the thread pool or
+    /// when the action is cancelled.
+    action: ActionSlot<T>,
+
+    /// The time at which the timer task sends the handler to the thread pool.
+    deadline: watch::Sender<Instant>,
+
+    /// The timer task for the action.
+    timer: JoinHandle<()>,
+}
+
+impl<T> PendingAction<T> {
+    /// Check whether the action is still waiting to run.
+    fn is_pending(&self) -> bool {
+        self.action.lock().unwrap().is_some()
+    }
+}
+
 /// The `DelayedHandler` struct is a container for handlers that need to be executed at a later time.
 pub struct DelayedHandler<K: Clone + Hash + PartialEq + Eq, T: Send + Sync + 'static> {
     /// A map of keys to handlers.
     handlers: HashMap<K, Handler<T>>,
 
-    /// The thread pool for executing the handlers.
-    thread_pool: ThreadPool,
+    /// A map of keys to handlers scheduled to run after a delay.
+    pending: HashMap<K, PendingAction<T>>,
+
+    /// The thread pool for executing the handlers. The timer tasks of delayed handlers share the
+    /// thread pool so that they can add jobs when their deadline arrives.
+    thread_pool: Arc<Mutex<ThreadPool>>,
 }
 
 impl<K: Clone + Hash + PartialEq + Eq, T: Send + Sync + 'static> DelayedHandler<K, T> {
@@ -34,7 +67,8 @@ impl<K: Clone + Hash + PartialEq + Eq, T: Send + Sync + 'static> DelayedHandler<
     pub fn new(max_workers: WorkerId) -> Self {
         DelayedHandler {
             handlers: HashMap::new(),
-            thread_pool: ThreadPool::new(max_workers),
+            pending: HashMap::new(),
+            thread_pool: Arc::new(Mutex::new(ThreadPool::new(max_workers))),
         }
     }
 
@@ -65,12 +99,157 @@ impl<K: Clone + Hash + PartialEq + Eq, T: Send + Sync + 'static> DelayedHandler<
                 handler(data);
                 Ok(())
             }));
-            self.thread_pool.add_job(thread_job)
+            self.thread_pool.lock().unwrap().add_job(thread_job)
         } else {
             Err(FoundationError::HandlerNotFound)
         }
     }
 
+    /// Schedule the handler with the given key and data for execution in the thread pool once
+    /// the delay has passed. Until then the action can be cancelled with `cancel` or delayed
+    /// further with `reschedule`. Only one action per key can wait at a time, so scheduling a key
+    /// whose action has not run yet fails with `FoundationError::HandlerAlreadyPending`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key associated with the handler.
+    /// * `data` - The data to pass to the handler.
+    /// * `delay` - How long to wait before executing the handler.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure of the scheduling operation.
+    pub fn schedule_handler_after(
+        &mut self,
+        key: &K,
+        data: T,
+        delay: Duration,
+    ) -> Result<(), FoundationError> {
+        // Replacing the pending action would drop it without running or cancelling it.
+        if self.is_pending(key) {
+            return Err(FoundationError::HandlerAlreadyPending);
+        }
+
+        let handler = match self.handlers.remove(key) {
+            Some(handler) => handler,
+            None => return Err(FoundationError::HandlerNotFound),
+        };
+
+        let action: ActionSlot<T> = Arc::new(Mutex::new(Some((handler, data))));
+        let (deadline, mut deadline_receiver) = watch::channel(Instant::now() + delay);
+        let timer_action = action.clone();
+        let thread_pool = self.thread_pool.clone();
+
+        let timer = tokio::spawn(async move {
+            loop {
+                let current_deadline = *deadline_receiver.borrow_and_update();
+                tokio::select! {
+                    _ = tokio::time::sleep_until(current_deadline) => break,
+                    changed = deadline_receiver.changed() => {
+                        // The sender only goes away when the action is cancelled.
+                        if changed.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            let (handler, data) = match timer_action.lock().unwrap().take() {
+                Some(action) => action,
+                None => return,
+            };
+
+            let mut thread_job = ThreadJob::new();
+            thread_job.add_task(Box::pin(async move {
+                handler(data);
+                Ok(())
+            }));
+            if let Err(e) = thread_pool.lock().unwrap().add_job(thread_job) {
+                error!("Failed to schedule delayed handler: {}", e);
+            }
+        });
+
+        self.pending.insert(
+            key.clone(),
+            PendingAction {
+                action,
+                deadline,
+                timer,
+            },
+        );
+        Ok(())
+    }
+
+    /// Cancel the delayed action for the given key. The handler of a cancelled action goes back
+    /// into the `DelayedHandler` instance so that it can be scheduled again.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key associated with the handler.
+    ///
+    /// # Returns
+    ///
+    /// True if an action was waiting to run and has been cancelled, false otherwise.
+    pub fn cancel(&mut self, key: &K) -> bool {
+        let pending_action = match self.pending.remove(key) {
+            Some(pending_action) => pending_action,
+            None => return false,
+        };
+
+        pending_action.timer.abort();
+        let action = pending_action.action.lock().unwrap().take();
+        match action {
+            Some((handler, _)) => {
+                self.handlers.insert(key.clone(), handler);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move the deadline of the delayed action for the given key, for example to debounce a
+    /// burst of requests into a single execution.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key associated with the handler.
+    /// * `new_delay` - How long to wait, starting now, before executing the handler.
+    ///
+    /// # Returns
+    ///
+    /// True if an action was waiting to run and has been rescheduled, false otherwise.
+    pub fn reschedule(&mut self, key: &K, new_delay: Duration) -> bool {
+        match self.pending.get(key) {
+            Some(pending_action) if pending_action.is_pending() => {
+                pending_action
+                    .deadline
+                    .send_replace(Instant::now() + new_delay);
+                true
+            }
+            Some(_) => {
+                self.pending.remove(key);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Check if an action for the given key is waiting to run.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key associated with the handler.
+    ///
+    /// # Returns
+    ///
+    /// True if an action scheduled with `schedule_handler_after` has not run yet, false
+    /// otherwise.
+    pub fn is_pending(&self, key: &K) -> bool {
+        self.pending
+            .get(key)
+            .is_some_and(|pending_action| pending_action.is_pending())
+    }
+
     /// Check if the `DelayedHandler` instance contains a handler for the given key.
     ///
     /// # Arguments
@@ -97,8 +276,7 @@ impl<K: Clone + Hash + PartialEq + Eq, T: Send + Sync + 'static> DelayedHandler<
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::{Arc, Mutex};
-    use tokio::time::{sleep, Duration};
+    use tokio::time::sleep;
 
     #[tokio::test]
     async fn test_delayed_handler() {
@@ -125,4 +303,97 @@ mod tests {
         let wrapped_bool = wrapped_bool.lock().unwrap();
         assert_eq!(*wrapped_bool, true);
     }
+
+    #[tokio::test]
+    async fn test_reschedule() {
+        let fired_at = Arc::new(Mutex::new(None));
+        let fired_at_c = fired_at.clone();
+        let mut delayed_handler: DelayedHandler<String, ()> = DelayedHandler::new(1);
+        let key = String::from("reschedule");
+        delayed_handler.add_handler(
+            &key,
+            Box::new(move |_| *fired_at_c.lock().unwrap() = Some(Instant::now())),
+        );
+
+        let start = Instant::now();
+        delayed_handler
+            .schedule_handler_after(&key, (), Duration::from_millis(300))
+            .unwrap();
+        assert!(delayed_handler.is_pending(&key));
+
+        sleep(Duration::from_millis(200)).await;
+        assert!(delayed_handler.reschedule(&key, Duration::from_millis(400)));
+
+        // The original deadline passes without the handler running.
+        sleep(Duration::from_millis(250)).await;
+        assert!(fired_at.lock().unwrap().is_none());
+
+        sleep(Duration::from_millis(500)).await;
+        let fired_at = fired_at.lock().unwrap().expect("handler did not run");
+        let elapsed = fired_at.duration_since(start);
+        assert!(elapsed >= Duration::from_millis(600), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(900), "{:?}", elapsed);
+
+        assert!(!delayed_handler.is_pending(&key));
+        assert!(!delayed_handler.reschedule(&key, Duration::from_millis(100)));
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let fired = Arc::new(Mutex::new(false));
+        let fired_c = fired.clone();
+        let mut delayed_handler: DelayedHandler<String, ()> = DelayedHandler::new(1);
+        let key = String::from("cancel");
+        delayed_handler.add_handler(&key, Box::new(move |_| *fired_c.lock().unwrap() = true));
+
+        delayed_handler
+            .schedule_handler_after(&key, (), Duration::from_millis(100))
+            .unwrap();
+        assert!(!delayed_handler.contains_handler_for_key(&key));
+        assert!(delayed_handler.cancel(&key));
+        assert!(!delayed_handler.cancel(&key));
+
+        // The handler of the cancelled action can be scheduled again.
+        assert!(delayed_handler.contains_handler_for_key(&key));
+
+        sleep(Duration::from_millis(300)).await;
+        assert!(!*fired.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_schedule_while_pending() {
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let fired_c = fired.clone();
+        let fired_d = fired.clone();
+        let mut delayed_handler: DelayedHandler<String, u32> = DelayedHandler::new(1);
+        let key = String::from("pending");
+        delayed_handler.add_handler(
+            &key,
+            Box::new(move |data| fired_c.lock().unwrap().push(data)),
+        );
+        delayed_handler
+            .schedule_handler_after(&key, 1, Duration::from_millis(100))
+            .unwrap();
+
+        // A second action for the same key is rejected and the first one still runs.
+        delayed_handler.add_handler(
+            &key,
+            Box::new(move |data| fired_d.lock().unwrap().push(data)),
+        );
+        assert!(matches!(
+            delayed_handler.schedule_handler_after(&key, 2, Duration::from_millis(100)),
+            Err(FoundationError::HandlerAlreadyPending)
+        ));
+        assert!(delayed_handler.contains_handler_for_key(&key));
+
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(*fired.lock().unwrap(), vec![1]);
+
+        // Once the first action has run, the key can be scheduled again.
+        delayed_handler
+            .schedule_handler_after(&key, 3, Duration::from_millis(10))
+            .unwrap();
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(*fired.lock().unwrap(), vec![1, 3]);
+    }
 }
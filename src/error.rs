@@ -7,8 +7,8 @@ use walkdir::Error as WalkdirError;
 
 #[derive(Error, Debug)]
 pub enum FoundationError {
-    #[error("Address Parse error: {0}")]
-    AddressParseError(std::net::AddrParseError),
+    #[error("Address Parse error")]
+    AddressParseError(#[source] std::net::AddrParseError),
 
     #[error("Copy failed: {0}")]
     CopyFailed(String),
@@ -16,20 +16,31 @@ pub enum FoundationError {
     #[error("File not found: {0:?}")]
     FileNotFound(std::path::PathBuf),
 
-    #[error("{0}")]
+    #[error(transparent)]
     GenericError(Box<dyn Error + Send + Sync + 'static>),
 
+    #[error("Glob pattern error")]
+    GlobPatternError(#[source] glob::PatternError),
+
     #[error("Handler not found")]
     HandlerNotFound,
 
+    #[error("Handler already pending")]
+    HandlerAlreadyPending,
+
+    /// The operation stopped because an interrupt was requested. The optional message describes
+    /// what was interrupted.
+    #[error("Operation interrupted{}", .0.as_ref().map(|context| format!(": {}", context)).unwrap_or_default())]
+    Interrupted(Option<String>),
+
     #[error("Could not convert {0} to {1}")]
     InvalidConversion(String, &'static str),
 
     #[error("Nothing implements {0}")]
     InvalidOperation(String),
 
-    #[error("IO error: {0}")]
-    IO(std::io::Error),
+    #[error("IO error")]
+    IO(#[source] std::io::Error),
 
     #[error("Join error: {0}")]
     JoinError(String),
@@ -37,17 +48,17 @@ pub enum FoundationError {
     #[error("MultiQueue error: {0}")]
     MultiQueueError(String),
 
-    #[error("Notify error: {0}")]
-    NotifyError(NotifyError),
+    #[error("Notify error")]
+    NotifyError(#[source] NotifyError),
 
     #[error("{0}")]
     OperationFailed(String),
 
-    #[error("Parse integer error: {0}")]
-    ParseIntError(std::num::ParseIntError),
+    #[error("Parse integer error")]
+    ParseIntError(#[source] std::num::ParseIntError),
 
-    #[error("Serde YAML error: {0}")]
-    SerdeYamlError(serde_yaml::Error),
+    #[error("Serde YAML error")]
+    SerdeYamlError(#[source] serde_yaml::Error),
 
     #[error("Sync error: {0}")]
     SyncError(String),
@@ -70,8 +81,8 @@ pub enum FoundationError {
     #[error("Unknown Wireless Standard: {0}")]
     UnknownWirelessStandard(String),
 
-    #[error("Walkdir error: {0}")]
-    WalkdirError(WalkdirError),
+    #[error("Walkdir error")]
+    WalkdirError(#[source] WalkdirError),
 }
 
 impl From<std::io::Error> for FoundationError {
@@ -86,6 +97,12 @@ impl From<Box<dyn std::error::Error + Send + Sync + 'static>> for FoundationErro
     }
 }
 
+impl From<glob::PatternError> for FoundationError {
+    fn from(error: glob::PatternError) -> Self {
+        FoundationError::GlobPatternError(error)
+    }
+}
+
 impl<T> From<MultiQueueError<T>> for FoundationError {
     fn from(error: MultiQueueError<T>) -> Self {
         FoundationError::MultiQueueError(error.to_string())
@@ -121,3 +138,50 @@ impl From<WalkdirError> for FoundationError {
         FoundationError::WalkdirError(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn test_interrupted() {
+        let error = FoundationError::Interrupted(None);
+        assert!(matches!(error, FoundationError::Interrupted(_)));
+        assert_eq!(error.to_string(), "Operation interrupted");
+
+        let error = FoundationError::Interrupted(Some("hashing /tmp/file".to_string()));
+        assert!(matches!(
+            &error,
+            FoundationError::Interrupted(Some(context)) if context == "hashing /tmp/file"
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Operation interrupted: hashing /tmp/file"
+        );
+    }
+
+    #[test]
+    fn test_source() {
+        let error = FoundationError::from(std::io::Error::new(ErrorKind::NotFound, "missing"));
+        // The message leaves the wrapped error to the source chain, so reports do not repeat it.
+        assert_eq!(error.to_string(), "IO error");
+        let source = error.source().unwrap();
+        let io_error = source.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io_error.kind(), ErrorKind::NotFound);
+        assert_eq!(io_error.to_string(), "missing");
+
+        let error = FoundationError::from("abc".parse::<u32>().unwrap_err());
+        assert!(error
+            .source()
+            .unwrap()
+            .downcast_ref::<std::num::ParseIntError>()
+            .is_some());
+
+        // Variants that only hold a message have no source.
+        assert!(FoundationError::OperationFailed("failed".to_string())
+            .source()
+            .is_none());
+        assert!(FoundationError::Interrupted(None).source().is_none());
+    }
+}
@@ -3,12 +3,18 @@
 
 use crate::error::FoundationError;
 use crate::threadcontroller::ThreadController;
+use futures::Stream;
 use log::{error, trace};
 use notify::{poll::PollWatcher, EventHandler, Watcher};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 use std::thread::Builder;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
 /// Configuration for the file system monitor.
 pub type Config = notify::Config;
@@ -28,20 +34,170 @@ pub type RecursiveMode = notify::RecursiveMode;
 /// Callback function that receives events from the file system monitor.
 type EventCallback = dyn FnMut(Event) + Send + Sync;
 
+/// The senders for the event streams of a file system monitor.
+type StreamSenders = Arc<Mutex<Vec<mpsc::UnboundedSender<Event>>>>;
+
+/// Wrap a callback so that each event is also sent to the event streams of the monitor.
+///
+/// # Arguments
+///
+/// * `callback` - The callback function that receives events from the file system monitor.
+/// * `senders` - The senders for the event streams of the monitor.
+fn deliver_to_streams(
+    mut callback: Box<EventCallback>,
+    senders: StreamSenders,
+) -> Box<EventCallback> {
+    Box::new(move |event: Event| {
+        // Forget the streams that have been dropped.
+        senders
+            .lock()
+            .unwrap()
+            .retain(|sender| sender.send(event.clone()).is_ok());
+        callback(event);
+    })
+}
+
+/// An asynchronous stream of the events from a file system monitor.
+///
+/// Dropping the last stream of a monitor stops the monitor thread.
+pub struct EventStream {
+    /// The receiver for the events sent to the stream.
+    receiver: mpsc::UnboundedReceiver<Event>,
+
+    /// The thread controller for the monitor thread.
+    thread_controller: Arc<ThreadController>,
+
+    /// The number of event streams of the monitor that have not been dropped.
+    live_streams: Arc<AtomicUsize>,
+}
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        if self.live_streams.fetch_sub(1, Ordering::SeqCst) == 1 {
+            trace!("FileSystemMonitor last event stream dropped, stopping monitor thread");
+            self.thread_controller.signal_stop();
+        }
+    }
+}
+
+/// Function that decides whether an event path passes a custom filter.
+type PathPredicate = dyn Fn(&Path) -> bool + Send + Sync;
+
+/// A filter that selects which events the file system monitor delivers.
+///
+/// An event matches a filter if any of the event's paths match.
+pub enum EventFilter {
+    /// Match paths with the given extension, such as `"yaml"`. The extension does not include
+    /// the leading dot.
+    Extension(String),
+
+    /// Match paths with a glob pattern, such as `"/etc/**/*.conf"`.
+    Glob(glob::Pattern),
+
+    /// Match paths for which the function returns true.
+    Custom(Box<PathPredicate>),
+}
+
+impl EventFilter {
+    /// Create a filter that matches paths with the given extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - The extension to match, without the leading dot.
+    pub fn extension(extension: &str) -> EventFilter {
+        EventFilter::Extension(extension.trim_start_matches('.').to_string())
+    }
+
+    /// Create a filter that matches paths with a glob pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The glob pattern to match.
+    ///
+    /// # Returns
+    ///
+    /// The filter on success and a `FoundationError` if the pattern is invalid.
+    pub fn glob(pattern: &str) -> Result<EventFilter, FoundationError> {
+        Ok(EventFilter::Glob(glob::Pattern::new(pattern)?))
+    }
+
+    /// Create a filter that matches paths for which the function returns true.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - The function that decides whether a path matches.
+    pub fn custom<F>(predicate: F) -> EventFilter
+    where
+        F: Fn(&Path) -> bool + Send + Sync + 'static,
+    {
+        EventFilter::Custom(Box::new(predicate))
+    }
+
+    /// Check whether a path matches the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to check.
+    pub fn matches_path(&self, path: &Path) -> bool {
+        match self {
+            EventFilter::Extension(extension) => path
+                .extension()
+                .is_some_and(|path_extension| path_extension == extension.as_str()),
+            EventFilter::Glob(pattern) => pattern.matches_path(path),
+            EventFilter::Custom(predicate) => predicate(path),
+        }
+    }
+
+    /// Check whether an event matches the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to check.
+    pub fn matches(&self, event: &Event) -> bool {
+        event.paths.iter().any(|path| self.matches_path(path))
+    }
+}
+
+/// The filters for a file system monitor.
+type EventFilters = Arc<Mutex<Vec<EventFilter>>>;
+
 /// The event handler for the file system monitor.
 struct MonitorEventHandler {
     /// The callback function that receives events from the file system monitor.
     callback: Box<EventCallback>,
+
+    /// The filters that select which events reach the callback.
+    filters: EventFilters,
 }
 
 impl MonitorEventHandler {
-    /// Create a new `MonitorEventHandler` with the given callback.
+    /// Create a new `MonitorEventHandler` with the given callback and filters.
     ///
     /// # Arguments
     ///
     /// * `callback` - The callback function that receives events from the file system monitor.
-    pub fn new(callback: Box<EventCallback>) -> MonitorEventHandler {
-        MonitorEventHandler { callback }
+    /// * `filters` - The filters that select which events reach the callback. If there are no
+    ///   filters, every event reaches the callback.
+    pub fn new(callback: Box<EventCallback>, filters: EventFilters) -> MonitorEventHandler {
+        MonitorEventHandler { callback, filters }
+    }
+
+    /// Check whether an event passes the filters.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to check.
+    fn accepts(&self, event: &Event) -> bool {
+        let filters = self.filters.lock().unwrap();
+        filters.is_empty() || filters.iter().any(|filter| filter.matches(event))
     }
 }
 
@@ -50,7 +206,9 @@ impl EventHandler for MonitorEventHandler {
         match event {
             Ok(event) => {
                 trace!("FileSystemMonitor Event: {:?}", event);
-                (self.callback)(event);
+                if self.accepts(&event) {
+                    (self.callback)(event);
+                }
             }
             Err(e) => {
                 error!("Error handling event: {}", e);
@@ -59,6 +217,73 @@ impl EventHandler for MonitorEventHandler {
     }
 }
 
+/// Coalesces events for the same paths and delivers them once the paths have been quiet for the
+/// debounce window.
+struct Debouncer {
+    /// How long the paths of an event must be quiet before the event is delivered.
+    window: Duration,
+
+    /// The most recent event for each set of paths and the time it arrived.
+    pending: Mutex<HashMap<Vec<PathBuf>, (Event, Instant)>>,
+
+    /// The callback function that receives the debounced events.
+    callback: Mutex<Box<EventCallback>>,
+}
+
+impl Debouncer {
+    /// Create a new `Debouncer` with the given window and callback.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - How long the paths of an event must be quiet before the event is delivered.
+    /// * `callback` - The callback function that receives the debounced events.
+    fn new(window: Duration, callback: Box<EventCallback>) -> Debouncer {
+        Debouncer {
+            window,
+            pending: Mutex::new(HashMap::new()),
+            callback: Mutex::new(callback),
+        }
+    }
+
+    /// Record an event, replacing any pending event for the same paths and restarting the
+    /// window for those paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to record.
+    fn add(&self, event: Event) {
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(event.paths.clone(), (event, Instant::now()));
+    }
+
+    /// Deliver every pending event whose paths have been quiet for the debounce window.
+    fn flush(&self) {
+        let now = Instant::now();
+        let mut ready = {
+            let mut pending = self.pending.lock().unwrap();
+            let ready_paths: Vec<Vec<PathBuf>> = pending
+                .iter()
+                .filter(|(_, (_, seen))| now.duration_since(*seen) >= self.window)
+                .map(|(paths, _)| paths.clone())
+                .collect();
+            ready_paths
+                .into_iter()
+                .filter_map(|paths| pending.remove(&paths))
+                .collect::<Vec<_>>()
+        };
+
+        // Deliver the events in the order they arrived.
+        ready.sort_by_key(|(_, seen)| *seen);
+        let mut callback = self.callback.lock().unwrap();
+        for (event, _) in ready {
+            trace!("FileSystemMonitor debounced event: {:?}", event);
+            callback(event);
+        }
+    }
+}
+
 /// The file system monitor object.
 #[derive(Clone)]
 pub struct FileSystemMonitor {
@@ -68,6 +293,18 @@ pub struct FileSystemMonitor {
 
     /// The poll watcher for the monitor thread.
     poll_watcher: Arc<Mutex<PollWatcher>>,
+
+    /// The debouncer for the monitor, if the monitor coalesces events.
+    debouncer: Option<Arc<Debouncer>>,
+
+    /// The senders for the event streams of the monitor.
+    stream_senders: StreamSenders,
+
+    /// The number of event streams of the monitor that have not been dropped.
+    live_streams: Arc<AtomicUsize>,
+
+    /// The filters that select which events the monitor delivers.
+    filters: EventFilters,
 }
 
 impl FileSystemMonitor {
@@ -80,17 +317,104 @@ impl FileSystemMonitor {
     pub fn new(
         callback: Box<EventCallback>,
         config: Config,
+    ) -> Result<FileSystemMonitor, FoundationError> {
+        FileSystemMonitor::create(callback, config, None)
+    }
+
+    /// Create a new `FileSystemMonitor` that coalesces events.
+    ///
+    /// A single logical change, such as an editor saving a file, can produce several events in
+    /// quick succession. The monitor holds each event until the event's paths have had no
+    /// further events for `window`, and then delivers only the most recent event for those
+    /// paths. Events still pending when the monitor stops are discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback function that receives the debounced events.
+    /// * `config` - The configuration for the file system monitor.
+    /// * `window` - How long the paths of an event must be quiet before the event is delivered.
+    pub fn with_debounce(
+        callback: Box<EventCallback>,
+        config: Config,
+        window: Duration,
+    ) -> Result<FileSystemMonitor, FoundationError> {
+        FileSystemMonitor::create(callback, config, Some(window))
+    }
+
+    /// Create a new `FileSystemMonitor`, optionally coalescing events.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback function that receives events from the file system monitor.
+    /// * `config` - The configuration for the file system monitor.
+    /// * `window` - The debounce window, or `None` to deliver every event as it arrives.
+    fn create(
+        callback: Box<EventCallback>,
+        config: Config,
+        window: Option<Duration>,
     ) -> Result<FileSystemMonitor, FoundationError> {
         let thread_controller = Arc::new(ThreadController::new(true));
-        let event_handler = MonitorEventHandler::new(callback);
+        let stream_senders: StreamSenders = Arc::new(Mutex::new(Vec::new()));
+        let callback = deliver_to_streams(callback, stream_senders.clone());
+
+        let (handler_callback, debouncer) = match window {
+            Some(window) => {
+                let debouncer = Arc::new(Debouncer::new(window, callback));
+                let handler_debouncer = debouncer.clone();
+                let handler_callback: Box<EventCallback> =
+                    Box::new(move |event: Event| handler_debouncer.add(event));
+                (handler_callback, Some(debouncer))
+            }
+            None => (callback, None),
+        };
+
+        let filters: EventFilters = Arc::new(Mutex::new(Vec::new()));
+        let event_handler = MonitorEventHandler::new(handler_callback, filters.clone());
         let poll_watcher = Arc::new(Mutex::new(PollWatcher::new(event_handler, config)?));
 
         Ok(FileSystemMonitor {
             thread_controller,
             poll_watcher,
+            debouncer,
+            stream_senders,
+            live_streams: Arc::new(AtomicUsize::new(0)),
+            filters,
         })
     }
 
+    /// Get an asynchronous stream of the events from the monitor.
+    ///
+    /// The stream receives the same events as the callback, after any debouncing, starting from
+    /// the time the stream is created. The stream lets callers await events in a `tokio::select!`
+    /// alongside other futures. A monitor can have several streams. Dropping the last of them
+    /// stops the monitor thread.
+    ///
+    /// # Returns
+    ///
+    /// An `EventStream` that yields the events from the monitor.
+    pub fn event_stream(&self) -> EventStream {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.stream_senders.lock().unwrap().push(sender);
+        self.live_streams.fetch_add(1, Ordering::SeqCst);
+        EventStream {
+            receiver,
+            thread_controller: self.thread_controller.clone(),
+            live_streams: self.live_streams.clone(),
+        }
+    }
+
+    /// Add a filter that selects which events the monitor delivers.
+    ///
+    /// Without filters the monitor delivers every event. Once filters are added, the monitor only
+    /// delivers events that match at least one of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The filter to add.
+    pub fn add_filter(&mut self, filter: EventFilter) {
+        self.filters.lock().unwrap().push(filter);
+    }
+
     /// Start the file system monitor thread.
     ///
     /// # Arguments
@@ -103,6 +427,13 @@ impl FileSystemMonitor {
     pub fn start(&mut self, timeout: u64) -> Result<(), FoundationError> {
         let controller = self.thread_controller.clone();
         let watcher = self.poll_watcher.clone();
+        let debouncer = self.debouncer.clone();
+
+        // When debouncing, wake up often enough to deliver events close to the end of the window.
+        let mut interval = Duration::from_millis(timeout);
+        if let Some(debouncer) = &debouncer {
+            interval = interval.min(debouncer.window);
+        }
 
         trace!("Starting FileSystemMonitor thread");
         Builder::new()
@@ -111,8 +442,12 @@ impl FileSystemMonitor {
                 while !controller.should_stop() {
                     watcher.lock().unwrap().poll()?;
 
+                    if let Some(debouncer) = &debouncer {
+                        debouncer.flush();
+                    }
+
                     // Sleep for a short time to avoid busy waiting.
-                    controller.wait_timeout(Duration::from_millis(timeout));
+                    controller.wait_timeout(interval);
                 }
                 Ok::<(), FoundationError>(())
             })?;
@@ -198,4 +533,122 @@ mod tests {
         std::fs::remove_file(tmp_file).unwrap();
         monitor.stop();
     }
+
+    #[test]
+    fn test_filter() {
+        let event_paths = Arc::new(Mutex::new(Vec::new()));
+        let paths = event_paths.clone();
+        let callback = Box::new(move |event: Event| {
+            println!("Event: {:?}", event);
+            paths.lock().unwrap().extend(event.paths);
+        });
+        let config = Config::default();
+        let mut monitor = FileSystemMonitor::new(callback, config).unwrap();
+        monitor.add_filter(EventFilter::extension("yaml"));
+        let watch_dir = std::env::temp_dir().join(format!(
+            "filesystem_monitor_filter_test_{}",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&watch_dir).unwrap();
+        monitor.watch(&watch_dir, RecursiveMode::Recursive).unwrap();
+        monitor.start(100).unwrap();
+
+        std::fs::write(watch_dir.join("notes.txt"), "test").unwrap();
+        std::fs::write(watch_dir.join("config.yaml"), "key: value").unwrap();
+        sleep(Duration::from_secs(1));
+        monitor.stop();
+
+        let event_paths = event_paths.lock().unwrap();
+        assert!(event_paths.contains(&watch_dir.join("config.yaml")));
+        assert!(event_paths.iter().all(|path| path
+            .extension()
+            .is_some_and(|extension| extension == "yaml")));
+        std::fs::remove_dir_all(&watch_dir).unwrap();
+    }
+
+    #[test]
+    fn test_filter_matching() {
+        let event = Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(PathBuf::from("/etc/hostapd/hostapd.conf"));
+
+        assert!(EventFilter::extension("conf").matches(&event));
+        assert!(EventFilter::extension(".conf").matches(&event));
+        assert!(!EventFilter::extension("yaml").matches(&event));
+        assert!(EventFilter::glob("/etc/**/*.conf").unwrap().matches(&event));
+        assert!(!EventFilter::glob("/var/**").unwrap().matches(&event));
+        assert!(EventFilter::custom(|path| path.starts_with("/etc")).matches(&event));
+        assert!(EventFilter::glob("[").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        use futures::StreamExt;
+
+        let callback = Box::new(|event: Event| {
+            println!("Event: {:?}", event);
+        });
+        let config = Config::default();
+        let mut monitor = FileSystemMonitor::new(callback, config).unwrap();
+        let watch_dir = std::env::temp_dir().join(format!(
+            "filesystem_monitor_stream_test_{}",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&watch_dir).unwrap();
+        monitor.watch(&watch_dir, RecursiveMode::Recursive).unwrap();
+        let mut stream = monitor.event_stream();
+        let other_stream = monitor.event_stream();
+        monitor.start(100).unwrap();
+
+        let tmp_file = watch_dir.join("created.txt");
+        std::fs::write(&tmp_file, "test").unwrap();
+
+        let created = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = stream.next().await {
+                if event.kind.is_create() && event.paths.contains(&tmp_file) {
+                    return true;
+                }
+            }
+            false
+        })
+        .await
+        .unwrap_or(false);
+        assert!(created);
+
+        // The monitor keeps running until its last stream is dropped.
+        drop(other_stream);
+        assert!(!monitor.thread_controller.should_stop());
+        drop(stream);
+        assert!(monitor.thread_controller.should_stop());
+        std::fs::remove_dir_all(&watch_dir).unwrap();
+    }
+
+    #[test]
+    fn test_debounce() {
+        let event_count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let count = event_count.clone();
+        let callback = Box::new(move |event: Event| {
+            println!("Event: {:?}", event);
+            count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+        let config = Config::default();
+        let mut monitor =
+            FileSystemMonitor::with_debounce(callback, config, Duration::from_millis(300)).unwrap();
+        monitor.start(10).unwrap();
+
+        // Feed the events straight to the debouncer, the way the poll watcher would deliver them
+        // for three rapid saves of the same file.
+        let debouncer = monitor.debouncer.clone().unwrap();
+        let tmp_file = std::env::temp_dir().join("filesystem_monitor_debounce_test.txt");
+        for _ in 0..3 {
+            let event = Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
+                .add_path(tmp_file.clone());
+            debouncer.add(event);
+            sleep(Duration::from_millis(20));
+        }
+        assert_eq!(event_count.load(std::sync::atomic::Ordering::Relaxed), 0);
+
+        sleep(Duration::from_millis(600));
+        assert_eq!(event_count.load(std::sync::atomic::Ordering::Relaxed), 1);
+        monitor.stop();
+    }
 }
@@ -1,6 +1,14 @@
+pub mod atomic_write;
+pub mod clear_dir;
 pub mod copy;
+pub mod disk_usage;
+pub mod move_path;
 
-pub use copy::copy;
+pub use atomic_write::write_atomic;
+pub use clear_dir::clear_dir;
+pub use copy::{copy, copy_dir};
+pub use disk_usage::{disk_usage, DiskUsage};
+pub use move_path::move_path;
 
 cfg_if! {
     if #[cfg(target_os = "linux")] {
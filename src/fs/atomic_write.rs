@@ -0,0 +1,137 @@
+use crate::error::FoundationError;
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Atomically replace the contents of a file.
+///
+/// The function writes the contents to a temporary file in the same directory as the target,
+/// syncs the temporary file to disk, sets its permissions, and then renames it over the target.
+/// Readers of the target see either the old contents or the new contents, never a partial write,
+/// even if the process or the system crashes part way through.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path representing the file to write.
+/// * `contents` - The new contents of the file.
+/// * `mode` - The Unix permission bits for the file, such as `0o600`. If `None`, the file keeps
+///   the permissions of the file it replaces, or gets the default permissions for a new file.
+///
+/// # Returns
+///
+/// A Result containing `()`. If the file is successfully written, the result will be `Ok(())`.
+/// If an error occurs, the result will be `Err(FoundationError)` and the target is unchanged.
+pub fn write_atomic(
+    path: &Path,
+    contents: &[u8],
+    mode: Option<u32>,
+) -> Result<(), FoundationError> {
+    let file_name = path.file_name().ok_or_else(|| {
+        FoundationError::OperationFailed(format!("{} does not name a file", path.display()))
+    })?;
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    // The temporary file must be in the same directory as the target so that the rename does not
+    // cross filesystems.
+    let temp_path = directory.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        Uuid::new_v4()
+    ));
+
+    let result = write_and_rename(path, &temp_path, &directory, contents, mode);
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+// Write the contents to the temporary file and rename it over the target.
+fn write_and_rename(
+    path: &Path,
+    temp_path: &Path,
+    directory: &Path,
+    contents: &[u8],
+    mode: Option<u32>,
+) -> Result<(), FoundationError> {
+    let mut temp_file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp_path)?;
+    temp_file.write_all(contents)?;
+
+    let permissions = match mode {
+        Some(mode) => Some(std::fs::Permissions::from_mode(mode)),
+        None => std::fs::metadata(path)
+            .ok()
+            .map(|metadata| metadata.permissions()),
+    };
+    if let Some(permissions) = permissions {
+        temp_file.set_permissions(permissions)?;
+    }
+
+    temp_file.sync_all()?;
+    drop(temp_file);
+
+    std::fs::rename(temp_path, path)?;
+
+    // Sync the directory so that the rename itself survives a crash.
+    std::fs::File::open(directory)?.sync_all()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_directory() -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("write_atomic_test_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let directory = test_directory();
+        let path = directory.join("config.yaml");
+
+        write_atomic(&path, b"first version\n", Some(0o600)).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first version\n");
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        // A shorter write replaces the whole file and keeps the permissions.
+        write_atomic(&path, b"second\n", None).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second\n");
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomic_leaves_no_temp_files() {
+        let directory = test_directory();
+        let path = directory.join("settings.conf");
+
+        write_atomic(&path, b"contents", None).unwrap();
+        write_atomic(&path, b"new contents", Some(0o644)).unwrap();
+
+        let entries: Vec<String> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(entries, vec!["settings.conf".to_string()]);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
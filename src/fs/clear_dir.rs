@@ -0,0 +1,186 @@
+use crate::error::FoundationError;
+use std::path::Path;
+
+/// Remove everything inside a directory but leave the directory itself.
+///
+/// Files, subdirectories, and symbolic links inside the directory are all removed. Symbolic
+/// links are removed without following them. The directory keeps its permissions and
+/// ownership. On Windows, read-only files have their read-only attribute cleared so that they
+/// can be removed.
+///
+/// `path` itself must not be a symbolic link, so that clearing a link never empties the
+/// directory it points to.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path representing the directory to clear.
+///
+/// # Returns
+///
+/// A Result containing `()`. If the directory is successfully cleared, the result will be
+/// `Ok(())`. If an error occurs, the result will be `Err(FoundationError)`.
+pub fn clear_dir(path: &Path) -> Result<(), FoundationError> {
+    let metadata = match path.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(_) => return Err(FoundationError::FileNotFound(path.to_path_buf())),
+    };
+
+    if metadata.file_type().is_symlink() {
+        return Err(FoundationError::OperationFailed(format!(
+            "{} is a symbolic link",
+            path.display()
+        )));
+    }
+
+    if !metadata.is_dir() {
+        return Err(FoundationError::OperationFailed(format!(
+            "{} is not a directory",
+            path.display()
+        )));
+    }
+
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let child = entry.path();
+
+        // The entry's file type does not follow symbolic links, so a link to a directory is
+        // removed rather than cleared. `remove_dir_all` does not follow links either, and on
+        // Windows it also removes read-only files.
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            std::fs::remove_dir_all(&child)?;
+        } else {
+            remove_non_directory(&child, &file_type)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Remove a file or symbolic link.
+fn remove_non_directory(path: &Path, file_type: &std::fs::FileType) -> Result<(), FoundationError> {
+    cfg_if! {
+        if #[cfg(windows)] {
+            use std::os::windows::fs::FileTypeExt;
+
+            // Windows removes a symbolic link to a directory as a directory.
+            let remove = |path: &Path| if file_type.is_symlink_dir() {
+                std::fs::remove_dir(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+
+            match remove(path) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                    let mut permissions = std::fs::symlink_metadata(path)?.permissions();
+                    if !permissions.readonly() {
+                        return Err(e.into());
+                    }
+                    #[allow(clippy::permissions_set_readonly_false)]
+                    permissions.set_readonly(false);
+                    std::fs::set_permissions(path, permissions)?;
+                    Ok(remove(path)?)
+                }
+                Err(e) => Err(e.into()),
+            }
+        } else {
+            let _ = file_type;
+            Ok(std::fs::remove_file(path)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn test_directory() -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("clear_dir_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_clear_dir() {
+        let directory = test_directory();
+        let outside = test_directory();
+        std::fs::write(outside.join("kept.txt"), "kept").unwrap();
+
+        std::fs::write(directory.join("a.txt"), "a").unwrap();
+        std::fs::create_dir_all(directory.join("sub").join("nested")).unwrap();
+        std::fs::write(directory.join("sub").join("nested").join("b.txt"), "b").unwrap();
+
+        let read_only = directory.join("sub").join("read_only.txt");
+        std::fs::write(&read_only, "read only").unwrap();
+        let mut permissions = std::fs::metadata(&read_only).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&read_only, permissions).unwrap();
+
+        cfg_if! {
+            if #[cfg(unix)] {
+                use std::os::unix::fs::PermissionsExt;
+
+                std::os::unix::fs::symlink(&outside, directory.join("link")).unwrap();
+                std::fs::set_permissions(&directory, std::fs::Permissions::from_mode(0o750))
+                    .unwrap();
+            }
+        }
+
+        clear_dir(&directory).unwrap();
+        assert!(directory.is_dir());
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+
+        // Links are removed without clearing what they point to.
+        assert_eq!(
+            std::fs::read_to_string(outside.join("kept.txt")).unwrap(),
+            "kept"
+        );
+
+        cfg_if! {
+            if #[cfg(unix)] {
+                assert_eq!(
+                    std::fs::metadata(&directory).unwrap().permissions().mode() & 0o777,
+                    0o750
+                );
+            }
+        }
+
+        std::fs::remove_dir_all(&directory).unwrap();
+        std::fs::remove_dir_all(&outside).unwrap();
+    }
+
+    #[test]
+    fn test_clear_dir_errors() {
+        let directory = test_directory();
+        let result = clear_dir(&directory.join("missing"));
+        assert!(matches!(result, Err(FoundationError::FileNotFound(_))));
+
+        let file = directory.join("file.txt");
+        std::fs::write(&file, "contents").unwrap();
+        assert!(matches!(
+            clear_dir(&file),
+            Err(FoundationError::OperationFailed(_))
+        ));
+
+        cfg_if! {
+            if #[cfg(unix)] {
+                // Clearing a link to a directory fails and leaves the target alone.
+                let target = test_directory();
+                std::fs::write(target.join("kept.txt"), "kept").unwrap();
+                let link = directory.join("link");
+                std::os::unix::fs::symlink(&target, &link).unwrap();
+                assert!(matches!(
+                    clear_dir(&link),
+                    Err(FoundationError::OperationFailed(_))
+                ));
+                assert!(target.join("kept.txt").exists());
+                std::fs::remove_dir_all(&target).unwrap();
+            }
+        }
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
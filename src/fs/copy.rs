@@ -3,7 +3,7 @@ use crate::progressmeter::ProgressMeter;
 use nix::unistd::fsync;
 use std::io::{Read, Write};
 use std::os::fd::AsRawFd;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 const BLOCKSIZE: libc::size_t = 8388608;
@@ -76,3 +76,194 @@ pub fn copy(
 
     Ok(())
 }
+
+/// Synchronously copy a directory tree from one location to another.
+///
+/// The function recreates the directories of the source tree under the destination, copies the
+/// contents of each file, recreates symbolic links, and preserves the permissions of the files and
+/// directories.
+///
+/// # Arguments
+///
+/// * `src` - A reference to a Path representing the source directory.
+/// * `dest` - A reference to a Path representing the destination directory.
+/// * `overwrite` - If the destination already exists, merge the source tree into it, replacing
+///   files with the same names, when `true`, or fail when `false`.
+/// * `meter` - An optional Arc<Mutex<ProgressMeter>>. If provided, the ProgressMeter will be
+///   updated with the number of bytes copied.
+///
+/// # Returns
+///
+/// A Result containing `()`. If the directory is successfully copied, the result will be `Ok(())`.
+/// If an error occurs, the result will be `Err(FoundationError)`.
+pub fn copy_dir(
+    src: &Path,
+    dest: &Path,
+    overwrite: bool,
+    meter: Option<Arc<Mutex<ProgressMeter>>>,
+) -> Result<(), FoundationError> {
+    if !src.is_dir() {
+        return Err(FoundationError::FileNotFound(src.to_path_buf()));
+    }
+
+    // Copying a directory into itself would walk into the directories we create and never end.
+    if absolute_path(dest)?.starts_with(src.canonicalize()?) {
+        return Err(FoundationError::CopyFailed(format!(
+            "{} is inside {}",
+            dest.display(),
+            src.display()
+        )));
+    }
+
+    if dest.exists() && !overwrite {
+        return Err(FoundationError::CopyFailed(format!(
+            "{} already exists",
+            dest.display()
+        )));
+    }
+
+    // We set the directory permissions after copying everything, because a read-only source
+    // directory would otherwise stop us from copying its contents.
+    let mut directories: Vec<(PathBuf, std::fs::Permissions)> = Vec::new();
+
+    for entry in walkdir::WalkDir::new(src) {
+        let entry = entry?;
+        let relative = entry
+            .path()
+            .strip_prefix(src)
+            .map_err(|e| FoundationError::CopyFailed(e.to_string()))?;
+        let target = dest.join(relative);
+        let file_type = entry.file_type();
+
+        if file_type.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            directories.push((target, entry.metadata()?.permissions()));
+        } else if file_type.is_symlink() {
+            let link = std::fs::read_link(entry.path())?;
+            if target.symlink_metadata().is_ok() {
+                std::fs::remove_file(&target)?;
+            }
+            std::os::unix::fs::symlink(link, &target)?;
+        } else {
+            copy(entry.path(), &target, meter.clone())?;
+            std::fs::set_permissions(&target, entry.metadata()?.permissions())?;
+        }
+    }
+
+    // Set the deepest directories first.
+    for (directory, permissions) in directories.into_iter().rev() {
+        std::fs::set_permissions(&directory, permissions)?;
+    }
+
+    Ok(())
+}
+
+/// Resolve `path` to an absolute path without symbolic links, even if the path does not exist yet.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path that may or may not exist.
+///
+/// # Returns
+///
+/// A Result containing the canonical form of the nearest existing ancestor of `path` joined with
+/// the components of `path` that do not exist yet.
+fn absolute_path(path: &Path) -> Result<PathBuf, FoundationError> {
+    let path = std::path::absolute(path)?;
+    for ancestor in path.ancestors() {
+        if ancestor.exists() {
+            let remainder = path
+                .strip_prefix(ancestor)
+                .map_err(|e| FoundationError::CopyFailed(e.to_string()))?;
+            return Ok(ancestor.canonicalize()?.join(remainder));
+        }
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_copy_dir() {
+        let root = std::env::temp_dir().join(format!("copy_dir_test_{}", uuid::Uuid::new_v4()));
+        let src = root.join("src");
+        let dest = root.join("dest");
+
+        std::fs::create_dir_all(src.join("sub").join("deeper")).unwrap();
+        std::fs::write(src.join("a.txt"), "first file").unwrap();
+        std::fs::write(src.join("sub").join("b.txt"), "second file contents").unwrap();
+        std::fs::write(src.join("sub").join("deeper").join("c.txt"), "").unwrap();
+        std::fs::set_permissions(src.join("a.txt"), std::fs::Permissions::from_mode(0o640))
+            .unwrap();
+        std::os::unix::fs::symlink("a.txt", src.join("link.txt")).unwrap();
+
+        let total_bytes = ("first file".len() + "second file contents".len()) as u64;
+        let meter = Arc::new(Mutex::new(ProgressMeter::new_with_notifier_and_size(
+            Box::new(|_| {}),
+            total_bytes,
+        )));
+
+        copy_dir(&src, &dest, false, Some(meter.clone())).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(dest.join("a.txt")).unwrap(),
+            "first file"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dest.join("sub").join("b.txt")).unwrap(),
+            "second file contents"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dest.join("sub").join("deeper").join("c.txt")).unwrap(),
+            ""
+        );
+        assert_eq!(
+            std::fs::metadata(dest.join("a.txt"))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o640
+        );
+        assert_eq!(
+            std::fs::read_link(dest.join("link.txt")).unwrap(),
+            PathBuf::from("a.txt")
+        );
+        assert_eq!(meter.lock().unwrap().current(), total_bytes);
+
+        // Copying again fails unless we ask to overwrite.
+        assert!(copy_dir(&src, &dest, false, None).is_err());
+        std::fs::write(src.join("a.txt"), "changed").unwrap();
+        copy_dir(&src, &dest, true, None).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dest.join("a.txt")).unwrap(),
+            "changed"
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_copy_dir_into_itself() {
+        let root = std::env::temp_dir().join(format!("copy_dir_self_{}", uuid::Uuid::new_v4()));
+        let src = root.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("a.txt"), "first file").unwrap();
+
+        assert!(copy_dir(&src, &src.join("nested").join("copy"), false, None).is_err());
+        assert!(copy_dir(&src, &src, true, None).is_err());
+        assert!(copy_dir(&src, &root.join(".").join("src"), true, None).is_err());
+        assert!(!src.join("nested").exists());
+
+        // A sibling whose name merely starts with the source name is fine.
+        copy_dir(&src, &root.join("src2"), false, None).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("src2").join("a.txt")).unwrap(),
+            "first file"
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,142 @@
+use crate::bytes::{normalize_byte_size, ByteMetricBase};
+use crate::error::FoundationError;
+use std::path::Path;
+
+/// The `DiskUsage` struct holds the space usage of the filesystem containing a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// The size of the filesystem in bytes.
+    pub total: u64,
+
+    /// The number of bytes in use on the filesystem.
+    pub used: u64,
+
+    /// The number of bytes available to unprivileged users. This can be less than
+    /// `total - used` because some filesystems reserve space for the superuser.
+    pub available: u64,
+}
+
+impl DiskUsage {
+    /// Get a human-readable description of the disk usage.
+    ///
+    /// # Arguments
+    ///
+    /// * `metric_base` - The base to use when converting bytes to a human-readable format.
+    ///
+    /// # Returns
+    ///
+    /// A string such as `"12.00 Gb used of 100.00 Gb (80.00 Gb available)"`.
+    pub fn to_human_readable(&self, metric_base: ByteMetricBase) -> String {
+        format!(
+            "{} used of {} ({} available)",
+            normalize_byte_size(self.used as u128, metric_base),
+            normalize_byte_size(self.total as u128, metric_base),
+            normalize_byte_size(self.available as u128, metric_base)
+        )
+    }
+}
+
+/// Get the space usage of the filesystem containing a path.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path on the filesystem to query.
+///
+/// # Returns
+///
+/// A Result containing the `DiskUsage` for the filesystem. If an error occurs, the result will
+/// be `Err(FoundationError)`.
+pub fn disk_usage(path: &Path) -> Result<DiskUsage, FoundationError> {
+    if !path.exists() {
+        return Err(FoundationError::FileNotFound(path.to_path_buf()));
+    }
+
+    cfg_if! {
+        if #[cfg(unix)] {
+            let stats = nix::sys::statvfs::statvfs(path).map_err(std::io::Error::from)?;
+            let fragment_size = stats.fragment_size() as u64;
+            let total = stats.blocks() as u64 * fragment_size;
+            let free = stats.blocks_free() as u64 * fragment_size;
+            let available = stats.blocks_available() as u64 * fragment_size;
+
+            Ok(DiskUsage {
+                total,
+                used: total.saturating_sub(free),
+                available,
+            })
+        } else if #[cfg(windows)] {
+            use std::os::windows::ffi::OsStrExt;
+            use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+            let wide_path: Vec<u16> = path
+                .as_os_str()
+                .encode_wide()
+                .chain(std::iter::once(0))
+                .collect();
+            let mut available: u64 = 0;
+            let mut total: u64 = 0;
+            let mut free: u64 = 0;
+
+            // SAFETY: wide_path is a null terminated UTF-16 string and the output pointers
+            // point to valid u64 values for the duration of the call.
+            let succeeded = unsafe {
+                GetDiskFreeSpaceExW(wide_path.as_ptr(), &mut available, &mut total, &mut free)
+            };
+            if succeeded == 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            Ok(DiskUsage {
+                total,
+                used: total.saturating_sub(free),
+                available,
+            })
+        } else {
+            Err(FoundationError::OperationFailed(
+                "disk usage is not supported on this platform".to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_usage() {
+        let usage = disk_usage(Path::new("/")).unwrap();
+        assert!(usage.total > 0);
+        assert!(usage.available <= usage.total);
+        assert!(usage.used <= usage.total);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_disk_usage_windows() {
+        let usage = disk_usage(Path::new("C:\\")).unwrap();
+        assert!(usage.total > 0);
+        assert!(usage.available <= usage.total);
+        assert!(usage.used <= usage.total);
+    }
+
+    #[test]
+    fn test_disk_usage_missing_path() {
+        let result = disk_usage(Path::new("/this/path/does/not/exist"));
+        assert!(matches!(result, Err(FoundationError::FileNotFound(_))));
+    }
+
+    #[test]
+    fn test_to_human_readable() {
+        let usage = DiskUsage {
+            total: 2048,
+            used: 1024,
+            available: 512,
+        };
+        assert_eq!(
+            usage.to_human_readable(ByteMetricBase::Metric),
+            "1.00 Kb used of 2.00 Kb (512.00 bytes available)"
+        );
+    }
+}
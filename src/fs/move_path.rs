@@ -0,0 +1,142 @@
+use crate::error::FoundationError;
+use crate::fs::copy::{copy, copy_dir};
+use std::path::Path;
+
+/// Move a file or directory from one location to another.
+///
+/// The function first tries to rename the source to the destination. A rename cannot cross
+/// filesystems, so if the rename fails because the source and destination are on different
+/// devices, the function falls back to copying the source to the destination, preserving
+/// permissions, and then removing the source.
+///
+/// # Arguments
+///
+/// * `src` - A reference to a Path representing the file or directory to move.
+/// * `dest` - A reference to a Path representing the new location.
+///
+/// # Returns
+///
+/// A Result containing `()`. If the path is successfully moved, the result will be `Ok(())`.
+/// If an error occurs, the result will be `Err(FoundationError)`.
+pub fn move_path(src: &Path, dest: &Path) -> Result<(), FoundationError> {
+    move_path_with_rename(src, dest, |from, to| std::fs::rename(from, to))
+}
+
+// Move a path using the given rename function. Tests use this to simulate a cross-device rename.
+fn move_path_with_rename<F>(src: &Path, dest: &Path, rename: F) -> Result<(), FoundationError>
+where
+    F: Fn(&Path, &Path) -> std::io::Result<()>,
+{
+    let metadata = match src.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(_) => return Err(FoundationError::FileNotFound(src.to_path_buf())),
+    };
+
+    match rename(src, dest) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let file_type = metadata.file_type();
+    if file_type.is_symlink() {
+        let link = std::fs::read_link(src)?;
+        std::os::unix::fs::symlink(link, dest)?;
+        std::fs::remove_file(src)?;
+    } else if file_type.is_dir() {
+        copy_dir(src, dest, false, None)?;
+        std::fs::remove_dir_all(src)?;
+    } else {
+        copy(src, dest, None)?;
+        std::fs::set_permissions(dest, metadata.permissions())?;
+        std::fs::remove_file(src)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    fn test_directory() -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("move_path_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn cross_device_rename(_: &Path, _: &Path) -> std::io::Result<()> {
+        Err(std::io::Error::from_raw_os_error(libc::EXDEV))
+    }
+
+    #[test]
+    fn test_move_path() {
+        let directory = test_directory();
+        let src = directory.join("a.txt");
+        let dest = directory.join("b.txt");
+        std::fs::write(&src, "contents").unwrap();
+
+        move_path(&src, &dest).unwrap();
+        assert!(!src.exists());
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "contents");
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_move_file_across_devices() {
+        let directory = test_directory();
+        let src = directory.join("settings.yaml");
+        let dest = directory.join("moved.yaml");
+        std::fs::write(&src, "network: {}\n").unwrap();
+        std::fs::set_permissions(&src, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+        move_path_with_rename(&src, &dest, cross_device_rename).unwrap();
+        assert!(!src.exists());
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "network: {}\n");
+        assert_eq!(
+            std::fs::metadata(&dest).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_move_directory_across_devices() {
+        let directory = test_directory();
+        let src = directory.join("src");
+        let dest = directory.join("dest");
+        std::fs::create_dir_all(src.join("sub")).unwrap();
+        std::fs::write(src.join("sub").join("c.txt"), "nested").unwrap();
+        std::fs::set_permissions(src.join("sub"), std::fs::Permissions::from_mode(0o750)).unwrap();
+
+        move_path_with_rename(&src, &dest, cross_device_rename).unwrap();
+        assert!(!src.exists());
+        assert_eq!(
+            std::fs::read_to_string(dest.join("sub").join("c.txt")).unwrap(),
+            "nested"
+        );
+        assert_eq!(
+            std::fs::metadata(dest.join("sub"))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o750
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_move_path_missing_source() {
+        let directory = test_directory();
+        let result = move_path(&directory.join("missing"), &directory.join("dest"));
+        assert!(matches!(result, Err(FoundationError::FileNotFound(_))));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -3,9 +3,11 @@
 //! functions use the `tokio` crate to perform the asynchronous operations.
 
 use crate::error::FoundationError;
+use crate::interrupter::Interrupter;
 use crate::progressmeter::ProgressMeter;
+use sha2::{Digest, Sha256, Sha512};
 use std::fs::File as StdFile;
-use std::io::BufReader as StdBufReader;
+use std::io::{BufReader as StdBufReader, Read};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use tokio::{
@@ -17,21 +19,237 @@ pub use blake3::Hasher;
 
 const CHUNK_SIZE: usize = 1024 * 1024;
 
+/// The `HashAlgorithm` enum selects the hash function used to hash files and directories.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgorithm {
+    /// The BLAKE3 hash function. The functions in this module use BLAKE3 unless told otherwise.
+    Blake3,
+
+    /// The SHA-256 hash function.
+    Sha256,
+
+    /// The SHA-512 hash function.
+    Sha512,
+}
+
+/// The `AlgorithmHasher` enum wraps the hasher of each `HashAlgorithm` behind one interface.
+enum AlgorithmHasher {
+    // The BLAKE3 hasher is much larger than the SHA-2 hashers, so we keep it on the heap.
+    Blake3(Box<Hasher>),
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl AlgorithmHasher {
+    /// Create a new hasher for the given algorithm.
+    fn new(algorithm: HashAlgorithm) -> AlgorithmHasher {
+        match algorithm {
+            HashAlgorithm::Blake3 => AlgorithmHasher::Blake3(Box::new(Hasher::new())),
+            HashAlgorithm::Sha256 => AlgorithmHasher::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => AlgorithmHasher::Sha512(Sha512::new()),
+        }
+    }
+
+    /// Add data to the hash.
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            AlgorithmHasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            AlgorithmHasher::Sha256(hasher) => hasher.update(data),
+            AlgorithmHasher::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Finish the hash and return it as a lowercase hex string.
+    fn finalize(self) -> String {
+        match self {
+            AlgorithmHasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+            AlgorithmHasher::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            AlgorithmHasher::Sha512(hasher) => format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+impl std::io::Write for AlgorithmHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The `SymlinkPolicy` enum controls how the directory hashing functions treat symbolic links.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymlinkPolicy {
+    /// Follow symbolic links and hash whatever they point to. Symbolic links that form a cycle
+    /// cause the hash to fail.
+    Follow,
+
+    /// Hash the target path stored in the symbolic link instead of following it.
+    HashTarget,
+
+    /// Hash the path of the symbolic link itself instead of following it.
+    HashLinkPath,
+
+    /// Ignore symbolic links.
+    Skip,
+}
+
 /// Get the hash of a file.
 ///
 /// # Arguments
 ///
 /// * `path` - A reference to a Path.
+/// * `interrupter` - An optional Interrupter to cancel hashing a large file. The function checks
+///   the interrupter between chunk reads.
 ///
 /// # Returns
 ///
 /// A Result containing a string. If the file is successfully hashed, the result will be `Ok(String)`.
-pub fn get_hash_for_file(path: &Path) -> Result<String, FoundationError> {
+/// If the interrupter requests an interrupt first, the result will be `FoundationError::Interrupted`.
+pub fn get_hash_for_file(
+    path: &Path,
+    interrupter: Option<&Interrupter>,
+) -> Result<String, FoundationError> {
     let file = StdFile::open(path)?;
-    let mut reader = StdBufReader::new(file);
-    let mut hasher = Hasher::new();
-    std::io::copy(&mut reader, &mut hasher)?;
-    Ok(hasher.finalize().to_hex().to_string())
+    hash_reader(file, HashAlgorithm::Blake3, None, interrupter)
+}
+
+/// Get the hash of a file using the given hash algorithm.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path.
+/// * `algorithm` - The hash algorithm to use.
+/// * `meter` - An optional ProgressMeter to increment by the number of bytes read.
+///
+/// # Returns
+///
+/// A Result containing the hash of the file contents as a lowercase hex String or a
+/// FoundationError if an error occurs.
+pub fn get_hash_for_file_with_algorithm(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    meter: Option<Arc<Mutex<ProgressMeter>>>,
+) -> Result<String, FoundationError> {
+    let file = StdFile::open(path)?;
+    hash_reader(file, algorithm, meter, None)
+}
+
+/// Check whether the hash of a file matches an expected hash. The comparison ignores the case of
+/// the hex digits and takes the same amount of time no matter where the hashes differ, so the
+/// function is safe to use for integrity checks.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path.
+/// * `expected` - The expected hash as a hex string.
+/// * `algorithm` - The hash algorithm that produced the expected hash.
+///
+/// # Returns
+///
+/// A Result containing true if the hashes match, or `FoundationError::IO` if the file cannot be
+/// read.
+pub fn verify_file_hash(
+    path: &Path,
+    expected: &str,
+    algorithm: HashAlgorithm,
+) -> Result<bool, FoundationError> {
+    let actual = get_hash_for_file_with_algorithm(path, algorithm, None)?;
+    Ok(hashes_match(&actual, expected))
+}
+
+/// Compare two hex hash strings without regard to case in constant time.
+///
+/// # Arguments
+///
+/// * `actual` - The computed hash.
+/// * `expected` - The expected hash.
+///
+/// # Returns
+///
+/// True if the hashes match.
+fn hashes_match(actual: &str, expected: &str) -> bool {
+    // The length of a hash is not a secret, so we can return early when the lengths differ.
+    if actual.len() != expected.len() {
+        return false;
+    }
+
+    let difference = actual
+        .bytes()
+        .zip(expected.bytes())
+        .fold(0u8, |difference, (a, b)| {
+            difference | (a.to_ascii_lowercase() ^ b.to_ascii_lowercase())
+        });
+    difference == 0
+}
+
+/// Get the hash of the data read from a reader, such as a socket or an in-memory buffer. The
+/// function reads until the reader reaches the end of its data.
+///
+/// # Arguments
+///
+/// * `reader` - The reader to hash the data from.
+/// * `meter` - An optional ProgressMeter to increment by the number of bytes read.
+///
+/// # Returns
+///
+/// A Result containing the hash of the data in a String or a FoundationError if an error occurs.
+pub fn get_hash_for_reader<R: Read>(
+    reader: R,
+    meter: Option<Arc<Mutex<ProgressMeter>>>,
+) -> Result<String, FoundationError> {
+    hash_reader(reader, HashAlgorithm::Blake3, meter, None)
+}
+
+/// Hash the data read from a reader in chunks.
+///
+/// # Arguments
+///
+/// * `reader` - The reader to hash the data from.
+/// * `algorithm` - The hash algorithm to use.
+/// * `meter` - An optional ProgressMeter to increment by the number of bytes read.
+/// * `interrupter` - An optional Interrupter checked before each chunk read.
+///
+/// # Returns
+///
+/// A Result containing the hash of the data in a String or a FoundationError if an error occurs.
+fn hash_reader<R: Read>(
+    mut reader: R,
+    algorithm: HashAlgorithm,
+    meter: Option<Arc<Mutex<ProgressMeter>>>,
+    interrupter: Option<&Interrupter>,
+) -> Result<String, FoundationError> {
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    let mut hasher = AlgorithmHasher::new(algorithm);
+
+    loop {
+        if interrupter.is_some_and(|interrupter| interrupter.is_interrupted()) {
+            return Err(FoundationError::Interrupted(Some(
+                "hashing stopped before the end of the data".to_string(),
+            )));
+        }
+
+        let bytes_read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(bytes_read) => bytes_read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        hasher.update(&chunk[..bytes_read]);
+        if let Some(meter) = &meter {
+            if let Ok(mut meter) = meter.lock() {
+                meter.increment_by(bytes_read as u64);
+                meter.notify(false);
+            }
+        }
+    }
+
+    Ok(hasher.finalize())
 }
 
 /// Asynchronously get the hash of a file.
@@ -124,20 +342,169 @@ pub async fn get_hash_for_file_with_meter_of_bytes(
 
 /// Get the hash of a directory.
 ///
+/// The entries of each directory are visited in file name order rather than the order the
+/// filesystem returns them in, so the same tree produces the same hash on every machine.
+///
 /// # Arguments
 ///
 /// * `path` - A reference to a Path.
+/// * `include_file_names` - A boolean indicating whether to include file names in the hash.
 ///
 /// # Returns
 ///
 /// A Result containing a string. If the directory is successfully hashed, the result will be `Ok(String)`.
 pub fn get_hash_for_dir(path: &Path, include_file_names: bool) -> Result<String, FoundationError> {
-    let mut hasher = Hasher::new();
+    get_hash_for_dir_with_symlink_policy(path, include_file_names, SymlinkPolicy::Skip)
+}
+
+/// Get the hash of a directory using the given hash algorithm. With `HashAlgorithm::Blake3` the
+/// hash matches the hash from `get_hash_for_dir`.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path.
+/// * `include_file_names` - A boolean indicating whether to include file names in the hash.
+/// * `algorithm` - The hash algorithm to use.
+///
+/// # Returns
+///
+/// A Result containing the hash of the directory as a lowercase hex String or a FoundationError
+/// if an error occurs.
+pub fn get_hash_for_dir_with_algorithm(
+    path: &Path,
+    include_file_names: bool,
+    algorithm: HashAlgorithm,
+) -> Result<String, FoundationError> {
+    hash_dir(
+        path,
+        include_file_names,
+        algorithm,
+        SymlinkPolicy::Skip,
+        &[],
+    )
+}
+
+/// Get the hash of a directory, treating symbolic links according to the given policy.
+/// `get_hash_for_dir` uses `SymlinkPolicy::Skip`.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path.
+/// * `include_file_names` - A boolean indicating whether to include file names in the hash.
+/// * `policy` - How to treat symbolic links in the directory.
+///
+/// # Returns
+///
+/// A Result containing a string. If the directory is successfully hashed, the result will be
+/// `Ok(String)`. If `policy` is `SymlinkPolicy::Follow` and the symbolic links form a cycle, the
+/// result will be `FoundationError::OperationFailed`.
+pub fn get_hash_for_dir_with_symlink_policy(
+    path: &Path,
+    include_file_names: bool,
+    policy: SymlinkPolicy,
+) -> Result<String, FoundationError> {
+    hash_dir(path, include_file_names, HashAlgorithm::Blake3, policy, &[])
+}
+
+/// Get the hash of a directory, leaving out the files and directories that match any of the
+/// given glob patterns. Excluded entries do not contribute to the hash at all, and the hashing
+/// does not descend into excluded directories.
+///
+/// The patterns match the path of an entry relative to `path`, using `/` as the separator:
+///
+/// * `*` matches any run of characters other than `/`.
+/// * `**` as a whole path component matches any number of directories, so `target/**` matches
+///   everything in the `target` directory.
+/// * `?` matches a single character other than `/`.
+/// * `[...]` matches one of the characters in the brackets.
+/// * A pattern ending in `/` only matches directories, so `.git/` excludes the `.git` directory.
+/// * A pattern without a `/` also matches the name of an entry at any depth, so `*.o` excludes
+///   every `.o` file in the tree.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path.
+/// * `include_file_names` - A boolean indicating whether to include file names in the hash.
+/// * `ignore_patterns` - The glob patterns of the entries to leave out of the hash.
+///
+/// # Returns
+///
+/// A Result containing a string. If the directory is successfully hashed, the result will be `Ok(String)`.
+/// An invalid pattern is an error.
+pub fn get_hash_for_dir_excluding(
+    path: &Path,
+    include_file_names: bool,
+    ignore_patterns: &[String],
+) -> Result<String, FoundationError> {
+    hash_dir(
+        path,
+        include_file_names,
+        HashAlgorithm::Blake3,
+        SymlinkPolicy::Skip,
+        ignore_patterns,
+    )
+}
+
+/// Hash the contents of a directory.
+///
+/// # Arguments
+///
+/// * `path` - A reference to a Path.
+/// * `include_file_names` - A boolean indicating whether to include file names in the hash.
+/// * `algorithm` - The hash algorithm to use.
+/// * `policy` - How to treat symbolic links in the directory.
+/// * `ignore_patterns` - The glob patterns of the entries to leave out of the hash.
+///
+/// # Returns
+///
+/// A Result containing the hash of the directory or a FoundationError if an error occurs.
+fn hash_dir(
+    path: &Path,
+    include_file_names: bool,
+    algorithm: HashAlgorithm,
+    policy: SymlinkPolicy,
+    ignore_patterns: &[String],
+) -> Result<String, FoundationError> {
+    let ignore_patterns = ignore_patterns
+        .iter()
+        .map(|pattern| IgnorePattern::new(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut hasher = AlgorithmHasher::new(algorithm);
     for entry in walkdir::WalkDir::new(path)
         .min_depth(1)
+        .follow_links(policy == SymlinkPolicy::Follow)
         .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+        .into_iter()
+        .filter_entry(|entry| !is_excluded(path, entry, &ignore_patterns))
     {
-        let entry = entry?;
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                if let Some(ancestor) = e.loop_ancestor() {
+                    return Err(FoundationError::OperationFailed(format!(
+                        "Symbolic link loop detected at {}",
+                        ancestor.display()
+                    )));
+                }
+                return Err(e.into());
+            }
+        };
+
+        if entry.path_is_symlink() && policy != SymlinkPolicy::Follow {
+            match policy {
+                SymlinkPolicy::HashTarget => {
+                    let target = std::fs::read_link(entry.path())?;
+                    hasher.update(target.display().to_string().as_bytes());
+                }
+                SymlinkPolicy::HashLinkPath => {
+                    hasher.update(entry.path().display().to_string().as_bytes());
+                }
+                _ => {}
+            }
+            continue;
+        }
+
         if entry.file_type().is_file() {
             let file = StdFile::open(entry.path())?;
             let mut reader = StdBufReader::new(file);
@@ -150,10 +517,101 @@ pub fn get_hash_for_dir(path: &Path, include_file_names: bool) -> Result<String,
             }
         }
     }
-    Ok(hasher.finalize().to_hex().to_string())
+    Ok(hasher.finalize())
 }
 
-/// Asynchronously get the hash of a directory.
+/// A compiled pattern from the ignore list of `get_hash_for_dir_excluding`.
+struct IgnorePattern {
+    /// The glob pattern, without any trailing `/`.
+    pattern: glob::Pattern,
+
+    /// True if the pattern ended in `/` and only matches directories.
+    directories_only: bool,
+
+    /// True if the pattern has no `/` and also matches the name of an entry at any depth.
+    match_name: bool,
+}
+
+impl IgnorePattern {
+    /// The options for matching relative paths, so that `*` and `?` do not match `/`.
+    const MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    /// Compile an ignore pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The glob pattern.
+    ///
+    /// # Returns
+    ///
+    /// The compiled pattern or a `FoundationError` if the pattern is invalid.
+    fn new(pattern: &str) -> Result<IgnorePattern, FoundationError> {
+        let (pattern, directories_only) = match pattern.strip_suffix('/') {
+            Some(pattern) => (pattern, true),
+            None => (pattern, false),
+        };
+        Ok(IgnorePattern {
+            pattern: glob::Pattern::new(pattern)?,
+            directories_only,
+            match_name: !pattern.contains('/'),
+        })
+    }
+
+    /// Check whether an entry matches the pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `relative` - The path of the entry relative to the directory being hashed, using `/` as
+    ///   the separator.
+    /// * `name` - The name of the entry.
+    /// * `is_dir` - True if the entry is a directory.
+    fn matches(&self, relative: &str, name: &str, is_dir: bool) -> bool {
+        if self.directories_only && !is_dir {
+            return false;
+        }
+        self.pattern.matches_with(relative, Self::MATCH_OPTIONS)
+            || (self.match_name && self.pattern.matches_with(name, Self::MATCH_OPTIONS))
+    }
+}
+
+/// Check whether a directory entry matches any of the ignore patterns.
+///
+/// # Arguments
+///
+/// * `root` - The directory being hashed.
+/// * `entry` - The directory entry to check.
+/// * `ignore_patterns` - The patterns of the entries to leave out of the hash.
+///
+/// # Returns
+///
+/// True if the entry should be left out of the hash.
+fn is_excluded(root: &Path, entry: &walkdir::DirEntry, ignore_patterns: &[IgnorePattern]) -> bool {
+    if ignore_patterns.is_empty() {
+        return false;
+    }
+
+    let relative = match entry.path().strip_prefix(root) {
+        Ok(relative) => relative,
+        Err(_) => return false,
+    };
+    let relative = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let name = entry.file_name().to_string_lossy();
+    let is_dir = entry.file_type().is_dir();
+
+    ignore_patterns
+        .iter()
+        .any(|pattern| pattern.matches(&relative, &name, is_dir))
+}
+
+/// Asynchronously get the hash of a directory. The hash matches the hash from `get_hash_for_dir`.
 ///
 /// # Arguments
 ///
@@ -188,7 +646,8 @@ pub async fn async_get_hash_for_dir(
     Ok(hasher.finalize().to_hex().to_string())
 }
 
-/// Asynchronously get the hash of a directory with a progress meter.
+/// Asynchronously get the hash of a directory with a progress meter. The hash matches the hash
+/// from `get_hash_for_dir`.
 ///
 /// # Arguments
 ///
@@ -252,3 +711,372 @@ pub fn hash_string(input: &str) -> String {
     hasher.update(input.as_bytes());
     hasher.finalize().to_hex().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::path::PathBuf;
+
+    fn create_tree(root: &Path, names: &[&str]) {
+        let _ = std::fs::remove_dir_all(root);
+        std::fs::create_dir_all(root).unwrap();
+        for name in names {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, format!("contents of {}", name)).unwrap();
+        }
+    }
+
+    fn test_root(name: &str) -> PathBuf {
+        let mut root = temp_dir();
+        root.push(format!("foundation_hash_{}", name));
+        root
+    }
+
+    #[test]
+    fn test_get_hash_for_dir_order_stable() {
+        let root = test_root("order_stable");
+        let mut names = vec![
+            "b.txt",
+            "a.txt",
+            "sub/z.txt",
+            "sub/c.txt",
+            "d/e.txt",
+            "c.txt",
+        ];
+
+        create_tree(&root, &names);
+        let with_names = get_hash_for_dir(&root, true).unwrap();
+        let without_names = get_hash_for_dir(&root, false).unwrap();
+
+        names.reverse();
+        create_tree(&root, &names);
+        assert_eq!(get_hash_for_dir(&root, true).unwrap(), with_names);
+        assert_eq!(get_hash_for_dir(&root, false).unwrap(), without_names);
+
+        names.sort();
+        create_tree(&root, &names);
+        assert_eq!(get_hash_for_dir(&root, true).unwrap(), with_names);
+        assert_eq!(get_hash_for_dir(&root, false).unwrap(), without_names);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_async_get_hash_for_dir_matches() {
+        let root = test_root("async_matches");
+        create_tree(&root, &["one.txt", "two/three.txt", "two/four.txt"]);
+
+        let expected = get_hash_for_dir(&root, true).unwrap();
+        assert_eq!(async_get_hash_for_dir(&root, true).await.unwrap(), expected);
+
+        let mut meter = Arc::new(Mutex::new(ProgressMeter::new()));
+        assert_eq!(
+            async_get_hash_for_dir_with_meter(&root, true, &mut meter)
+                .await
+                .unwrap(),
+            expected
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_hash_for_dir_symlink_policies() {
+        let root = test_root("symlink_policies");
+        create_tree(&root, &["real/one.txt", "real/two.txt", "top.txt"]);
+        let skipped = get_hash_for_dir(&root, false).unwrap();
+
+        std::os::unix::fs::symlink(root.join("real"), root.join("link")).unwrap();
+
+        // Skip ignores the link, like the plain directory hash.
+        assert_eq!(
+            get_hash_for_dir_with_symlink_policy(&root, false, SymlinkPolicy::Skip).unwrap(),
+            skipped
+        );
+        assert_eq!(get_hash_for_dir(&root, false).unwrap(), skipped);
+
+        // Follow hashes the linked directory as if it were a copy of the sibling.
+        let followed =
+            get_hash_for_dir_with_symlink_policy(&root, false, SymlinkPolicy::Follow).unwrap();
+        let copy = test_root("symlink_policies_copy");
+        create_tree(
+            &copy,
+            &[
+                "link/one.txt",
+                "link/two.txt",
+                "real/one.txt",
+                "real/two.txt",
+            ],
+        );
+        std::fs::write(copy.join("link/one.txt"), "contents of real/one.txt").unwrap();
+        std::fs::write(copy.join("link/two.txt"), "contents of real/two.txt").unwrap();
+        std::fs::write(copy.join("top.txt"), "contents of top.txt").unwrap();
+        assert_eq!(get_hash_for_dir(&copy, false).unwrap(), followed);
+
+        // The two path policies hash different strings, and neither matches skipping the link.
+        let target =
+            get_hash_for_dir_with_symlink_policy(&root, false, SymlinkPolicy::HashTarget).unwrap();
+        let link_path =
+            get_hash_for_dir_with_symlink_policy(&root, false, SymlinkPolicy::HashLinkPath)
+                .unwrap();
+        assert_ne!(target, skipped);
+        assert_ne!(link_path, skipped);
+        assert_ne!(target, link_path);
+
+        std::fs::remove_dir_all(&root).unwrap();
+        std::fs::remove_dir_all(&copy).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_hash_for_dir_symlink_loop() {
+        let root = test_root("symlink_loop");
+        create_tree(&root, &["dir/file.txt"]);
+        std::os::unix::fs::symlink(&root, root.join("dir/loop")).unwrap();
+
+        match get_hash_for_dir_with_symlink_policy(&root, false, SymlinkPolicy::Follow) {
+            Err(FoundationError::OperationFailed(_)) => {}
+            other => panic!("expected a symbolic link loop error, got {:?}", other),
+        }
+
+        // The other policies never follow the link, so they cannot loop.
+        assert!(get_hash_for_dir_with_symlink_policy(&root, false, SymlinkPolicy::Skip).is_ok());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_pattern() {
+        let matches = |pattern: &str, relative: &str| {
+            IgnorePattern::new(pattern)
+                .unwrap()
+                .pattern
+                .matches_with(relative, IgnorePattern::MATCH_OPTIONS)
+        };
+        assert!(matches("target/**", "target/debug/foo"));
+        assert!(matches("target/**", "target/foo"));
+        assert!(!matches("target/**", "src/target"));
+        assert!(matches("*.o", "main.o"));
+        assert!(!matches("*.o", "src/main.o"));
+        assert!(matches("**/*.o", "main.o"));
+        assert!(matches("**/*.o", "src/deep/main.o"));
+        assert!(matches("src/?.rs", "src/a.rs"));
+        assert!(!matches("src/?.rs", "src/ab.rs"));
+        assert!(matches(".git", ".git"));
+
+        // A pattern without a `/` also matches the name, and a trailing `/` only matches
+        // directories.
+        let pattern = IgnorePattern::new("*.o").unwrap();
+        assert!(pattern.matches("src/main.o", "main.o", false));
+        let pattern = IgnorePattern::new(".git/").unwrap();
+        assert!(pattern.matches(".git", ".git", true));
+        assert!(!pattern.matches(".git", ".git", false));
+
+        // A long run of stars does not take exponential time to fail.
+        let text = "a".repeat(64);
+        assert!(!matches("*a*a*a*a*a*a*a*a*a*a*a*a*b", &text));
+
+        assert!(IgnorePattern::new("[").is_err());
+    }
+
+    #[test]
+    fn test_get_hash_for_dir_excluding() {
+        let root = test_root("excluding");
+        create_tree(
+            &root,
+            &[
+                "src/main.rs",
+                "src/main.o",
+                "target/debug/app",
+                "target/release/app",
+                ".git/HEAD",
+                "README.md",
+            ],
+        );
+
+        let patterns = vec![
+            "target/**".to_string(),
+            ".git/".to_string(),
+            "*.o".to_string(),
+        ];
+        let excluded = get_hash_for_dir_excluding(&root, true, &patterns).unwrap();
+        assert_ne!(excluded, get_hash_for_dir(&root, true).unwrap());
+
+        // Changing an excluded file does not change the hash.
+        std::fs::write(root.join("target/debug/app"), "rebuilt").unwrap();
+        assert_eq!(
+            get_hash_for_dir_excluding(&root, true, &patterns).unwrap(),
+            excluded
+        );
+
+        // Excluded entries contribute nothing, so the hash matches the tree without them.
+        std::fs::remove_dir_all(root.join("target")).unwrap();
+        std::fs::remove_dir_all(root.join(".git")).unwrap();
+        std::fs::remove_file(root.join("src/main.o")).unwrap();
+        assert_eq!(get_hash_for_dir(&root, true).unwrap(), excluded);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_get_hash_for_reader() {
+        let data: Vec<u8> = (0..(3 * CHUNK_SIZE + 17))
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let expected = blake3::hash(&data).to_hex().to_string();
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let notified = progress.clone();
+        let meter = Arc::new(Mutex::new(ProgressMeter::new_with_notifier_and_size(
+            Box::new(move |percent| notified.lock().unwrap().push(percent)),
+            data.len() as u64,
+        )));
+
+        let hash =
+            get_hash_for_reader(std::io::Cursor::new(data.clone()), Some(meter.clone())).unwrap();
+        assert_eq!(hash, expected);
+        assert_eq!(progress.lock().unwrap().last(), Some(&100));
+
+        assert_eq!(
+            get_hash_for_reader(std::io::Cursor::new(Vec::new()), None).unwrap(),
+            blake3::hash(&[]).to_hex().to_string()
+        );
+    }
+
+    #[test]
+    fn test_get_hash_for_file() {
+        let root = test_root("file");
+        create_tree(&root, &["file.txt"]);
+
+        assert_eq!(
+            get_hash_for_file(&root.join("file.txt"), None).unwrap(),
+            blake3::hash(b"contents of file.txt").to_hex().to_string()
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_get_hash_for_file_interrupted() {
+        let root = test_root("file_interrupted");
+        create_tree(&root, &[]);
+        let path = root.join("huge.bin");
+
+        // A sparse file takes no disk space, but still takes a long time to hash.
+        let file = StdFile::create(&path).unwrap();
+        file.set_len(16 * 1024 * 1024 * 1024).unwrap();
+        drop(file);
+
+        let interrupter = Interrupter::new();
+        let thread_interrupter = interrupter.clone();
+        let thread_path = path.clone();
+        let handle =
+            std::thread::spawn(move || get_hash_for_file(&thread_path, Some(&thread_interrupter)));
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        interrupter.interrupt();
+
+        let result = handle.join().unwrap();
+        assert!(matches!(result, Err(FoundationError::Interrupted(_))));
+
+        // An already interrupted interrupter stops the hash before the first read.
+        assert!(matches!(
+            get_hash_for_file(&path, Some(&interrupter)),
+            Err(FoundationError::Interrupted(_))
+        ));
+
+        // The hash closed the file when it returned.
+        #[cfg(target_os = "linux")]
+        for entry in std::fs::read_dir("/proc/self/fd").unwrap() {
+            if let Ok(target) = std::fs::read_link(entry.unwrap().path()) {
+                assert_ne!(target, path);
+            }
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_get_hash_for_file_with_algorithm() {
+        let root = test_root("file_with_algorithm");
+        create_tree(&root, &[]);
+        let path = root.join("abc.txt");
+        std::fs::write(&path, "abc").unwrap();
+
+        assert_eq!(
+            get_hash_for_file_with_algorithm(&path, HashAlgorithm::Blake3, None).unwrap(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        assert_eq!(
+            get_hash_for_file_with_algorithm(&path, HashAlgorithm::Sha256, None).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            get_hash_for_file_with_algorithm(&path, HashAlgorithm::Sha512, None).unwrap(),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            get_hash_for_file(&path, None).unwrap(),
+            get_hash_for_file_with_algorithm(&path, HashAlgorithm::Blake3, None).unwrap()
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_get_hash_for_dir_with_algorithm() {
+        let root = test_root("dir_with_algorithm");
+        create_tree(&root, &["a.txt", "b/c.txt"]);
+
+        assert_eq!(
+            get_hash_for_dir_with_algorithm(&root, true, HashAlgorithm::Blake3).unwrap(),
+            get_hash_for_dir(&root, true).unwrap()
+        );
+
+        // The directory contents are hashed as one stream of the file contents in name order.
+        let mut expected = Sha256::new();
+        expected.update(b"contents of a.txt");
+        expected.update(b"contents of b/c.txt");
+        assert_eq!(
+            get_hash_for_dir_with_algorithm(&root, false, HashAlgorithm::Sha256).unwrap(),
+            format!("{:x}", expected.finalize())
+        );
+        assert_eq!(
+            get_hash_for_dir_with_algorithm(&root, false, HashAlgorithm::Sha512)
+                .unwrap()
+                .len(),
+            128
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_verify_file_hash() {
+        let root = test_root("verify_file_hash");
+        create_tree(&root, &[]);
+        let path = root.join("abc.txt");
+        std::fs::write(&path, "abc").unwrap();
+
+        let sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert!(verify_file_hash(&path, sha256, HashAlgorithm::Sha256).unwrap());
+        assert!(verify_file_hash(&path, &sha256.to_uppercase(), HashAlgorithm::Sha256).unwrap());
+        assert!(!verify_file_hash(&path, sha256, HashAlgorithm::Blake3).unwrap());
+        assert!(!verify_file_hash(&path, &sha256[1..], HashAlgorithm::Sha256).unwrap());
+
+        let mut mismatched = sha256.to_string();
+        mismatched.replace_range(63.., "e");
+        assert!(!verify_file_hash(&path, &mismatched, HashAlgorithm::Sha256).unwrap());
+
+        assert!(matches!(
+            verify_file_hash(&root.join("missing.txt"), sha256, HashAlgorithm::Sha256),
+            Err(FoundationError::IO(_))
+        ));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,295 @@
+//! The `interrupter` module provides the `Interrupter` object which lets one part of a program ask
+//! a long-running operation in another part of the program (or another thread) to stop early.
+
+use crate::error::FoundationError;
+use log::error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// The `Interrupter` struct holds a shared interrupt flag. Clones of an `Interrupter` share the
+/// same flag, so a UI can keep one clone to request the interrupt while a long-running operation
+/// checks another clone between units of work.
+///
+/// # Example
+///
+/// ```rust
+/// use foundation::interrupter::Interrupter;
+///
+/// let interrupter = Interrupter::new();
+/// let worker_interrupter = interrupter.clone();
+/// assert!(!worker_interrupter.is_interrupted());
+/// interrupter.interrupt();
+/// assert!(worker_interrupter.is_interrupted());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Interrupter {
+    /// The shared interrupt flag.
+    interrupted: Arc<AtomicBool>,
+
+    /// Broadcasts changes to the interrupt flag to the subscriptions.
+    sender: Arc<watch::Sender<bool>>,
+
+    /// Whether the signal handlers for the interrupt flag have been installed.
+    signal_handler_installed: Arc<AtomicBool>,
+}
+
+impl Interrupter {
+    /// The `new` function creates a new `Interrupter` object that has not been interrupted.
+    pub fn new() -> Interrupter {
+        Interrupter {
+            interrupted: Arc::new(AtomicBool::new(false)),
+            sender: Arc::new(watch::channel(false).0),
+            signal_handler_installed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// The `interrupt` function requests that the operations checking this interrupter stop.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+        self.sender.send_replace(true);
+    }
+
+    /// The `subscribe` function creates a subscription that observes the interrupt requests of
+    /// this interrupter. Each subscription observes every request independently of the other
+    /// subscriptions, so several subsystems can share one interrupter.
+    pub fn subscribe(&self) -> InterruptSubscription {
+        InterruptSubscription {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// The `is_interrupted` function returns true if an interrupt has been requested. The check
+    /// is a single atomic load, so it is cheap enough to call in a tight loop.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+
+    /// The `wait` function waits until an interrupt has been requested, so that a `tokio::select!`
+    /// can stop work when the interrupter fires. It returns immediately if an interrupt has
+    /// already been requested. Any number of tasks can wait at the same time.
+    pub async fn wait(&self) {
+        self.subscribe().await_interrupt().await;
+    }
+
+    /// The `reset` function clears the interrupt request so that the interrupter can be used
+    /// again.
+    pub fn reset(&self) {
+        self.interrupted.store(false, Ordering::SeqCst);
+        self.sender.send_replace(false);
+    }
+
+    /// The `install_signal_handler` function makes SIGINT and SIGTERM interrupt this interrupter,
+    /// so that Ctrl-C stops long-running operations the same way a call to `interrupt` does. On
+    /// Windows, Ctrl-C interrupts the interrupter.
+    ///
+    /// The signals no longer terminate the process once the handler is installed; the program is
+    /// expected to check `is_interrupted` and exit on its own. A background thread waits for the
+    /// signals, so the interrupt is requested shortly after the signal arrives. The handler is
+    /// installed only once for an interrupter and its clones, so calling the function again does
+    /// nothing.
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) on success and a `FoundationError` if a signal handler could not be registered.
+    pub fn install_signal_handler(&self) -> Result<(), FoundationError> {
+        if self
+            .signal_handler_installed
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        let wait_for_signals = match signal_waiter(self.clone()) {
+            Ok(wait_for_signals) => wait_for_signals,
+            Err(e) => {
+                self.signal_handler_installed.store(false, Ordering::SeqCst);
+                return Err(e);
+            }
+        };
+
+        if let Err(e) = std::thread::Builder::new()
+            .name("interrupter-signals".to_string())
+            .spawn(wait_for_signals)
+        {
+            error!("Failed to start the signal handler thread: {}", e);
+            self.signal_handler_installed.store(false, Ordering::SeqCst);
+            return Err(FoundationError::IO(e));
+        }
+
+        Ok(())
+    }
+}
+
+/// Register the signal handlers for an interrupter.
+///
+/// # Arguments
+///
+/// * `interrupter` - The interrupter the signals interrupt.
+///
+/// # Returns
+///
+/// A function that waits for the signals forever and interrupts the interrupter each time one
+/// arrives, or a `FoundationError` if the handlers could not be registered.
+fn signal_waiter(
+    interrupter: Interrupter,
+) -> Result<impl FnOnce() + Send + 'static, FoundationError> {
+    cfg_if! {
+        if #[cfg(unix)] {
+            let mut signals = signal_hook::iterator::Signals::new([
+                signal_hook::consts::SIGINT,
+                signal_hook::consts::SIGTERM,
+            ])?;
+
+            Ok(move || {
+                for _ in signals.forever() {
+                    interrupter.interrupt();
+                }
+            })
+        } else {
+            // signal-hook has no signal iterator on Windows, so wait for Ctrl-C with tokio on a
+            // runtime owned by the signal thread.
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            let mut ctrl_c = {
+                let _guard = runtime.enter();
+                tokio::signal::windows::ctrl_c()?
+            };
+
+            Ok(move || {
+                runtime.block_on(async move {
+                    while ctrl_c.recv().await.is_some() {
+                        interrupter.interrupt();
+                    }
+                });
+            })
+        }
+    }
+}
+
+impl Default for Interrupter {
+    fn default() -> Self {
+        Interrupter::new()
+    }
+}
+
+/// The `InterruptSubscription` struct observes the interrupt requests of an `Interrupter`. Create
+/// one with `Interrupter::subscribe`.
+#[derive(Debug, Clone)]
+pub struct InterruptSubscription {
+    /// The receiver for changes to the interrupt flag.
+    receiver: watch::Receiver<bool>,
+}
+
+impl InterruptSubscription {
+    /// The `is_interrupted` function returns true if an interrupt has been requested.
+    pub fn is_interrupted(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// The `await_interrupt` function waits until an interrupt has been requested. It returns
+    /// immediately if an interrupt has already been requested, and also returns if every clone
+    /// of the interrupter has been dropped, since no interrupt can be requested after that.
+    pub async fn await_interrupt(&mut self) {
+        let _ = self.receiver.wait_for(|interrupted| *interrupted).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interrupt() {
+        let interrupter = Interrupter::new();
+        let clone = interrupter.clone();
+        assert!(!interrupter.is_interrupted());
+        assert!(!clone.is_interrupted());
+
+        clone.interrupt();
+        assert!(interrupter.is_interrupted());
+        assert!(clone.is_interrupted());
+
+        interrupter.reset();
+        assert!(!interrupter.is_interrupted());
+        assert!(!clone.is_interrupted());
+    }
+
+    #[test]
+    fn test_interrupt_from_thread() {
+        let interrupter = Interrupter::new();
+        let clone = interrupter.clone();
+        std::thread::spawn(move || clone.interrupt())
+            .join()
+            .unwrap();
+        assert!(interrupter.is_interrupted());
+    }
+
+    #[tokio::test]
+    async fn test_wait() {
+        let interrupter = Interrupter::new();
+        let waiter = interrupter.clone();
+        let handle = tokio::spawn(async move {
+            waiter.wait().await;
+            waiter.is_interrupted()
+        });
+
+        let clone = interrupter.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            clone.interrupt();
+        });
+
+        let interrupted = tokio::time::timeout(std::time::Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(interrupted);
+
+        // Waiting on an interrupter that has already fired returns immediately.
+        tokio::time::timeout(std::time::Duration::from_secs(1), interrupter.wait())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let interrupter = Interrupter::new();
+        let subscriptions: Vec<InterruptSubscription> =
+            (0..3).map(|_| interrupter.subscribe()).collect();
+        assert!(subscriptions
+            .iter()
+            .all(|subscription| !subscription.is_interrupted()));
+
+        let handles: Vec<_> = subscriptions
+            .into_iter()
+            .map(|mut subscription| {
+                tokio::spawn(async move {
+                    subscription.await_interrupt().await;
+                    subscription.is_interrupted()
+                })
+            })
+            .collect();
+
+        let clone = interrupter.clone();
+        tokio::spawn(async move { clone.interrupt() });
+
+        for handle in handles {
+            let interrupted = tokio::time::timeout(std::time::Duration::from_secs(5), handle)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(interrupted);
+        }
+
+        // A subscription created after the interrupt sees it immediately.
+        let mut late_subscription = interrupter.subscribe();
+        assert!(late_subscription.is_interrupted());
+        late_subscription.await_interrupt().await;
+
+        interrupter.reset();
+        assert!(!late_subscription.is_interrupted());
+    }
+}
@@ -27,19 +27,265 @@
 //!   assert_eq!(configuration, loaded_configuration);
 //! }
 //! ```
+//!
+//! Saving a `HashMap` rewrites the whole file, dropping comments and reordering the keys. To
+//! change a few values in a file maintained by hand, use `load_ordered` and `write_ordered`
+//! instead, which keep every line that is not changed.
 
 use crate::error::FoundationError;
+use crate::fs::write_atomic;
 use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::PathBuf;
 
+/// The `ConfigurationValues` trait provides typed access to the values of a loaded
+/// configuration.
+pub trait ConfigurationValues {
+    /// Get the raw value for a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up.
+    ///
+    /// # Returns
+    ///
+    /// The value for the key, or `None` if the key is not present.
+    fn get_value(&self, key: &str) -> Option<&str>;
+
+    /// Get the value for a key as an integer.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if the key is not present, `Ok(Some(value))` if the value is an integer, and a
+    /// `FoundationError::OperationFailed` naming the key if the value is not an integer.
+    fn get_int(&self, key: &str) -> Result<Option<i64>, FoundationError> {
+        match self.get_value(key) {
+            Some(value) => match value.trim().parse::<i64>() {
+                Ok(number) => Ok(Some(number)),
+                Err(e) => Err(FoundationError::OperationFailed(format!(
+                    "Invalid integer value '{}' for key {}: {}",
+                    value, key, e
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Get the value for a key as a boolean. The values `1` and `true` are true and the values
+    /// `0` and `false` are false.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if the key is not present, `Ok(Some(value))` if the value is a boolean, and a
+    /// `FoundationError::OperationFailed` naming the key if the value is not a boolean.
+    fn get_bool(&self, key: &str) -> Result<Option<bool>, FoundationError> {
+        match self.get_value(key) {
+            Some(value) => match value.trim().to_lowercase().as_str() {
+                "1" | "true" => Ok(Some(true)),
+                "0" | "false" => Ok(Some(false)),
+                _ => Err(FoundationError::OperationFailed(format!(
+                    "Invalid boolean value '{}' for key {}",
+                    value, key
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Get the value for a key as a list of items.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up.
+    /// * `separator` - The character that separates the items. Whitespace around each item is
+    ///   trimmed and empty items are skipped.
+    ///
+    /// # Returns
+    ///
+    /// The items of the value, or `None` if the key is not present.
+    fn get_list(&self, key: &str, separator: char) -> Option<Vec<String>> {
+        self.get_value(key).map(|value| {
+            value
+                .split(separator)
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect()
+        })
+    }
+}
+
+impl ConfigurationValues for HashMap<String, String> {
+    fn get_value(&self, key: &str) -> Option<&str> {
+        self.get(key).map(String::as_str)
+    }
+}
+
+/// A single line of a key = value configuration file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ConfigLine {
+    /// A key = value entry.
+    Entry {
+        /// The text before the first `=`.
+        key: String,
+
+        /// The text after the first `=`.
+        value: String,
+
+        /// The line as it appeared in the file, or `None` if the entry was added or changed
+        /// after the file was read. An unchanged entry is written back exactly as it was read.
+        original: Option<String>,
+    },
+
+    /// Any other line, such as a comment or a blank line, kept exactly as it appeared.
+    Text(String),
+}
+
+/// The `OrderedConfiguration` object holds every line of a key = value configuration file in
+/// file order, so that values can be changed without losing comments or reordering the keys.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct OrderedConfiguration {
+    /// The lines of the configuration file.
+    lines: Vec<ConfigLine>,
+}
+
+impl OrderedConfiguration {
+    /// Create a new, empty `OrderedConfiguration`.
+    pub fn new() -> OrderedConfiguration {
+        OrderedConfiguration { lines: Vec::new() }
+    }
+
+    /// Get the value for a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to look up.
+    ///
+    /// # Returns
+    ///
+    /// The value of the first entry for the key, or `None` if the key is not present.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            ConfigLine::Entry {
+                key: entry_key,
+                value,
+                ..
+            } if entry_key == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Set the value for a key.
+    ///
+    /// The first entry for the key is updated in place. If the key is not present, a new entry
+    /// is added to the end of the configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to set.
+    /// * `value` - The new value for the key.
+    pub fn set(&mut self, key: &str, value: &str) {
+        for line in self.lines.iter_mut() {
+            if let ConfigLine::Entry {
+                key: entry_key,
+                value: entry_value,
+                original,
+            } = line
+            {
+                if entry_key == key {
+                    if entry_value != value {
+                        *entry_value = value.to_string();
+                        *original = None;
+                    }
+                    return;
+                }
+            }
+        }
+
+        self.lines.push(ConfigLine::Entry {
+            key: key.to_string(),
+            value: value.to_string(),
+            original: None,
+        });
+    }
+
+    /// Remove every entry for a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to remove.
+    ///
+    /// # Returns
+    ///
+    /// `true` if an entry was removed, `false` otherwise.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let original_length = self.lines.len();
+        self.lines.retain(
+            |line| !matches!(line, ConfigLine::Entry { key: entry_key, .. } if entry_key == key),
+        );
+        self.lines.len() != original_length
+    }
+
+    /// Get the keys of the configuration in file order.
+    pub fn keys(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                ConfigLine::Entry { key, .. } => Some(key.as_str()),
+                ConfigLine::Text(_) => None,
+            })
+            .collect()
+    }
+
+    /// Get the lines of the configuration in file order.
+    pub fn lines(&self) -> &[ConfigLine] {
+        &self.lines
+    }
+
+    /// Get the key value pairs of the configuration as a `HashMap`. If a key appears more than
+    /// once, the last value wins, matching `KeyValueConfigFile::load_configuration`.
+    pub fn to_hash_map(&self) -> HashMap<String, String> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                ConfigLine::Entry { key, value, .. } => Some((key.clone(), value.clone())),
+                ConfigLine::Text(_) => None,
+            })
+            .collect()
+    }
+}
+
+impl ConfigurationValues for OrderedConfiguration {
+    fn get_value(&self, key: &str) -> Option<&str> {
+        self.get(key)
+    }
+}
+
 /// The `KeyValueConfigFile` object is used to read and write configuration files that have a simple
 /// key = value format.
+///
+/// By default the key and value are separated by `=` and the value is everything after the first
+/// `=`, exactly as written. Use `with_separator` for files that separate keys and values with
+/// another character, and `with_quoting` for files that quote values containing whitespace.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct KeyValueConfigFile {
     /// The path to the configuration file.
     filename: PathBuf,
+
+    /// The character that separates keys from values.
+    separator: char,
+
+    /// Whether values are quoted when they contain whitespace or the separator.
+    quoting: bool,
 }
 
 impl KeyValueConfigFile {
@@ -49,7 +295,80 @@ impl KeyValueConfigFile {
     ///
     /// * `path` - The path to the configuration file.
     pub fn new(path: PathBuf) -> KeyValueConfigFile {
-        KeyValueConfigFile { filename: path }
+        KeyValueConfigFile {
+            filename: path,
+            separator: '=',
+            quoting: false,
+        }
+    }
+
+    /// Use a different character to separate keys from values.
+    ///
+    /// # Arguments
+    ///
+    /// * `separator` - The character that separates keys from values, such as `:`.
+    pub fn with_separator(mut self, separator: char) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Turn quoting of values on or off.
+    ///
+    /// With quoting on, whitespace around keys and values is ignored when reading, values in
+    /// double quotes are read without the quotes, and values containing whitespace, the
+    /// separator, `"`, or `#` are written in double quotes. Inside quotes, `"` and `\` are
+    /// escaped with a backslash.
+    ///
+    /// # Arguments
+    ///
+    /// * `quoting` - `true` to quote values, `false` to read and write values exactly.
+    pub fn with_quoting(mut self, quoting: bool) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Parse a line of the configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line to parse, without the trailing newline.
+    fn parse_line(&self, line: &str) -> ConfigLine {
+        if line.starts_with('#') {
+            return ConfigLine::Text(line.to_string());
+        }
+
+        match line.split_once(self.separator) {
+            Some((key, value)) if self.quoting => ConfigLine::Entry {
+                key: key.trim().to_string(),
+                value: unquote(value.trim()),
+                original: Some(line.to_string()),
+            },
+            Some((key, value)) => ConfigLine::Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+                original: Some(line.to_string()),
+            },
+            None => ConfigLine::Text(line.to_string()),
+        }
+    }
+
+    /// Format a key value pair as a line of the configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key.
+    /// * `value` - The value.
+    fn format_entry(&self, key: &str, value: &str) -> String {
+        let needs_quotes = self.quoting
+            && value
+                .chars()
+                .any(|c| c.is_whitespace() || c == self.separator || c == '"' || c == '#');
+
+        if needs_quotes {
+            format!("{}{}{}", key, self.separator, quote(value))
+        } else {
+            format!("{}{}{}", key, self.separator, value)
+        }
     }
 
     /// Load the configuration from the file.
@@ -63,28 +382,10 @@ impl KeyValueConfigFile {
             Ok(contents) => {
                 let mut configuration = HashMap::new();
                 for line in contents.lines() {
-                    // Skip empty lines
-                    if line.is_empty() {
-                        continue;
-                    }
-
-                    // Skip lines that are comments.
-                    if line.chars().nth(0).unwrap() == '#' {
-                        continue;
-                    }
-
-                    // Trim off a newline character if it exists.
-                    let the_line = if line.ends_with('\n') {
-                        &line[0..line.len() - 1]
-                    } else {
-                        &line
-                    };
-
-                    let parts: Vec<&str> = the_line.splitn(2, '=').collect();
-
-                    // Only use lines that have a key = value, otherwise discard them.
-                    if parts.len() == 2 {
-                        configuration.insert(parts[0].to_string(), parts[1].to_string());
+                    // Only use lines that have a key = value, otherwise discard them. Comments
+                    // and empty lines are never entries.
+                    if let ConfigLine::Entry { key, value, .. } = self.parse_line(line) {
+                        configuration.insert(key, value);
                     }
                 }
                 Ok(configuration)
@@ -109,13 +410,14 @@ impl KeyValueConfigFile {
     ) -> Result<(), FoundationError> {
         match OpenOptions::new()
             .write(true)
+            .truncate(true)
             .create(true)
             .open(&self.filename)
         {
             Ok(mut file) => {
                 for (key, value) in configuration {
                     if !value.is_empty() {
-                        writeln!(file, "{}={}", key, value)?;
+                        writeln!(file, "{}", self.format_entry(key, value))?;
                     } else {
                         writeln!(file, "{}", key)?;
                     }
@@ -126,6 +428,55 @@ impl KeyValueConfigFile {
         }
     }
 
+    /// Load every line of the file, keeping comments, blank lines, and the order of the keys.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `OrderedConfiguration` if the file was successfully read,
+    /// otherwise a `FoundationError` is returned.
+    pub fn load_ordered(&self) -> Result<OrderedConfiguration, FoundationError> {
+        let contents = std::fs::read_to_string(&self.filename)?;
+        Ok(OrderedConfiguration {
+            lines: contents.lines().map(|line| self.parse_line(line)).collect(),
+        })
+    }
+
+    /// Write an ordered configuration to the file.
+    ///
+    /// Comments, blank lines, other lines that are not entries, and entries that were not
+    /// changed are written exactly as they were read. Entries that were added or changed are
+    /// written with the file's separator and quoting rules. The file is replaced atomically and
+    /// keeps its permissions.
+    ///
+    /// # Arguments
+    ///
+    /// * `configuration` - The configuration to write to the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `()` if the configuration was successfully written to the file,
+    /// otherwise a `FoundationError` is returned.
+    pub fn write_ordered(
+        &self,
+        configuration: &OrderedConfiguration,
+    ) -> Result<(), FoundationError> {
+        let mut contents = String::new();
+        for line in &configuration.lines {
+            match line {
+                ConfigLine::Entry {
+                    original: Some(original),
+                    ..
+                } => contents.push_str(original),
+                ConfigLine::Entry { key, value, .. } => {
+                    contents.push_str(&self.format_entry(key, value));
+                }
+                ConfigLine::Text(text) => contents.push_str(text),
+            }
+            contents.push('\n');
+        }
+        write_atomic(&self.filename, contents.as_bytes(), None)
+    }
+
     /// Check if the file exists.
     ///
     /// # Returns
@@ -136,6 +487,53 @@ impl KeyValueConfigFile {
     }
 }
 
+/// Put a value in double quotes, escaping quotes and backslashes.
+///
+/// # Arguments
+///
+/// * `value` - The value to quote.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Remove the double quotes from a quoted value, undoing the escapes added by `quote`. Values
+/// that are not quoted are returned unchanged.
+///
+/// # Arguments
+///
+/// * `value` - The value to unquote.
+fn unquote(value: &str) -> String {
+    let inner = match value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        Some(inner) => inner,
+        None => return value.to_string(),
+    };
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                unquoted.push(escaped);
+                continue;
+            }
+        }
+        unquoted.push(c);
+    }
+    unquoted
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,4 +570,160 @@ mod tests {
         assert_eq!(configuration, loaded_configuration);
         assert!(file.file_exists());
     }
+
+    #[test]
+    fn test_ordered_configuration() {
+        let mut temp_path = temp_dir();
+        temp_path.push("keyvalueconfigfile_test_ordered_configuration.txt");
+        let original = "# hostapd configuration\n\
+                        interface=wlan0\n\
+                        \n\
+                        # Network name\n\
+                        ssid=old-network\n\
+                        channel=6\n\
+                        wmm_enabled\n";
+        std::fs::write(&temp_path, original).unwrap();
+
+        let file = KeyValueConfigFile::new(temp_path.clone());
+        let mut configuration = file.load_ordered().unwrap();
+        assert_eq!(configuration.keys(), vec!["interface", "ssid", "channel"]);
+        assert_eq!(configuration.get("ssid"), Some("old-network"));
+
+        configuration.set("ssid", "new-network");
+        file.write_ordered(&configuration).unwrap();
+
+        let written = std::fs::read_to_string(&temp_path).unwrap();
+        assert_eq!(written, original.replace("old-network", "new-network"));
+
+        configuration.set("country_code", "US");
+        assert!(configuration.remove("channel"));
+        assert!(!configuration.remove("channel"));
+        file.write_ordered(&configuration).unwrap();
+
+        let reloaded = file.load_ordered().unwrap();
+        assert_eq!(reloaded.keys(), vec!["interface", "ssid", "country_code"]);
+        assert_eq!(reloaded.to_hash_map(), file.load_configuration().unwrap());
+        std::fs::remove_file(&temp_path).unwrap();
+    }
+
+    #[test]
+    fn test_get_int() {
+        let mut configuration = HashMap::new();
+        configuration.insert("channel".to_string(), "6".to_string());
+        configuration.insert("offset".to_string(), " -3 ".to_string());
+        configuration.insert("bad".to_string(), "six".to_string());
+
+        assert_eq!(configuration.get_int("channel").unwrap(), Some(6));
+        assert_eq!(configuration.get_int("offset").unwrap(), Some(-3));
+        assert_eq!(configuration.get_int("missing").unwrap(), None);
+
+        let error = configuration.get_int("bad").unwrap_err();
+        assert!(matches!(error, FoundationError::OperationFailed(_)));
+        assert!(error.to_string().contains("bad"));
+    }
+
+    #[test]
+    fn test_get_bool() {
+        let mut configuration = OrderedConfiguration::new();
+        configuration.set("ieee80211n", "1");
+        configuration.set("wmm_enabled", "0");
+        configuration.set("ignore_broadcast_ssid", "True");
+        configuration.set("macaddr_acl", "false");
+        configuration.set("bad", "yes");
+
+        assert_eq!(configuration.get_bool("ieee80211n").unwrap(), Some(true));
+        assert_eq!(configuration.get_bool("wmm_enabled").unwrap(), Some(false));
+        assert_eq!(
+            configuration.get_bool("ignore_broadcast_ssid").unwrap(),
+            Some(true)
+        );
+        assert_eq!(configuration.get_bool("macaddr_acl").unwrap(), Some(false));
+        assert_eq!(configuration.get_bool("missing").unwrap(), None);
+
+        let error = configuration.get_bool("bad").unwrap_err();
+        assert!(matches!(error, FoundationError::OperationFailed(_)));
+        assert!(error.to_string().contains("bad"));
+    }
+
+    #[test]
+    fn test_get_list() {
+        let mut configuration = HashMap::new();
+        configuration.insert("wpa_pairwise".to_string(), "TKIP CCMP".to_string());
+        configuration.insert("server".to_string(), "8.8.8.8, 1.1.1.1,".to_string());
+
+        assert_eq!(
+            configuration.get_list("wpa_pairwise", ' '),
+            Some(vec!["TKIP".to_string(), "CCMP".to_string()])
+        );
+        assert_eq!(
+            configuration.get_list("server", ','),
+            Some(vec!["8.8.8.8".to_string(), "1.1.1.1".to_string()])
+        );
+        assert_eq!(configuration.get_list("missing", ','), None);
+    }
+
+    #[test]
+    fn test_quoted_values() {
+        let mut temp_path = temp_dir();
+        temp_path.push("keyvalueconfigfile_test_quoted_values.txt");
+        let file = KeyValueConfigFile::new(temp_path.clone()).with_quoting(true);
+        let mut configuration = HashMap::new();
+        configuration.insert("ssid".to_string(), "My Home Network".to_string());
+        configuration.insert("options".to_string(), "a=b".to_string());
+        configuration.insert("quoted".to_string(), "say \"hi\" \\o/".to_string());
+        configuration.insert("plain".to_string(), "value".to_string());
+        file.save_configuration(&configuration).unwrap();
+
+        let written = std::fs::read_to_string(&temp_path).unwrap();
+        assert!(written.contains("ssid=\"My Home Network\"\n"));
+        assert!(written.contains("options=\"a=b\"\n"));
+        assert!(written.contains("plain=value\n"));
+
+        assert_eq!(file.load_configuration().unwrap(), configuration);
+        std::fs::remove_file(&temp_path).unwrap();
+    }
+
+    #[test]
+    fn test_custom_separator() {
+        let mut temp_path = temp_dir();
+        temp_path.push("keyvalueconfigfile_test_custom_separator.txt");
+        std::fs::write(
+            &temp_path,
+            "# Server settings\nhost: example.com\nbanner: \"Welcome: all users\"\n",
+        )
+        .unwrap();
+
+        let file = KeyValueConfigFile::new(temp_path.clone())
+            .with_separator(':')
+            .with_quoting(true);
+        let mut configuration = file.load_ordered().unwrap();
+        assert_eq!(configuration.get("host"), Some("example.com"));
+        assert_eq!(configuration.get("banner"), Some("Welcome: all users"));
+
+        configuration.set("host", "example.com");
+        configuration.set("motd", "time: now");
+        file.write_ordered(&configuration).unwrap();
+
+        // Untouched entries keep their spacing, new entries use the file's formatting rules.
+        let written = std::fs::read_to_string(&temp_path).unwrap();
+        assert_eq!(
+            written,
+            "# Server settings\nhost: example.com\nbanner: \"Welcome: all users\"\nmotd:\"time: now\"\n"
+        );
+
+        configuration.set("host", "example.org");
+        file.write_ordered(&configuration).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&temp_path).unwrap(),
+            "# Server settings\nhost:example.org\nbanner: \"Welcome: all users\"\nmotd:\"time: now\"\n"
+        );
+        assert_eq!(
+            file.load_configuration()
+                .unwrap()
+                .get("motd")
+                .map(String::as_str),
+            Some("time: now")
+        );
+        std::fs::remove_file(&temp_path).unwrap();
+    }
 }
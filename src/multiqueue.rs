@@ -7,14 +7,20 @@ use std::error::Error;
 use std::fmt;
 use std::fmt::Display;
 use std::marker::PhantomData;
-use std::sync::{Arc, Mutex};
+use std::mem::ManuallyDrop;
+use std::sync::{Arc, Condvar, Mutex, TryLockError};
+use std::time::{Duration, Instant};
 
 /// Error returned by MultiQueue functions.
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum MultiQueueError<T> {
-    /// Failed to add item to the queue.
+    /// Failed to add item to the queue because the queue core lock is poisoned.
     Push(T),
 
+    /// Could not add the item to the queue without blocking because another fork of the queue
+    /// currently holds the queue core lock.
+    WouldBlock(T),
+
     /// Failed to fork the queue.
     Fork,
 }
@@ -24,6 +30,9 @@ impl<T> Display for MultiQueueError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             MultiQueueError::Push(_) => write!(f, "failed to add item to the queue"),
+            MultiQueueError::WouldBlock(_) => {
+                write!(f, "failed to add item to the queue without blocking")
+            }
             MultiQueueError::Fork => write!(f, "failed to fork the queue"),
         }
     }
@@ -49,11 +58,19 @@ struct Block<T> {
     // A pointer to the next block in the list.
     next: *mut Block<T>,
 
-    // The data contained in the block.
-    object: T,
+    // The data contained in the block. The object is wrapped in `ManuallyDrop` because
+    // `pop_front_value` may move the object out of the block before the block itself is dropped.
+    object: ManuallyDrop<T>,
 
     // The reference count of the block.
     reference_count: u32,
+
+    // The number of forks of the queue that have read this block and are parked on it at the end
+    // of the queue. These forks still hold a reference to the block, but no longer need its object.
+    count_at_end_of_queue: u32,
+
+    // True if the object has been moved out of the block and must not be dropped with the block.
+    taken: bool,
 }
 
 impl<T> Block<T> {
@@ -69,8 +86,21 @@ impl<T> Block<T> {
     fn new(object: T) -> Block<T> {
         Block {
             next: std::ptr::null_mut(),
-            object,
+            object: ManuallyDrop::new(object),
             reference_count: 1,
+            count_at_end_of_queue: 0,
+            taken: false,
+        }
+    }
+}
+
+impl<T> Drop for Block<T> {
+    fn drop(&mut self) {
+        if !self.taken {
+            // The object is still owned by the block, so it is our job to drop it.
+            unsafe {
+                ManuallyDrop::drop(&mut self.object);
+            }
         }
     }
 }
@@ -91,6 +121,31 @@ pub struct Core<T> {
 
     /// The number of forks of the queue currently at the end of the queue.
     count_at_end_of_queue: u32,
+
+    /// The condition variable used to wake forks waiting for an object in `wait_front`. The
+    /// condition variable is signalled whenever an object is added to the queue.
+    condvar: Arc<Condvar>,
+
+    /// The generation of the queue. `clear_shared` increments the generation, and each fork
+    /// compares its own generation to this one to detect that the queue was cleared.
+    generation: u64,
+
+    /// The block lists removed from the queue by `clear_shared` that other forks still hold
+    /// references to. A fork releases its references the next time it syncs with the core.
+    detached: Vec<DetachedList<T>>,
+}
+
+/// The `DetachedList` struct records a list of blocks removed from the queue by `clear_shared`.
+/// We cannot free the blocks right away because other forks may still point into the list (for
+/// example, a fork may have handed out a reference from `front` or be in the middle of iterating
+/// the queue). Instead, each fork releases its references when it notices the new generation, and
+/// the blocks are freed once their reference counts reach zero.
+struct DetachedList<T> {
+    /// The generation of the queue that the list belonged to.
+    generation: u64,
+
+    /// A pointer to the first block in the list that has not yet been freed.
+    head: *mut Block<T>,
 }
 
 impl<T> Core<T> {
@@ -105,6 +160,9 @@ impl<T> Core<T> {
             tail: std::ptr::null_mut(),
             reference_count: 1,
             count_at_end_of_queue: 0,
+            condvar: Arc::new(Condvar::new()),
+            generation: 0,
+            detached: Vec::new(),
         }
     }
 
@@ -141,6 +199,9 @@ impl<T> Core<T> {
             // to the `Core` object.
             (*self.tail).reference_count = self.reference_count;
         }
+
+        // Wake up any forks waiting for an object.
+        self.condvar.notify_all();
     }
 
     /// The `update` function removes any blocks from the front of the queue that have a reference
@@ -236,6 +297,127 @@ impl<T> Core<T> {
     pub fn empty(&self) -> bool {
         self.head.is_null()
     }
+
+    /// The `leave_end_of_queue` function records that a fork of the queue is no longer parked at
+    /// the end of the queue. If the count of forks at the end of the queue is already zero, then
+    /// the bookkeeping is out of sync. Rather than let the count wrap (or panic in debug builds),
+    /// we log the problem and leave the count at zero.
+    fn leave_end_of_queue(&mut self) {
+        if self.count_at_end_of_queue == 0 {
+            error!("MultiQueue fork left the end of the queue, but no forks were at the end");
+        }
+        self.count_at_end_of_queue = self.count_at_end_of_queue.saturating_sub(1);
+    }
+
+    /// The `sync_fork` function brings a fork up to date with the current generation of the queue.
+    /// If another fork cleared the queue since this fork last looked at the core, the fork releases
+    /// its references to the detached blocks and starts over as if it had just been created. The
+    /// caller must hold the core lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `head` - The head pointer of the fork.
+    /// * `at_end_of_queue` - The end of queue flag of the fork.
+    /// * `generation` - The generation of the fork.
+    fn sync_fork(
+        &mut self,
+        head: &mut *mut Block<T>,
+        at_end_of_queue: &mut bool,
+        generation: &mut u64,
+    ) {
+        if *generation == self.generation {
+            return;
+        }
+
+        // The fork holds a reference to every block from its head to the end of the list of its
+        // own generation (or to every block if it never looked at the queue), and to every block
+        // of the lists detached after that.
+        for list in self.detached.iter() {
+            if list.generation < *generation {
+                continue;
+            }
+
+            let start = if list.generation == *generation && !head.is_null() {
+                *head
+            } else {
+                list.head
+            };
+            Self::release_from(start);
+        }
+        self.free_detached();
+
+        // The fork also holds a reference to every block pushed since the queue was cleared,
+        // which is exactly the state of a fork that has not yet looked at the queue.
+        *head = std::ptr::null_mut();
+        *at_end_of_queue = false;
+        *generation = self.generation;
+    }
+
+    /// The `clear` function removes every block from the queue for all forks and moves the queue
+    /// to a new generation. The calling fork must already be in sync with the core, and the caller
+    /// must hold the core lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `head` - The head pointer of the calling fork.
+    /// * `at_end_of_queue` - The end of queue flag of the calling fork.
+    /// * `generation` - The generation of the calling fork.
+    fn clear(
+        &mut self,
+        head: &mut *mut Block<T>,
+        at_end_of_queue: &mut bool,
+        generation: &mut u64,
+    ) {
+        if !self.head.is_null() {
+            let start = if head.is_null() { self.head } else { *head };
+            self.detached.push(DetachedList {
+                generation: self.generation,
+                head: self.head,
+            });
+            Self::release_from(start);
+            self.free_detached();
+        }
+
+        self.head = std::ptr::null_mut();
+        self.tail = std::ptr::null_mut();
+        self.count_at_end_of_queue = 0;
+        self.generation += 1;
+
+        *head = std::ptr::null_mut();
+        *at_end_of_queue = false;
+        *generation = self.generation;
+    }
+
+    /// The `release_from` function decrements the reference count of every block from the given
+    /// block to the end of the list.
+    fn release_from(block: *mut Block<T>) {
+        let mut tmp = block;
+        while !tmp.is_null() {
+            unsafe {
+                (*tmp).reference_count -= 1;
+                tmp = (*tmp).next;
+            }
+        }
+    }
+
+    /// The `free_detached` function frees the blocks of the detached lists that no fork references
+    /// anymore. A fork always references every block from its head to the end of a list, so the
+    /// blocks with a reference count of 0 are always at the front of a list.
+    fn free_detached(&mut self) {
+        for list in self.detached.iter_mut() {
+            while !list.head.is_null() {
+                unsafe {
+                    if (*list.head).reference_count != 0 {
+                        break;
+                    }
+                    let next = (*list.head).next;
+                    drop(Box::from_raw(list.head));
+                    list.head = next;
+                }
+            }
+        }
+        self.detached.retain(|list| !list.head.is_null());
+    }
 }
 
 impl<T> Drop for Core<T> {
@@ -259,6 +441,9 @@ pub struct MultiQueue<T> {
     /// that the queue is forked before we insert any elements to help correctly keep track
     /// of the block reference counts.
     at_end_of_queue: bool,
+
+    /// The generation of the queue this fork last synced with. See `clear_shared`.
+    generation: u64,
 }
 
 impl<T> MultiQueue<T> {
@@ -268,6 +453,7 @@ impl<T> MultiQueue<T> {
             core: Arc::new(Mutex::new(Core::new())),
             head: std::ptr::null_mut(),
             at_end_of_queue: false,
+            generation: 0,
         }
     }
 
@@ -283,6 +469,11 @@ impl<T> MultiQueue<T> {
     pub fn push_back(&mut self, object: T) -> Result<(), MultiQueueError<T>> {
         match self.core.lock() {
             Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
                 core.push_back(object);
                 if self.head == std::ptr::null_mut() {
                     self.head = core.head;
@@ -293,18 +484,50 @@ impl<T> MultiQueue<T> {
         }
     }
 
+    /// The `try_push_back` function adds an object to the back of the queue without blocking.
+    /// If another fork of the queue currently holds the queue core lock, the function returns
+    /// immediately and gives the object back to the caller so that the caller can retry later.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The object to add to the back of the queue.
+    ///
+    /// # Returns
+    ///
+    /// An `Ok` result if the object was added to the queue, `MultiQueueError::WouldBlock` if the
+    /// core is locked by another fork, or `MultiQueueError::Push` if the core lock is poisoned.
+    pub fn try_push_back(&mut self, object: T) -> Result<(), MultiQueueError<T>> {
+        match self.core.try_lock() {
+            Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
+                core.push_back(object);
+                if self.head.is_null() {
+                    self.head = core.head;
+                }
+                Ok(())
+            }
+            Err(TryLockError::WouldBlock) => Err(MultiQueueError::WouldBlock(object)),
+            Err(TryLockError::Poisoned(_)) => Err(MultiQueueError::Push(object)),
+        }
+    }
+
     /// The `empty` function returns true if the queue is empty.
     pub fn empty(&self) -> bool {
         match self.core.lock() {
             Ok(core) => {
-                if self.head == std::ptr::null_mut() {
+                let (head, at_end_of_queue) = self.visible_cursor(&core);
+                if head.is_null() {
                     return core.empty();
                 }
 
-                if self.at_end_of_queue {
-                    // We just verified that self.head points to something.
+                if at_end_of_queue {
+                    // We just verified that head points to something.
                     unsafe {
-                        return (*self.head).next.is_null();
+                        return (*head).next.is_null();
                     }
                 }
 
@@ -325,6 +548,11 @@ impl<T> MultiQueue<T> {
     pub fn front(&mut self) -> Option<&T> {
         match self.core.lock() {
             Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
                 if core.empty() {
                     return None;
                 }
@@ -343,18 +571,19 @@ impl<T> MultiQueue<T> {
 
                     unsafe {
                         (*self.head).reference_count -= 1;
+                        (*self.head).count_at_end_of_queue -= 1;
                     }
 
                     core.update();
 
                     self.head = next;
                     self.at_end_of_queue = false;
-                    core.count_at_end_of_queue -= 1;
+                    core.leave_end_of_queue();
                 }
 
                 assert_eq!(self.head.is_null(), false, "head is null");
                 unsafe {
-                    return Some(&(*self.head).object);
+                    return Some(&*(*self.head).object);
                 }
             }
             Err(_) => {
@@ -372,6 +601,11 @@ impl<T> MultiQueue<T> {
     pub fn front_mut(&mut self) -> Option<&mut T> {
         match self.core.lock() {
             Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
                 if core.empty() {
                     return None;
                 }
@@ -390,19 +624,161 @@ impl<T> MultiQueue<T> {
 
                     unsafe {
                         (*self.head).reference_count -= 1;
+                        (*self.head).count_at_end_of_queue -= 1;
                     }
 
                     core.update();
 
                     self.head = next;
                     self.at_end_of_queue = false;
-                    core.count_at_end_of_queue -= 1;
+                    core.leave_end_of_queue();
                 }
 
                 assert_eq!(self.head.is_null(), false, "head is null");
                 unsafe {
-                    return Some(&mut (*self.head).object);
+                    return Some(&mut *(*self.head).object);
+                }
+            }
+            Err(_) => {
+                error!("Could not lock the MultiQueue core");
+                None
+            }
+        }
+    }
+
+    /// The `wait_front` function returns a reference to the object at the front of the queue,
+    /// waiting for another fork to add an object if the queue is empty for this fork. Unlike
+    /// polling `empty` in a loop, the calling thread parks until an object arrives. Since this
+    /// function blocks the calling thread, avoid calling it from an async task.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum amount of time to wait for an object, or `None` to wait forever.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the object at the front of the queue, or `None` if no object arrived before
+    /// the timeout expired.
+    pub fn wait_front(&mut self, timeout: Option<Duration>) -> Option<&T> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        let mut core = match self.core.lock() {
+            Ok(core) => core,
+            Err(_) => {
+                error!("Could not lock the MultiQueue core");
+                return None;
+            }
+        };
+
+        loop {
+            // Another fork may have cleared the queue while we were waiting.
+            core.sync_fork(
+                &mut self.head,
+                &mut self.at_end_of_queue,
+                &mut self.generation,
+            );
+            if !core.empty() {
+                if self.head.is_null() {
+                    self.head = core.head;
+                }
+
+                if !self.at_end_of_queue {
+                    unsafe {
+                        return Some(&*(*self.head).object);
+                    }
+                }
+
+                // We just verified that self.head points to something valid.
+                let next = unsafe { (*self.head).next };
+
+                if !next.is_null() {
+                    unsafe {
+                        (*self.head).reference_count -= 1;
+                        (*self.head).count_at_end_of_queue -= 1;
+                    }
+
+                    core.update();
+
+                    self.head = next;
+                    self.at_end_of_queue = false;
+                    core.leave_end_of_queue();
+
+                    unsafe {
+                        return Some(&*(*self.head).object);
+                    }
+                }
+            }
+
+            // Nothing to read yet, wait for another fork to push an object. The loop takes care
+            // of spurious wake ups by checking the queue again.
+            let condvar = core.condvar.clone();
+            let result = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    condvar
+                        .wait_timeout(core, deadline - now)
+                        .map(|(core, _)| core)
+                        .ok()
                 }
+                None => condvar.wait(core).ok(),
+            };
+
+            core = match result {
+                Some(core) => core,
+                None => {
+                    error!("Could not lock the MultiQueue core");
+                    return None;
+                }
+            };
+        }
+    }
+
+    /// The `peek_nth` function returns a reference to the object `n` places behind the front of
+    /// the queue without consuming any objects. `peek_nth(0)` returns the same object as `front`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The zero-based position of the object, counting from the front of the queue.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the object at position `n`, or `None` if the queue has `n` or fewer
+    /// objects remaining for this fork.
+    pub fn peek_nth(&self, n: usize) -> Option<&T> {
+        match self.core.lock() {
+            Ok(core) => {
+                if core.empty() {
+                    return None;
+                }
+
+                // Find the first block visible to this fork. If we are at the end of the queue
+                // then our head block has already been read and the next block is the front.
+                let (head, at_end_of_queue) = self.visible_cursor(&core);
+                let mut tmp = if head.is_null() {
+                    core.head
+                } else if at_end_of_queue {
+                    unsafe { (*head).next }
+                } else {
+                    head
+                };
+
+                let mut index = 0;
+                while !tmp.is_null() {
+                    if index == n {
+                        unsafe {
+                            return Some(&*(*tmp).object);
+                        }
+                    }
+                    index += 1;
+                    unsafe {
+                        tmp = (*tmp).next;
+                    }
+                }
+
+                None
             }
             Err(_) => {
                 error!("Could not lock the MultiQueue core");
@@ -416,6 +792,11 @@ impl<T> MultiQueue<T> {
     pub fn pop_front(&mut self) {
         match self.core.lock() {
             Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
                 if core.empty() {
                     return;
                 }
@@ -424,60 +805,159 @@ impl<T> MultiQueue<T> {
                     self.head = core.head;
                 }
 
-                if self.at_end_of_queue {
-                    // We are at the end of the queue, and we have a valid head pointer.
-                    // This means that we will discard the head pointer and move to the next
-                    // pointer in the list if it exists.  However, the pop front operation
-                    // means that we pop the next valid block and move beyond it.  Our current
-                    // head pointer is not the current valid block.
+                Self::advance(&mut self.head, &mut self.at_end_of_queue, &mut core);
+            }
+            Err(e) => {
+                error!("Could not lock the MultiQueue core: {}", e);
+            }
+        }
+    }
 
-                    unsafe {
-                        // If the next block is still null then we don't do anything else, we have
-                        // no other block to move to.
-                        if (*self.head).next == std::ptr::null_mut() {
-                            return;
-                        }
+    /// The `pop_front_value` function removes the object at the front of the queue and returns
+    /// it to the caller.
+    ///
+    /// The object can only be moved out of the queue when no other fork of the queue still needs
+    /// to read it. Every fork that has not moved past a block holds a reference to the block, but
+    /// forks parked on the block at the end of the queue have already read it. So when the block's
+    /// reference count minus the number of forks parked on it is 1, the only fork that still needs
+    /// the object is this one, and no other fork can ever observe the object again. In that case
+    /// the object is moved out of the block and the block is marked so that dropping it does not
+    /// drop the object a second time. The block itself may stay in the list a little longer (for
+    /// example, this fork keeps a pointer to the last block in the queue to mark the end of the
+    /// queue), but nothing reads the object of a block that a fork has already moved past.
+    ///
+    /// If another fork still needs the object, then this function leaves the queue untouched and
+    /// returns `None`. Use `pop_front_cloned` to get a copy of a shared object instead.
+    ///
+    /// # Returns
+    ///
+    /// The object at the front of the queue, or `None` if the queue is empty or if another fork
+    /// still needs the object.
+    pub fn pop_front_value(&mut self) -> Option<T> {
+        match self.core.lock() {
+            Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
+                let front = Self::front_block(&mut self.head, self.at_end_of_queue, &core);
+                if front.is_null() {
+                    return None;
+                }
 
-                        // Decrement the reference count on the current head block.
-                        (*self.head).reference_count -= 1;
-                        self.head = (*self.head).next;
+                let value = unsafe {
+                    if (*front).reference_count - (*front).count_at_end_of_queue != 1 {
+                        return None;
                     }
 
-                    // Now, if the new head has a next block of null, then the pop operation
-                    // will leave us at the end of the list.
-                    unsafe {
-                        // We are already at the end of the queue, so we only care about the
-                        // case where the next block is not null.
-                        if (*self.head).next != std::ptr::null_mut() {
-                            (*self.head).reference_count -= 1;
-                            self.head = (*self.head).next;
-                            self.at_end_of_queue = false;
-                            core.count_at_end_of_queue -= 1;
-                        }
-                    }
-                } else {
-                    // If I am not at the end of the queue, then the current head block is the
-                    // next block in the queue.  I can decrement its reference count and go
-                    // to the next block.
-                    unsafe {
-                        if (*self.head).next == std::ptr::null_mut() {
-                            self.at_end_of_queue = true;
-                            core.count_at_end_of_queue += 1;
-                        } else {
-                            (*self.head).reference_count -= 1;
-                            self.head = (*self.head).next;
-                        }
-                    }
-                }
+                    // We are the only fork that still needs this block, take the object.
+                    (*front).taken = true;
+                    ManuallyDrop::take(&mut (*front).object)
+                };
 
-                core.update();
+                Self::advance(&mut self.head, &mut self.at_end_of_queue, &mut core);
+                Some(value)
             }
             Err(e) => {
                 error!("Could not lock the MultiQueue core: {}", e);
+                None
             }
         }
     }
 
+    /// The `drain` function returns an iterator that moves objects out of the front of the queue
+    /// and yields them to the caller, see `pop_front_value`. The iterator yields the objects
+    /// visible to this fork when the iterator was created, objects pushed to the queue while
+    /// draining are left in the queue. The iterator stops early at an object that another fork
+    /// still needs, and that object stays in the queue. Use `drain_cloned` to get copies of shared
+    /// objects instead. If the iterator is dropped before it finishes, the objects it did not
+    /// yield stay in the queue.
+    pub fn drain(&mut self) -> MultiQueueDrain<'_, T> {
+        MultiQueueDrain::new(self, Self::pop_front_value)
+    }
+
+    /// The `front_block` function returns a pointer to the block at the front of the queue for
+    /// this fork, or null if this fork has no more blocks to read. This function also takes care
+    /// of pointing a fork that has not yet seen any blocks at the head of the queue.
+    fn front_block(
+        head: &mut *mut Block<T>,
+        at_end_of_queue: bool,
+        core: &Core<T>,
+    ) -> *mut Block<T> {
+        if core.empty() {
+            return std::ptr::null_mut();
+        }
+
+        if head.is_null() {
+            *head = core.head;
+        }
+
+        if at_end_of_queue {
+            unsafe { (**head).next }
+        } else {
+            *head
+        }
+    }
+
+    /// The `advance` function moves this fork past the block at the front of the queue and
+    /// updates the block reference counts. The caller must hold the core lock, and `head` must be a
+    /// valid pointer.
+    fn advance(head: &mut *mut Block<T>, at_end_of_queue: &mut bool, core: &mut Core<T>) {
+        if *at_end_of_queue {
+            // We are at the end of the queue, and we have a valid head pointer.
+            // This means that we will discard the head pointer and move to the next
+            // pointer in the list if it exists.  However, the pop front operation
+            // means that we pop the next valid block and move beyond it.  Our current
+            // head pointer is not the current valid block.
+
+            unsafe {
+                // If the next block is still null then we don't do anything else, we have
+                // no other block to move to.
+                if (**head).next.is_null() {
+                    return;
+                }
+
+                // Decrement the reference count on the current head block.
+                (**head).reference_count -= 1;
+                (**head).count_at_end_of_queue -= 1;
+                *head = (**head).next;
+            }
+
+            // Now, if the new head has a next block of null, then the pop operation
+            // will leave us at the end of the list.
+            unsafe {
+                // We are already at the end of the queue, so we only care about the
+                // case where the next block is not null.
+                if !(**head).next.is_null() {
+                    (**head).reference_count -= 1;
+                    *head = (**head).next;
+                    *at_end_of_queue = false;
+                    core.leave_end_of_queue();
+                } else {
+                    // We stay at the end of the queue, parked on the new head block.
+                    (**head).count_at_end_of_queue += 1;
+                }
+            }
+        } else {
+            // If I am not at the end of the queue, then the current head block is the
+            // next block in the queue.  I can decrement its reference count and go
+            // to the next block.
+            unsafe {
+                if (**head).next.is_null() {
+                    *at_end_of_queue = true;
+                    (**head).count_at_end_of_queue += 1;
+                    core.count_at_end_of_queue += 1;
+                } else {
+                    (**head).reference_count -= 1;
+                    *head = (**head).next;
+                }
+            }
+        }
+
+        core.update();
+    }
+
     /// The `pop_all` function removes all the objects from the queue.
     pub fn pop_all(&mut self) {
         while self.size() > 0 {
@@ -485,6 +965,36 @@ impl<T> MultiQueue<T> {
         }
     }
 
+    /// The `clear_shared` function removes every object from the queue for all forks of the queue.
+    /// Unlike `pop_all`, which only moves this fork past its objects, `clear_shared` resets the
+    /// shared queue so that every fork sees an empty queue. Objects pushed after the call are
+    /// visible to every fork as usual.
+    ///
+    /// The other forks detect the reset the next time they use the queue and release their
+    /// references to the cleared objects at that point, so the memory of an object is freed once
+    /// every fork has noticed the reset. A fork in the middle of iterating the queue, or holding a
+    /// reference from `front`, keeps seeing the cleared objects until its next operation on the
+    /// queue.
+    pub fn clear_shared(&mut self) {
+        match self.core.lock() {
+            Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
+                core.clear(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
+            }
+            Err(e) => {
+                error!("Could not lock the MultiQueue core: {}", e);
+            }
+        }
+    }
+
     /// The `fork` function creates a new `MultiQueue` object that shares the same underlying data
     /// as the original queue.
     ///
@@ -495,6 +1005,18 @@ impl<T> MultiQueue<T> {
     pub fn fork(&mut self) -> Result<MultiQueue<T>, MultiQueueError<T>> {
         match self.core.lock() {
             Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
+                // If this fork has not looked at the queue yet, then its view of the queue
+                // starts at the head of the core. Point at the head of the core now so that the
+                // walk below counts the new fork's references to the existing blocks.
+                if self.head.is_null() {
+                    self.head = core.head;
+                }
+
                 // Update the reference counts of the blocks in the queue before we create
                 // the new queue structure.
                 core.reference_count += 1;
@@ -507,6 +1029,10 @@ impl<T> MultiQueue<T> {
                 }
 
                 if self.at_end_of_queue {
+                    // The new fork is parked on the same block as we are.
+                    unsafe {
+                        (*self.head).count_at_end_of_queue += 1;
+                    }
                     core.count_at_end_of_queue += 1;
                 }
             }
@@ -519,9 +1045,23 @@ impl<T> MultiQueue<T> {
             core: self.core.clone(),
             head: self.head,
             at_end_of_queue: self.at_end_of_queue,
+            generation: self.generation,
         })
     }
 
+    /// The `snapshot` function creates a new `MultiQueue` object positioned at the same place in
+    /// the queue as this fork. A snapshot is a fork intended for transient reads: advance it and
+    /// drop it without affecting the read position of this fork. Dropping the snapshot releases
+    /// its references to the queue blocks, so taking and discarding snapshots does not leak blocks.
+    ///
+    /// # Returns
+    ///
+    /// A new `MultiQueue` object at the same read position as this fork or a `MultiQueueError`
+    /// if the snapshot operation failed.
+    pub fn snapshot(&mut self) -> Result<MultiQueue<T>, MultiQueueError<T>> {
+        self.fork()
+    }
+
     /// The `size` function returns the number of elements in the queue.
     /// If an error occurs while locking the core, then this function returns 0.
     ///
@@ -535,21 +1075,18 @@ impl<T> MultiQueue<T> {
                     return 0;
                 }
 
-                if self.at_end_of_queue {
-                    if self.head == std::ptr::null_mut() {
+                let (head, at_end_of_queue) = self.visible_cursor(&core);
+                if at_end_of_queue {
+                    if head.is_null() {
                         return core.size();
                     }
 
                     unsafe {
-                        return self.count_size_from((*self.head).next);
+                        return self.count_size_from((*head).next);
                     }
                 }
 
-                let tmp = if self.head == std::ptr::null_mut() {
-                    core.head
-                } else {
-                    self.head
-                };
+                let tmp = if head.is_null() { core.head } else { head };
                 self.count_size_from(tmp)
             }
             Err(_) => {
@@ -590,6 +1127,44 @@ impl<T> MultiQueue<T> {
         }
     }
 
+    /// The `fork_count` function returns the number of live forks of the queue, including this
+    /// one. If an error occurs while locking the core, then this function returns 0.
+    pub fn fork_count(&self) -> u32 {
+        match self.core.lock() {
+            Ok(core) => core.reference_count,
+            Err(_) => {
+                error!("Could not lock the MultiQueue core");
+                0
+            }
+        }
+    }
+
+    /// The `forks_at_end` function returns the number of forks of the queue that have read every
+    /// object in the queue and are parked at the end of the queue. When this number equals
+    /// `fork_count`, every consumer has drained the queue. If an error occurs while locking the
+    /// core, then this function returns 0.
+    pub fn forks_at_end(&self) -> u32 {
+        match self.core.lock() {
+            Ok(core) => core.count_at_end_of_queue,
+            Err(_) => {
+                error!("Could not lock the MultiQueue core");
+                0
+            }
+        }
+    }
+
+    /// The `visible_cursor` function returns the head pointer and end of queue flag of this fork
+    /// as seen through the core. A fork that has not yet synced with a `clear_shared` call from
+    /// another fork still points into the cleared blocks, so it sees the queue the way a fork that
+    /// has not yet looked at the queue would.
+    fn visible_cursor(&self, core: &Core<T>) -> (*mut Block<T>, bool) {
+        if self.generation == core.generation {
+            (self.head, self.at_end_of_queue)
+        } else {
+            (std::ptr::null_mut(), false)
+        }
+    }
+
     /// The `count_size_from` function returns the number of elements in the queue starting from
     /// the given block.
     fn count_size_from(&self, block: *mut Block<T>) -> usize {
@@ -606,32 +1181,117 @@ impl<T> MultiQueue<T> {
 
     /// The `iter` function returns an iterator over the elements in the queue.
     pub fn iter(&mut self) -> MultiQueueIterator<T> {
+        match self.core.lock() {
+            Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
+                if self.head.is_null() {
+                    self.head = core.head;
+                }
+            }
+            Err(_) => {
+                error!("Could not lock the MultiQueue core");
+            }
+        }
         MultiQueueIterator::new(self)
     }
 }
 
-impl<T> Drop for MultiQueue<T> {
-    fn drop(&mut self) {
-        // We need to pop everything off our queue so that we decrement the reference counts.
-        self.pop_all();
+impl<T: Clone> MultiQueue<T> {
+    /// The `pop_front_cloned` function removes the object at the front of the queue and returns
+    /// it to the caller. If no other fork of the queue still needs the object, the object is moved
+    /// out of the queue just like `pop_front_value`, otherwise the caller receives a clone of the
+    /// object and the original stays in the queue for the other forks.
+    ///
+    /// # Returns
+    ///
+    /// The object at the front of the queue, or `None` if the queue is empty.
+    pub fn pop_front_cloned(&mut self) -> Option<T> {
+        match self.core.lock() {
+            Ok(mut core) => {
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
+                let front = Self::front_block(&mut self.head, self.at_end_of_queue, &core);
+                if front.is_null() {
+                    return None;
+                }
 
-        // pop_all will take us to the last element of the list, but it will not decrement
-        // the reference count. Since we are dropping we need to decrement that reference
-        // count.
-        if self.head != std::ptr::null_mut() {
-            unsafe {
-                (*self.head).reference_count -= 1;
+                let value = unsafe {
+                    if (*front).reference_count - (*front).count_at_end_of_queue == 1 {
+                        (*front).taken = true;
+                        ManuallyDrop::take(&mut (*front).object)
+                    } else {
+                        (*(*front).object).clone()
+                    }
+                };
+
+                Self::advance(&mut self.head, &mut self.at_end_of_queue, &mut core);
+                Some(value)
+            }
+            Err(e) => {
+                error!("Could not lock the MultiQueue core: {}", e);
+                None
             }
         }
+    }
 
-        // Now try to decrement the core reference count.
+    /// The `drain_cloned` function returns an iterator that removes objects from the front of the
+    /// queue and yields them to the caller, like `drain`, except that objects other forks still
+    /// need are cloned instead of ending the iteration. See `pop_front_cloned`.
+    pub fn drain_cloned(&mut self) -> MultiQueueDrain<'_, T> {
+        MultiQueueDrain::new(self, Self::pop_front_cloned)
+    }
+}
+
+impl<T> Drop for MultiQueue<T> {
+    fn drop(&mut self) {
         match self.core.lock() {
             Ok(mut core) => {
+                // Release any blocks we still hold from before a `clear_shared` call.
+                core.sync_fork(
+                    &mut self.head,
+                    &mut self.at_end_of_queue,
+                    &mut self.generation,
+                );
+
+                // A fork that has not looked at the queue yet still holds references to every
+                // block in the queue.
+                if self.head.is_null() {
+                    self.head = core.head;
+                }
+
+                // Release our reference to every block from our head to the end of the queue.
+                // We do this in one pass rather than popping each block so that dropping a fork
+                // (or a snapshot) of a long queue stays cheap.
+                let mut tmp = self.head;
+                while !tmp.is_null() {
+                    unsafe {
+                        (*tmp).reference_count -= 1;
+                        tmp = (*tmp).next;
+                    }
+                }
+
+                if self.at_end_of_queue {
+                    unsafe {
+                        (*self.head).count_at_end_of_queue -= 1;
+                    }
+
+                    // This fork is no longer parked at the end of the queue.
+                    core.leave_end_of_queue();
+                }
+
                 // Decrement the reference count of the core. We do not actually
                 // delete the core because the Arc around the core will handle that
                 // deletion. We are just keeping the reference counting that handles
                 // the blocks up-to-date.
                 core.reference_count -= 1;
+                core.update();
             }
             Err(_) => {
                 error!("Could not lock the MultiQueue core");
@@ -647,7 +1307,9 @@ unsafe impl<T> Send for MultiQueue<T> {}
 unsafe impl<T> Sync for MultiQueue<T> {}
 
 pub struct MultiQueueIterator<'a, T> {
-    head: *mut Block<T>,
+    // The blocks visible to the fork when the iterator was created. The list is singly linked, so
+    // we collect the block pointers up front to be able to walk the blocks from either end.
+    blocks: std::vec::IntoIter<*mut Block<T>>,
 
     // Our iterator does not contain a reference to the core, but rather a pointer, so we use
     // the PhantomData member to ensure that the pointer has the same lifetime as the core.
@@ -656,8 +1318,25 @@ pub struct MultiQueueIterator<'a, T> {
 
 impl<'a, T> MultiQueueIterator<'a, T> {
     pub fn new(queue: &'a mut MultiQueue<T>) -> MultiQueueIterator<'a, T> {
+        // When the fork is at the end of the queue, the head block has already been read (and
+        // its object may have been moved out by `pop_front_value`), so start at the next block.
+        let head = if queue.at_end_of_queue && !queue.head.is_null() {
+            unsafe { (*queue.head).next }
+        } else {
+            queue.head
+        };
+
+        let mut blocks = Vec::new();
+        let mut tmp = head;
+        while !tmp.is_null() {
+            blocks.push(tmp);
+            unsafe {
+                tmp = (*tmp).next;
+            }
+        }
+
         MultiQueueIterator {
-            head: queue.head,
+            blocks: blocks.into_iter(),
             phantom: PhantomData,
         }
     }
@@ -667,19 +1346,72 @@ impl<'a, T> Iterator for MultiQueueIterator<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Just a reminder here that the head pointer is not actually the head inside the queue,
-        // but rather our head pointer that we copied from the queue. (I include this comment
-        // because it helped me to remember what was going on here.)
-        if self.head == std::ptr::null_mut() {
+        // The fork holds a reference to every block in our snapshot, so the blocks stay valid
+        // for the lifetime of the iterator.
+        self.blocks.next().map(|block| unsafe { &*(*block).object })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.blocks.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for MultiQueueIterator<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.blocks
+            .next_back()
+            .map(|block| unsafe { &*(*block).object })
+    }
+}
+
+/// The `MultiQueueDrain` struct is an iterator that removes objects from the front of a
+/// `MultiQueue` and yields them to the caller. See `MultiQueue::drain` and
+/// `MultiQueue::drain_cloned`.
+pub struct MultiQueueDrain<'a, T> {
+    queue: &'a mut MultiQueue<T>,
+
+    // The function that removes the object at the front of the queue.
+    pop: fn(&mut MultiQueue<T>) -> Option<T>,
+
+    // The number of objects left to yield. This number bounds the iterator to the objects that
+    // were in the queue when the iterator was created.
+    remaining: usize,
+}
+
+impl<'a, T> MultiQueueDrain<'a, T> {
+    fn new(queue: &'a mut MultiQueue<T>, pop: fn(&mut MultiQueue<T>) -> Option<T>) -> Self {
+        let remaining = queue.size();
+        MultiQueueDrain {
+            queue,
+            pop,
+            remaining,
+        }
+    }
+}
+
+impl<T> Iterator for MultiQueueDrain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
             return None;
         }
 
-        unsafe {
-            let result = Some(&(*self.head).object);
-            self.head = (*self.head).next;
-            result
+        match (self.pop)(self.queue) {
+            Some(value) => {
+                self.remaining -= 1;
+                Some(value)
+            }
+            None => {
+                self.remaining = 0;
+                None
+            }
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
 }
 
 #[cfg(test)]
@@ -728,6 +1460,68 @@ mod tests {
         assert_eq!(queue.empty(), true);
     }
 
+    #[test]
+    fn test_try_push_back() {
+        let mut queue = MultiQueue::new();
+        let mut fork = queue.fork().unwrap();
+        queue.try_push_back(1).unwrap();
+        queue.try_push_back(2).unwrap();
+        assert_eq!(queue.size(), 2);
+        assert_eq!(fork.size(), 2);
+
+        {
+            // Hold the core lock so that the fork cannot get it.
+            let _core = queue.core.lock().unwrap();
+            match fork.try_push_back(3) {
+                Err(MultiQueueError::WouldBlock(object)) => assert_eq!(object, 3),
+                _ => panic!("try_push_back should have returned WouldBlock"),
+            }
+        }
+
+        fork.try_push_back(3).unwrap();
+        assert_eq!(queue.size(), 3);
+        assert_eq!(fork.size(), 3);
+    }
+
+    #[test]
+    fn test_try_push_back_contention() {
+        let count: u32 = 1000;
+        let mut queue: MultiQueue<(usize, u32)> = MultiQueue::new();
+
+        let mut handles = Vec::new();
+        for id in 0..2 {
+            let mut producer = queue.fork().unwrap();
+            handles.push(std::thread::spawn(move || {
+                for i in 0..count {
+                    let mut item = (id, i);
+                    loop {
+                        match producer.try_push_back(item) {
+                            Ok(()) => break,
+                            Err(MultiQueueError::WouldBlock(object)) => {
+                                item = object;
+                                std::thread::yield_now();
+                            }
+                            Err(e) => panic!("unexpected error: {}", e),
+                        }
+                    }
+                }
+            }));
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Each producer's items must all be present and in the order they were pushed.
+        let mut seen = [0u32; 2];
+        while let Some(&(id, value)) = queue.front() {
+            assert_eq!(value, seen[id]);
+            seen[id] += 1;
+            queue.pop_front();
+        }
+        assert_eq!(seen, [count, count]);
+    }
+
     #[test]
     fn test_empty() {
         let mut queue = MultiQueue::new();
@@ -735,39 +1529,128 @@ mod tests {
         queue.push_back(1).unwrap();
         assert_eq!(queue.empty(), false);
         queue.pop_front();
-        assert_eq!(queue.empty(), true);
+        assert_eq!(queue.empty(), true);
+    }
+
+    #[test]
+    fn test_front() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+        queue.push_back(3).unwrap();
+        assert_eq!(queue.front(), Some(&1));
+        queue.pop_front();
+        assert_eq!(queue.front(), Some(&2));
+        queue.pop_front();
+        assert_eq!(queue.front(), Some(&3));
+        queue.pop_front();
+        assert_eq!(queue.front(), None);
+    }
+
+    #[test]
+    fn test_front_mut() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+        queue.push_back(3).unwrap();
+        assert_eq!(queue.front_mut(), Some(&mut 1));
+        queue.pop_front();
+        assert_eq!(queue.front_mut(), Some(&mut 2));
+        queue.pop_front();
+        assert_eq!(queue.front_mut(), Some(&mut 3));
+        *queue.front_mut().unwrap() = 4;
+        assert_eq!(queue.front(), Some(&4));
+        queue.pop_front();
+        assert_eq!(queue.front_mut(), None);
+    }
+
+    #[test]
+    fn test_wait_front() {
+        let mut queue = MultiQueue::new();
+        let mut producer = queue.fork().unwrap();
+
+        assert_eq!(queue.wait_front(Some(Duration::from_millis(100))), None);
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            producer.push_back(1).unwrap();
+            std::thread::sleep(Duration::from_millis(200));
+            producer.push_back(2).unwrap();
+        });
+
+        let start = Instant::now();
+        assert_eq!(queue.wait_front(Some(Duration::from_secs(5))), Some(&1));
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // Pop the only object so that the queue is parked at the end of the queue, then wait for
+        // the next object.
+        queue.pop_front();
+        assert!(queue.empty());
+        assert_eq!(queue.wait_front(None), Some(&2));
+        assert!(start.elapsed() < Duration::from_secs(2));
+        queue.pop_front();
+
+        handle.join().unwrap();
+        assert_eq!(queue.wait_front(Some(Duration::from_millis(100))), None);
     }
 
     #[test]
-    fn test_front() {
+    fn test_peek_nth() {
         let mut queue = MultiQueue::new();
+        assert_eq!(queue.peek_nth(0), None);
         queue.push_back(1).unwrap();
         queue.push_back(2).unwrap();
         queue.push_back(3).unwrap();
+        assert_eq!(queue.peek_nth(0), Some(&1));
+        assert_eq!(queue.peek_nth(1), Some(&2));
+        assert_eq!(queue.peek_nth(2), Some(&3));
+        assert_eq!(queue.peek_nth(3), None);
+
+        // Peeking must not consume anything.
+        assert_eq!(queue.size(), 3);
         assert_eq!(queue.front(), Some(&1));
         queue.pop_front();
-        assert_eq!(queue.front(), Some(&2));
-        queue.pop_front();
-        assert_eq!(queue.front(), Some(&3));
-        queue.pop_front();
-        assert_eq!(queue.front(), None);
+        assert_eq!(queue.peek_nth(0), Some(&2));
+        assert_eq!(queue.peek_nth(1), Some(&3));
+        assert_eq!(queue.peek_nth(2), None);
     }
 
     #[test]
-    fn test_front_mut() {
+    fn test_peek_nth_with_fork() {
         let mut queue = MultiQueue::new();
         queue.push_back(1).unwrap();
         queue.push_back(2).unwrap();
         queue.push_back(3).unwrap();
-        assert_eq!(queue.front_mut(), Some(&mut 1));
+
+        let mut fork = queue.fork().unwrap();
         queue.pop_front();
-        assert_eq!(queue.front_mut(), Some(&mut 2));
+
+        assert_eq!(queue.peek_nth(0), Some(&2));
+        assert_eq!(queue.peek_nth(1), Some(&3));
+        assert_eq!(queue.peek_nth(2), None);
+        assert_eq!(fork.peek_nth(0), Some(&1));
+        assert_eq!(fork.peek_nth(2), Some(&3));
+        assert_eq!(fork.peek_nth(3), None);
+
+        // Drain the parent to the end of the queue and make sure peeking respects the
+        // end-of-queue state.
         queue.pop_front();
-        assert_eq!(queue.front_mut(), Some(&mut 3));
-        *queue.front_mut().unwrap() = 4;
+        queue.pop_front();
+        assert_eq!(queue.peek_nth(0), None);
+        assert_eq!(fork.peek_nth(1), Some(&2));
+
+        queue.push_back(4).unwrap();
+        assert_eq!(queue.peek_nth(0), Some(&4));
+        assert_eq!(queue.peek_nth(1), None);
+        assert_eq!(fork.peek_nth(3), Some(&4));
+
+        // A later pop_front must still behave normally.
+        fork.pop_front();
+        assert_eq!(fork.front(), Some(&2));
+        assert_eq!(fork.size(), 3);
         assert_eq!(queue.front(), Some(&4));
         queue.pop_front();
-        assert_eq!(queue.front_mut(), None);
+        assert_eq!(queue.front(), None);
     }
 
     #[test]
@@ -791,6 +1674,163 @@ mod tests {
         assert_eq!(queue.front(), None);
     }
 
+    #[test]
+    fn test_pop_front_value() {
+        let mut queue = MultiQueue::new();
+        assert_eq!(queue.pop_front_value(), None);
+        queue.push_back(String::from("one")).unwrap();
+        queue.push_back(String::from("two")).unwrap();
+        assert_eq!(queue.pop_front_value(), Some(String::from("one")));
+        assert_eq!(queue.pop_front_value(), Some(String::from("two")));
+        assert_eq!(queue.pop_front_value(), None);
+        assert!(queue.empty());
+        assert_eq!(queue.iter().next(), None);
+
+        queue.push_back(String::from("three")).unwrap();
+        assert_eq!(queue.size(), 1);
+        assert_eq!(queue.pop_front_value(), Some(String::from("three")));
+        assert_eq!(queue.size(), 0);
+    }
+
+    #[test]
+    fn test_pop_front_value_with_fork() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+        let mut fork = queue.fork().unwrap();
+
+        // Both forks still need the objects, so they cannot be moved out.
+        assert_eq!(queue.pop_front_value(), None);
+        assert_eq!(queue.size(), 2);
+        assert_eq!(queue.pop_front_cloned(), Some(1));
+        assert_eq!(fork.front(), Some(&1));
+
+        // The fork is now the only one that needs the first object.
+        assert_eq!(fork.pop_front_value(), Some(1));
+        assert_eq!(fork.pop_front_value(), None);
+        assert_eq!(queue.pop_front_value(), None);
+
+        // The queue reads the last object and parks at the end of the queue, which leaves the
+        // fork as the only fork that still needs the object.
+        assert_eq!(queue.pop_front_cloned(), Some(2));
+        assert_eq!(fork.pop_front_value(), Some(2));
+        assert!(queue.empty());
+        assert!(fork.empty());
+    }
+
+    #[tokio::test]
+    async fn test_pop_front_value_drop() {
+        let bound = 500;
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel::<i32>();
+        {
+            let mut queue: MultiQueue<TestHelper<i32>> = MultiQueue::new();
+            let mut fork = queue.fork().unwrap();
+
+            let mut i = 0;
+            while i < bound {
+                queue.push_back(TestHelper(i, sender.clone())).unwrap();
+                i += 1;
+            }
+
+            // Let the fork move past everything so that the queue owns every object.
+            fork.pop_all();
+
+            let mut expected = 0;
+            while let Some(helper) = queue.pop_front_value() {
+                assert_eq!(helper.0, expected);
+                expected += 1;
+                // Dropping the helper sends the value to the receiver. If the queue also dropped
+                // the object we would see the value twice.
+                drop(helper);
+            }
+            assert_eq!(expected, bound);
+        }
+
+        drop(sender);
+
+        test_receiver(receiver, bound).await
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+        queue.push_back(3).unwrap();
+
+        let drained: Vec<i32> = queue.drain().collect();
+        assert_eq!(drained, vec![1, 2, 3]);
+        assert!(queue.empty());
+        assert_eq!(queue.drain().next(), None);
+    }
+
+    #[test]
+    fn test_drain_without_clone() {
+        #[derive(Debug, PartialEq)]
+        struct Token(u32);
+
+        let mut queue = MultiQueue::new();
+        queue.push_back(Token(1)).unwrap();
+        queue.push_back(Token(2)).unwrap();
+
+        let drained: Vec<Token> = queue.drain().collect();
+        assert_eq!(drained, vec![Token(1), Token(2)]);
+        assert!(queue.empty());
+    }
+
+    #[test]
+    fn test_drain_stops_at_end() {
+        let mut queue = MultiQueue::new();
+        let mut producer = queue.fork().unwrap();
+        producer.push_back(1).unwrap();
+        producer.push_back(2).unwrap();
+
+        // The producer fork also holds the objects, so copy them out.
+        let mut drain = queue.drain_cloned();
+        assert_eq!(drain.next(), Some(1));
+        producer.push_back(3).unwrap();
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.next(), None);
+
+        // The object pushed while draining is still in the queue.
+        assert_eq!(queue.size(), 1);
+        assert_eq!(queue.front(), Some(&3));
+    }
+
+    #[test]
+    fn test_drain_with_fork() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(String::from("one")).unwrap();
+        queue.push_back(String::from("two")).unwrap();
+        queue.push_back(String::from("three")).unwrap();
+        let mut fork = queue.fork().unwrap();
+
+        // The fork still needs every object, so nothing can be moved out of the queue.
+        assert_eq!(queue.drain().next(), None);
+        assert_eq!(queue.size(), 3);
+
+        // Drop the drain part way through, the rest must stay queued.
+        {
+            let mut drain = queue.drain_cloned();
+            assert_eq!(drain.next(), Some(String::from("one")));
+        }
+        assert_eq!(queue.size(), 2);
+        assert_eq!(queue.front(), Some(&String::from("two")));
+
+        // The fork still sees every object and moves out the one nobody else needs.
+        assert_eq!(fork.size(), 3);
+        let fork_drained: Vec<String> = fork.drain().collect();
+        assert_eq!(fork_drained, vec!["one"]);
+        assert_eq!(fork.size(), 2);
+
+        let drained: Vec<String> = queue.drain_cloned().collect();
+        assert_eq!(drained, vec!["two", "three"]);
+        let fork_drained: Vec<String> = fork.drain().collect();
+        assert_eq!(fork_drained, vec!["two", "three"]);
+        assert!(queue.empty());
+        assert!(fork.empty());
+    }
+
     #[test]
     fn test_pop_all() {
         let mut queue = MultiQueue::new();
@@ -946,6 +1986,83 @@ mod tests {
         assert_eq!(fork.size(), 0);
     }
 
+    #[test]
+    fn test_snapshot() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+        queue.push_back(3).unwrap();
+        queue.pop_front();
+
+        let mut snapshot = queue.snapshot().unwrap();
+        assert_eq!(snapshot.front(), Some(&2));
+        snapshot.pop_all();
+        assert_eq!(snapshot.size(), 0);
+        drop(snapshot);
+
+        assert_eq!(queue.references(), 1);
+        assert_eq!(queue.size(), 2);
+        assert_eq!(queue.front(), Some(&2));
+        queue.pop_front();
+        assert_eq!(queue.front(), Some(&3));
+        queue.pop_front();
+        assert_eq!(queue.front(), None);
+    }
+
+    #[test]
+    fn test_fork_before_first_read() {
+        let mut queue = MultiQueue::new();
+        let mut fork = queue.fork().unwrap();
+        fork.push_back(1).unwrap();
+        fork.push_back(2).unwrap();
+
+        // The queue has not looked at the core yet, so a fork of the queue must still pick up
+        // references to the blocks the other fork pushed.
+        let mut snapshot = queue.snapshot().unwrap();
+        assert_eq!(snapshot.size(), 2);
+        snapshot.pop_all();
+        drop(snapshot);
+
+        assert_eq!(queue.front(), Some(&1));
+        queue.pop_front();
+        assert_eq!(queue.front(), Some(&2));
+        assert_eq!(fork.front(), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_stress() {
+        let bound = 10000;
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel::<i32>();
+        {
+            let mut queue: MultiQueue<TestHelper<i32>> = MultiQueue::new();
+            let mut producer = queue.fork().unwrap();
+
+            let producer_sender = sender.clone();
+            let handle = std::thread::spawn(move || {
+                for i in 0..bound {
+                    producer
+                        .push_back(TestHelper(i, producer_sender.clone()))
+                        .unwrap();
+                }
+            });
+
+            for _ in 0..bound {
+                let mut snapshot = queue.snapshot().unwrap();
+                snapshot.pop_front();
+                snapshot.pop_front();
+            }
+
+            handle.join().unwrap();
+
+            assert_eq!(queue.references(), 1);
+            assert_eq!(queue.size(), bound as usize);
+        }
+
+        drop(sender);
+
+        test_receiver(receiver, bound).await
+    }
+
     #[test]
     fn test_contents_with_fork() {
         let mut queue = MultiQueue::new();
@@ -1058,6 +2175,36 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn test_iterator_rev() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+        queue.push_back(3).unwrap();
+        queue.push_back(4).unwrap();
+
+        let mut forward: Vec<i32> = queue.iter().cloned().collect();
+        forward.reverse();
+        let backward: Vec<i32> = queue.iter().rev().cloned().collect();
+        assert_eq!(backward, forward);
+
+        // Mixing both ends meets in the middle.
+        let mut iter = queue.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), Some(&3));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        // A fork at the end of the queue only sees the objects it has not read.
+        let mut fork = queue.fork().unwrap();
+        fork.pop_all();
+        assert_eq!(fork.iter().next_back(), None);
+        queue.push_back(5).unwrap();
+        assert_eq!(fork.iter().rev().collect::<Vec<_>>(), vec![&5]);
+    }
+
     #[test]
     fn test_fork_references() {
         let mut queue = MultiQueue::new();
@@ -1117,6 +2264,65 @@ mod tests {
         assert_eq!(fork.size(), 0);
     }
 
+    #[test]
+    fn test_fork_count() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+
+        let mut fork1 = queue.fork().unwrap();
+        let mut fork2 = queue.fork().unwrap();
+        let fork3 = queue.fork().unwrap();
+        assert_eq!(queue.fork_count(), 4);
+        assert_eq!(fork3.fork_count(), 4);
+        assert_eq!(queue.forks_at_end(), 0);
+
+        fork1.pop_all();
+        fork2.pop_all();
+        assert_eq!(queue.forks_at_end(), 2);
+        assert_eq!(fork3.forks_at_end(), 2);
+
+        drop(fork3);
+        assert_eq!(queue.fork_count(), 3);
+    }
+
+    #[test]
+    fn test_drop_fork_at_end_of_queue() {
+        let mut queue = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+
+        let mut fork = queue.fork().unwrap();
+        fork.pop_all();
+        assert_eq!(queue.forks_at_end(), 1);
+
+        // Dropping a fork parked at the end of the queue must take it out of the count.
+        drop(fork);
+        assert_eq!(queue.fork_count(), 1);
+        assert_eq!(queue.forks_at_end(), 0);
+
+        queue.pop_front();
+        queue.pop_front();
+        assert_eq!(queue.forks_at_end(), 1);
+        assert_eq!(queue.shared_size(), 0);
+
+        queue.push_back(3).unwrap();
+        assert_eq!(queue.front(), Some(&3));
+        assert_eq!(queue.forks_at_end(), 0);
+        queue.pop_front();
+        queue.push_back(4).unwrap();
+        queue.pop_front();
+        assert_eq!(queue.forks_at_end(), 1);
+        assert_eq!(queue.size(), 0);
+    }
+
+    #[test]
+    fn test_leave_end_of_queue_saturates() {
+        let mut core: Core<i32> = Core::new();
+        core.leave_end_of_queue();
+        assert_eq!(core.count_at_end_of_queue, 0);
+    }
+
     const BUFFER_SIZE: usize = 8192;
 
     #[test]
@@ -1442,4 +2648,100 @@ mod tests {
 
         test_receiver(receiver, bound).await
     }
+
+    #[test]
+    fn test_clear_shared() {
+        let mut queue: MultiQueue<i32> = MultiQueue::new();
+        let mut fork = queue.fork().unwrap();
+
+        queue.push_back(1).unwrap();
+        queue.push_back(2).unwrap();
+        queue.push_back(3).unwrap();
+        assert_eq!(fork.front(), Some(&1));
+
+        queue.clear_shared();
+        assert!(queue.empty());
+        assert!(fork.empty());
+        assert_eq!(fork.size(), 0);
+        assert_eq!(fork.peek_nth(0), None);
+        assert_eq!(fork.front(), None);
+
+        // Both forks see objects pushed after the reset.
+        fork.push_back(4).unwrap();
+        queue.push_back(5).unwrap();
+        assert_eq!(queue.iter().collect::<Vec<_>>(), vec![&4, &5]);
+        assert_eq!(fork.iter().collect::<Vec<_>>(), vec![&4, &5]);
+        assert_eq!(fork.pop_front_cloned(), Some(4));
+        assert_eq!(queue.pop_front_cloned(), Some(4));
+        assert_eq!(queue.pop_front_value(), None);
+        assert_eq!(fork.pop_front_value(), None);
+        queue.pop_front();
+        assert_eq!(fork.pop_front_value(), Some(5));
+        assert!(queue.empty());
+        assert!(fork.empty());
+    }
+
+    #[test]
+    fn test_clear_shared_at_end_of_queue() {
+        let mut queue: MultiQueue<i32> = MultiQueue::new();
+        queue.push_back(1).unwrap();
+        let mut fork = queue.fork().unwrap();
+        fork.pop_front();
+        assert_eq!(fork.forks_at_end(), 1);
+
+        queue.clear_shared();
+        assert_eq!(queue.forks_at_end(), 0);
+        assert!(fork.empty());
+
+        queue.push_back(2).unwrap();
+        assert_eq!(fork.front(), Some(&2));
+        assert_eq!(queue.front(), Some(&2));
+
+        // Clear twice before the fork notices.
+        queue.clear_shared();
+        queue.push_back(3).unwrap();
+        queue.clear_shared();
+        assert!(fork.empty());
+        queue.push_back(4).unwrap();
+        assert_eq!(fork.pop_front_cloned(), Some(4));
+
+        // A fork created after the reset starts with the queue as every other fork sees it.
+        let late = fork.fork().unwrap();
+        assert!(late.empty());
+        let mut other = queue.fork().unwrap();
+        assert_eq!(other.front(), Some(&4));
+        assert_eq!(late.fork_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_clear_shared_drop() {
+        let bound = 100;
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel::<i32>();
+        {
+            let mut queue: MultiQueue<TestHelper<i32>> = MultiQueue::new();
+            let mut fork = queue.fork().unwrap();
+            let snapshot = fork.snapshot().unwrap();
+
+            let mut i = 0;
+            while i < bound / 2 {
+                queue.push_back(TestHelper(i, sender.clone())).unwrap();
+                i += 1;
+            }
+            fork.pop_front();
+
+            // The blocks stay alive until every fork has noticed the reset.
+            queue.clear_shared();
+            drop(fork);
+            drop(snapshot);
+
+            while i < bound {
+                queue.push_back(TestHelper(i, sender.clone())).unwrap();
+                i += 1;
+            }
+        }
+
+        drop(sender);
+
+        test_receiver(receiver, bound).await
+    }
 }
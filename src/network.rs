@@ -1,12 +1,15 @@
 pub use dhcprange::DHCPRange;
 pub use interfaceaddr::InterfaceAddr;
 pub use ipaddrquery::IpAddrQuery as IPAddrQuery;
+pub use macaddress::MacAddress;
 pub use networkconfiguration::AddressMode;
 pub use networkconfiguration::NetworkConfiguration;
 pub use networkinterface::NetworkInterface;
 pub use networkinterfaces::NetworkInterfaces;
 pub use networkmanager::NetworkManager;
 pub use networkservice::NetworkService;
+pub use route::Route;
+pub use wireless::configuration::SecurityProtocol;
 pub use wireless::configuration::WirelessConfiguration;
 pub use wireless::configuration::WirelessMode;
 pub use wireless::configuration::WirelessStandard;
@@ -14,6 +17,7 @@ pub use wireless::configuration::WirelessStandard;
 pub mod dhcprange;
 pub mod interfaceaddr;
 pub mod ipaddrquery;
+pub mod macaddress;
 mod netmask;
 pub mod networkconfiguration;
 pub mod networkinterface;
@@ -21,6 +25,7 @@ pub mod networkinterfacequery;
 pub mod networkinterfaces;
 pub mod networkmanager;
 pub mod networkservice;
+pub mod route;
 pub mod wireless;
 
 cfg_if! {
@@ -29,5 +34,6 @@ cfg_if! {
         mod dnsmasqservice;
         mod hostapdservice;
         mod netplanservice;
+        mod nmcliservice;
     }
 }
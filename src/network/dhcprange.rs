@@ -1,36 +1,133 @@
 //! The `dhcprange` module provides a structure to represent a range of IP addresses used for DHCP.
 
 use crate::error::FoundationError;
+use crate::network::interfaceaddr::InterfaceAddr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
+use std::time::Duration;
+
+/// The lease time used when a DHCP range does not specify one.
+const DEFAULT_LEASE_TIME: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// The lease time of a DHCP range whose leases never expire, written as `infinite` by dnsmasq.
+pub const INFINITE_LEASE_TIME: Duration = Duration::MAX;
 
 /// The `DHCPRange` struct represents a range of IP addresses used for DHCP.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DHCPRange {
     /// The starting IP address of the range.
     pub start: IpAddr,
 
     /// The ending IP address of the range.
     pub end: IpAddr,
+
+    /// How long a client may keep an address leased from the range. `INFINITE_LEASE_TIME` means
+    /// the leases never expire.
+    pub lease_time: Duration,
 }
 
 impl DHCPRange {
-    /// Create a new `DHCPRange` instance.
+    /// Create a new `DHCPRange` instance with the default lease time of 12 hours.
     ///
     /// # Arguments
     ///
     /// * `start` - The starting IP address of the range.
     /// * `end` - The ending IP address of the range.
     pub fn new(start: IpAddr, end: IpAddr) -> Self {
-        DHCPRange { start, end }
+        DHCPRange::new_with_lease_time(start, end, DEFAULT_LEASE_TIME)
+    }
+
+    /// Create a new `DHCPRange` instance with a lease time.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The starting IP address of the range.
+    /// * `end` - The ending IP address of the range.
+    /// * `lease_time` - How long a client may keep an address leased from the range.
+    pub fn new_with_lease_time(start: IpAddr, end: IpAddr, lease_time: Duration) -> Self {
+        DHCPRange {
+            start,
+            end,
+            lease_time,
+        }
+    }
+
+    /// Check if the range lies inside the subnet of an interface address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The interface address. The address must have a netmask.
+    ///
+    /// # Returns
+    ///
+    /// `true` if both the start and the end of the range are in the subnet of the address,
+    /// `false` otherwise.
+    pub fn is_in_subnet(&self, address: &InterfaceAddr) -> bool {
+        let network = match address.network_address() {
+            Some(network) => network,
+            None => return false,
+        };
+        [self.start, self.end].iter().all(|ip| {
+            InterfaceAddr::new(*ip, None, address.netmask).network_address() == Some(network)
+        })
     }
 }
 
+/// Parse a dnsmasq lease time such as `3600`, `45m`, `12h`, `2d`, `1w` or `infinite`. Units
+/// may be upper or lower case.
+fn parse_lease_time(value: &str) -> Result<Duration, FoundationError> {
+    if value.eq_ignore_ascii_case("infinite") {
+        return Ok(INFINITE_LEASE_TIME);
+    }
+
+    let (number, multiplier) = match value.char_indices().last() {
+        Some((index, unit)) => match unit.to_ascii_lowercase() {
+            'm' => (&value[..index], 60),
+            'h' => (&value[..index], 60 * 60),
+            'd' => (&value[..index], 24 * 60 * 60),
+            'w' => (&value[..index], 7 * 24 * 60 * 60),
+            _ => (value, 1),
+        },
+        None => (value, 1),
+    };
+    let number: u64 = number.parse()?;
+    match number.checked_mul(multiplier) {
+        Some(seconds) => Ok(Duration::from_secs(seconds)),
+        None => Err(FoundationError::OperationFailed(format!(
+            "lease time {} is too long",
+            value
+        ))),
+    }
+}
+
+/// Format a lease time in the largest whole dnsmasq unit.
+fn format_lease_time(lease_time: Duration) -> String {
+    if lease_time == INFINITE_LEASE_TIME {
+        return "infinite".to_string();
+    }
+
+    let seconds = lease_time.as_secs();
+    for (unit, size) in [
+        ("w", 7 * 24 * 60 * 60),
+        ("d", 24 * 60 * 60),
+        ("h", 60 * 60),
+        ("m", 60),
+    ] {
+        if seconds > 0 && seconds % size == 0 {
+            return format!("{}{}", seconds / size, unit);
+        }
+    }
+    seconds.to_string()
+}
+
 impl TryFrom<&str> for DHCPRange {
     type Error = FoundationError;
 
     /// Attempt to parse a `DHCPRange` from a string.
     ///
-    /// The string should be in the format `start,end`.
+    /// The string should be in the format `start,end[,netmask][,lease_time]`.
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         if value.contains(',') {
             let parts = value.split(',').collect::<Vec<&str>>();
@@ -41,7 +138,15 @@ impl TryFrom<&str> for DHCPRange {
             }
             let start = parts[0].parse()?;
             let end = parts[1].parse()?;
-            return Ok(DHCPRange::new(start, end));
+
+            // dnsmasq allows an optional netmask before the lease time.
+            let lease_time = match parts[2..].last() {
+                Some(lease_time) if lease_time.parse::<IpAddr>().is_err() => {
+                    parse_lease_time(lease_time)?
+                }
+                _ => DEFAULT_LEASE_TIME,
+            };
+            return Ok(DHCPRange::new_with_lease_time(start, end, lease_time));
         }
         Err(FoundationError::OperationFailed(
             "value does not contain a valid DHCP range".to_string(),
@@ -51,6 +156,60 @@ impl TryFrom<&str> for DHCPRange {
 
 impl std::fmt::Display for DHCPRange {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{},{}", self.start, self.end)
+        write!(
+            f,
+            "{},{},{}",
+            self.start,
+            self.end,
+            format_lease_time(self.lease_time)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let range = DHCPRange::try_from("192.168.4.2,192.168.4.20,24h").unwrap();
+        assert_eq!(range.start, "192.168.4.2".parse::<IpAddr>().unwrap());
+        assert_eq!(range.end, "192.168.4.20".parse::<IpAddr>().unwrap());
+        assert_eq!(range.lease_time, Duration::from_secs(24 * 60 * 60));
+        assert_eq!(range.to_string(), "192.168.4.2,192.168.4.20,1d");
+
+        let range = DHCPRange::try_from("192.168.4.2,192.168.4.20").unwrap();
+        assert_eq!(range.lease_time, DEFAULT_LEASE_TIME);
+        assert_eq!(range.to_string(), "192.168.4.2,192.168.4.20,12h");
+
+        let range = DHCPRange::try_from("10.0.0.5,10.0.0.50,255.255.255.0,45m").unwrap();
+        assert_eq!(range.lease_time, Duration::from_secs(45 * 60));
+
+        let range = DHCPRange::try_from("10.0.0.5,10.0.0.50,255.255.255.0").unwrap();
+        assert_eq!(range.lease_time, DEFAULT_LEASE_TIME);
+
+        let range = DHCPRange::try_from("10.0.0.5,10.0.0.50,90").unwrap();
+        assert_eq!(range.lease_time, Duration::from_secs(90));
+        assert_eq!(range.to_string(), "10.0.0.5,10.0.0.50,90");
+
+        let range = DHCPRange::try_from("10.0.0.5,10.0.0.50,12H").unwrap();
+        assert_eq!(range.lease_time, Duration::from_secs(12 * 60 * 60));
+
+        let range = DHCPRange::try_from("10.0.0.5,10.0.0.50,infinite").unwrap();
+        assert_eq!(range.lease_time, INFINITE_LEASE_TIME);
+        assert_eq!(range.to_string(), "10.0.0.5,10.0.0.50,infinite");
+
+        assert!(DHCPRange::try_from("10.0.0.5,10.0.0.50,18446744073709551615w").is_err());
+        assert!(DHCPRange::try_from("10.0.0.5").is_err());
+        assert!(DHCPRange::try_from("10.0.0.5,10.0.0.50,soon").is_err());
+    }
+
+    #[test]
+    fn test_is_in_subnet() {
+        let range = DHCPRange::try_from("192.168.4.2,192.168.4.20,24h").unwrap();
+        assert!(range.is_in_subnet(&InterfaceAddr::from_cidr("192.168.4.1/24").unwrap()));
+        assert!(!range.is_in_subnet(&InterfaceAddr::from_cidr("192.168.4.1/28").unwrap()));
+        assert!(!range.is_in_subnet(&InterfaceAddr::from_cidr("192.168.5.1/24").unwrap()));
+        assert!(!range.is_in_subnet(&InterfaceAddr::try_from("192.168.4.1").unwrap()));
     }
 }
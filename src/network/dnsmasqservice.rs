@@ -70,10 +70,29 @@ impl NetworkService for DNSMasqService {
                 let mut config_map: HashMap<String, String> = HashMap::new();
                 config_map.insert("interface".to_string(), name.clone());
                 if let Some(dhcp_range) = &config.dhcp_range {
-                    config_map.insert(
-                        "dhcp-range".to_string(),
-                        format!("{},{},12h", dhcp_range.start, dhcp_range.end),
-                    );
+                    // When the interface has an address for the range, the range must be in the
+                    // same subnet or the clients will not be able to reach the interface.
+                    let subnet_addresses = config
+                        .interface
+                        .addresses
+                        .iter()
+                        .filter(|address| {
+                            address.netmask.is_some()
+                                && address.ip.is_ipv4() == dhcp_range.start.is_ipv4()
+                        })
+                        .collect::<Vec<_>>();
+                    if !subnet_addresses.is_empty()
+                        && !subnet_addresses
+                            .iter()
+                            .any(|address| dhcp_range.is_in_subnet(address))
+                    {
+                        return Err(FoundationError::OperationFailed(format!(
+                            "DHCP range {} is not in the subnet of {}",
+                            dhcp_range, name
+                        )));
+                    }
+
+                    config_map.insert("dhcp-range".to_string(), dhcp_range.to_string());
                 }
                 config_map.insert("port".to_string(), "0".to_string());
                 config_map.insert("bogus-priv".to_string(), String::new());
@@ -106,9 +125,11 @@ impl NetworkService for DNSMasqService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::network::interfaceaddr::InterfaceAddr;
     use crate::network::networkconfiguration::AddressMode;
     use crate::network::networkinterface::NetworkInterface;
-    use crate::network::wireless::configuration::WirelessConfiguration;
+    use crate::network::wireless::configuration::{WirelessConfiguration, WirelessMode};
+    use std::time::Duration;
 
     // Note that this service can lose configuration fidelity in the sense that the dnsmasq configuration
     // file does not contain all settings supported by this library's notion of a network configuration.
@@ -156,4 +177,76 @@ mod tests {
 
         dnsmasq_service.remove_config_file().unwrap();
     }
+
+    #[test]
+    fn test_dnsmasq_access_point_round_trip() {
+        let mut interface = NetworkInterface::new_with_name("wlan0");
+        interface
+            .addresses
+            .push(InterfaceAddr::from_cidr("192.168.4.1/24").unwrap());
+        let wifi_config = WirelessConfiguration {
+            mode: WirelessMode::AccessPoint,
+            ..Default::default()
+        };
+        let dhcp_range = DHCPRange::new_with_lease_time(
+            "192.168.4.2".parse().unwrap(),
+            "192.168.4.20".parse().unwrap(),
+            Duration::from_secs(24 * 60 * 60),
+        );
+        let config = NetworkConfiguration::new(
+            AddressMode::Static,
+            interface,
+            true,
+            Some(wifi_config),
+            Some(dhcp_range.clone()),
+        );
+        let mut config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        config_map.insert("wlan0".to_string(), config);
+
+        let mut dnsmasq_service = DNSMasqService::new(PathBuf::from("/tmp/dnsmasq_ap.conf"));
+        dnsmasq_service.write_configuration(&config_map).unwrap();
+
+        let contents = std::fs::read_to_string("/tmp/dnsmasq_ap.conf").unwrap();
+        assert!(contents.contains("interface=wlan0"));
+        assert!(contents.contains("dhcp-range=192.168.4.2,192.168.4.20,1d"));
+
+        let mut other_config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        dnsmasq_service
+            .load_configuration(&mut other_config_map)
+            .unwrap();
+        assert_eq!(
+            other_config_map.get("wlan0").unwrap().dhcp_range,
+            Some(dhcp_range)
+        );
+
+        dnsmasq_service.remove_config_file().unwrap();
+    }
+
+    #[test]
+    fn test_dnsmasq_range_outside_subnet() {
+        let mut interface = NetworkInterface::new_with_name("wlan0");
+        interface
+            .addresses
+            .push(InterfaceAddr::from_cidr("192.168.4.1/24").unwrap());
+        let wifi_config = WirelessConfiguration {
+            mode: WirelessMode::AccessPoint,
+            ..Default::default()
+        };
+        let config = NetworkConfiguration::new(
+            AddressMode::Static,
+            interface,
+            true,
+            Some(wifi_config),
+            Some(DHCPRange::new(
+                "192.168.4.2".parse().unwrap(),
+                "192.168.5.20".parse().unwrap(),
+            )),
+        );
+        let mut config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        config_map.insert("wlan0".to_string(), config);
+
+        let dnsmasq_service = DNSMasqService::new(PathBuf::from("/tmp/dnsmasq_bad_range.conf"));
+        assert!(dnsmasq_service.write_configuration(&config_map).is_err());
+        assert!(!PathBuf::from("/tmp/dnsmasq_bad_range.conf").exists());
+    }
 }
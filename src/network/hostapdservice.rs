@@ -6,7 +6,7 @@ use crate::keyvalueconfigfile::KeyValueConfigFile;
 use crate::network::networkconfiguration::NetworkConfiguration;
 use crate::network::networkservice::NetworkService;
 use crate::network::wireless::configuration::{
-    WirelessConfiguration, WirelessMode, WirelessStandard,
+    SecurityProtocol, WirelessConfiguration, WirelessMode, WirelessStandard,
 };
 use crate::systemctlservice::SystemCTLService;
 use std::collections::HashMap;
@@ -36,6 +36,81 @@ impl HostAPDService {
     }
 }
 
+/// The HostAPD keys that are decided by a `SecurityProtocol`.
+const SECURITY_PROTOCOL_KEYS: [&str; 5] = [
+    "wpa",
+    "wpa_key_mgmt",
+    "wpa_pairwise",
+    "rsn_pairwise",
+    "ieee80211w",
+];
+
+/// Get the HostAPD keys and values for a security protocol. Keys from `SECURITY_PROTOCOL_KEYS`
+/// that are not in the result must not appear in the configuration.
+///
+/// # Arguments
+///
+/// * `protocol` - The security protocol.
+///
+/// # Returns
+///
+/// A vector of HostAPD key and value pairs.
+fn security_protocol_values(protocol: SecurityProtocol) -> Vec<(&'static str, &'static str)> {
+    match protocol {
+        SecurityProtocol::Open => vec![],
+        SecurityProtocol::WPA2Personal => vec![
+            ("wpa", "2"),
+            ("wpa_key_mgmt", "WPA-PSK"),
+            ("rsn_pairwise", "CCMP"),
+        ],
+        SecurityProtocol::WPA3Personal => vec![
+            ("wpa", "2"),
+            ("wpa_key_mgmt", "SAE"),
+            ("rsn_pairwise", "CCMP"),
+            ("ieee80211w", "2"),
+        ],
+        SecurityProtocol::WPA2WPA3Mixed => vec![
+            ("wpa", "2"),
+            ("wpa_key_mgmt", "WPA-PSK SAE"),
+            ("rsn_pairwise", "CCMP"),
+            ("ieee80211w", "1"),
+        ],
+    }
+}
+
+/// The highest channel number in the 2.4 GHz band. Higher channels are in the 5 GHz band.
+const MAX_2_4_GHZ_CHANNEL: u32 = 14;
+
+/// Infer the security protocol from HostAPD configuration values.
+///
+/// # Arguments
+///
+/// * `values` - The HostAPD configuration values.
+///
+/// # Returns
+///
+/// The security protocol whose keys exactly match the configuration values, or `None` if the
+/// configuration does not match any protocol.
+fn security_protocol_from_values(values: &HashMap<String, String>) -> Option<SecurityProtocol> {
+    [
+        SecurityProtocol::Open,
+        SecurityProtocol::WPA2Personal,
+        SecurityProtocol::WPA3Personal,
+        SecurityProtocol::WPA2WPA3Mixed,
+    ]
+    .into_iter()
+    .find(|protocol| {
+        let protocol_values = security_protocol_values(*protocol);
+        SECURITY_PROTOCOL_KEYS.iter().all(|key| {
+            let expected = protocol_values
+                .iter()
+                .find(|(protocol_key, _)| protocol_key == key)
+                .map(|(_, value)| *value);
+            values.get(*key).map(String::as_str) == expected
+        })
+    })
+}
+
 impl NetworkService for HostAPDService {
     fn load_configuration(
         &mut self,
@@ -61,8 +136,10 @@ impl NetworkService for HostAPDService {
                 config_map.get_mut(interface_name).unwrap()
             };
 
-            let mut wifi_config = WirelessConfiguration::default();
-            wifi_config.mode = WirelessMode::AccessPoint;
+            let mut wifi_config = WirelessConfiguration {
+                mode: WirelessMode::AccessPoint,
+                ..Default::default()
+            };
 
             if let Some(ssid_str) = configuration.get("ssid") {
                 wifi_config.ssid = ssid_str.to_string();
@@ -78,6 +155,16 @@ impl NetworkService for HostAPDService {
                 }
             }
 
+            // HostAPD enables 802.11n on top of the a or g hardware mode. The band is recovered
+            // from the channel when the configuration is written.
+            if configuration.get("ieee80211n").map(String::as_str) == Some("1") {
+                wifi_config.standard = WirelessStandard::N;
+            }
+
+            if let Some(country_code_str) = configuration.get("country_code") {
+                wifi_config.country_code = Some(country_code_str.to_string());
+            }
+
             if let Some(channel_str) = configuration.get("channel") {
                 wifi_config.channel = channel_str.parse()?;
             }
@@ -86,20 +173,24 @@ impl NetworkService for HostAPDService {
                 wifi_config.password = Some(password_str.to_string());
             }
 
-            if let Some(wpa_mode_str) = configuration.get("wpa") {
-                wifi_config.wpa_mode = wpa_mode_str.parse()?;
-            }
+            if let Some(protocol) = security_protocol_from_values(&configuration) {
+                wifi_config.security_protocol = Some(protocol);
+            } else {
+                if let Some(wpa_mode_str) = configuration.get("wpa") {
+                    wifi_config.wpa_mode = wpa_mode_str.parse()?;
+                }
 
-            if let Some(wpa_key_mgmt_str) = configuration.get("wpa_key_mgmt") {
-                wifi_config.wpa_key_mgmt = Some(wpa_key_mgmt_str.to_string());
-            }
+                if let Some(wpa_key_mgmt_str) = configuration.get("wpa_key_mgmt") {
+                    wifi_config.wpa_key_mgmt = Some(wpa_key_mgmt_str.to_string());
+                }
 
-            if let Some(wpa_pairwise_str) = configuration.get("wpa_pairwise") {
-                wifi_config.wpa_pairwise = Some(wpa_pairwise_str.to_string());
-            }
+                if let Some(wpa_pairwise_str) = configuration.get("wpa_pairwise") {
+                    wifi_config.wpa_pairwise = Some(wpa_pairwise_str.to_string());
+                }
 
-            if let Some(rsn_pairwise_str) = configuration.get("rsn_pairwise") {
-                wifi_config.rsn_pairwise = Some(rsn_pairwise_str.to_string());
+                if let Some(rsn_pairwise_str) = configuration.get("rsn_pairwise") {
+                    wifi_config.rsn_pairwise = Some(rsn_pairwise_str.to_string());
+                }
             }
 
             config.wifi_configuration = Some(wifi_config);
@@ -132,34 +223,61 @@ impl NetworkService for HostAPDService {
                     WirelessStandard::A => value_map.insert(hw_mode, "a".to_string()),
                     WirelessStandard::B => value_map.insert(hw_mode, "b".to_string()),
                     WirelessStandard::G => value_map.insert(hw_mode, "g".to_string()),
-                    WirelessStandard::N => value_map.insert(hw_mode, "n".to_string()),
+                    WirelessStandard::N => {
+                        // HostAPD has no "n" hardware mode, 802.11n runs on top of "a" in the
+                        // 5 GHz band or "g" in the 2.4 GHz band, and needs WMM.
+                        value_map.insert("ieee80211n".to_string(), "1".to_string());
+                        value_map.insert("wmm_enabled".to_string(), "1".to_string());
+                        let band = if wifi_config.channel > MAX_2_4_GHZ_CHANNEL {
+                            "a"
+                        } else {
+                            "g"
+                        };
+                        value_map.insert(hw_mode, band.to_string())
+                    }
                 };
 
                 value_map.insert("channel".to_string(), wifi_config.channel.to_string());
+
+                if let Some(country_code_str) = &wifi_config.country_code {
+                    value_map.insert("country_code".to_string(), country_code_str.clone());
+                    value_map.insert("ieee80211d".to_string(), "1".to_string());
+                }
+
                 value_map.insert("macaddr_acl".to_string(), "0".to_string());
                 value_map.insert("auth_algs".to_string(), "1".to_string());
                 value_map.insert("ignore_broadcast_ssid".to_string(), "0".to_string());
-                value_map.insert("wpa".to_string(), wifi_config.wpa_mode.to_string());
                 if let Some(password_str) = &wifi_config.password {
-                    value_map.insert("wpa_passphrase".to_string(), password_str.clone());
+                    if wifi_config.security_protocol != Some(SecurityProtocol::Open) {
+                        value_map.insert("wpa_passphrase".to_string(), password_str.clone());
+                    }
                 }
 
-                if let Some(wpa_key_management_str) = &wifi_config.wpa_key_mgmt {
-                    value_map.insert("wpa_key_mgmt".to_string(), wpa_key_management_str.clone());
+                if let Some(protocol) = wifi_config.security_protocol {
+                    for (key, value) in security_protocol_values(protocol) {
+                        value_map.insert(key.to_string(), value.to_string());
+                    }
                 } else {
-                    value_map.insert("wpa_key_mgmt".to_string(), "WPA-PSK".to_string());
-                }
-
-                if let Some(wpa_pairwise_str) = &wifi_config.wpa_pairwise {
-                    value_map.insert("wpa_pairwise".to_string(), wpa_pairwise_str.clone());
-                } else {
-                    value_map.insert("wpa_pairwise".to_string(), "TKIP".to_string());
-                }
-
-                if let Some(rsn_pairwise_str) = &wifi_config.rsn_pairwise {
-                    value_map.insert("rsn_pairwise".to_string(), rsn_pairwise_str.clone());
-                } else {
-                    value_map.insert("rsn_pairwise".to_string(), "CCMP".to_string());
+                    value_map.insert("wpa".to_string(), wifi_config.wpa_mode.to_string());
+
+                    if let Some(wpa_key_management_str) = &wifi_config.wpa_key_mgmt {
+                        value_map
+                            .insert("wpa_key_mgmt".to_string(), wpa_key_management_str.clone());
+                    } else {
+                        value_map.insert("wpa_key_mgmt".to_string(), "WPA-PSK".to_string());
+                    }
+
+                    if let Some(wpa_pairwise_str) = &wifi_config.wpa_pairwise {
+                        value_map.insert("wpa_pairwise".to_string(), wpa_pairwise_str.clone());
+                    } else {
+                        value_map.insert("wpa_pairwise".to_string(), "TKIP".to_string());
+                    }
+
+                    if let Some(rsn_pairwise_str) = &wifi_config.rsn_pairwise {
+                        value_map.insert("rsn_pairwise".to_string(), rsn_pairwise_str.clone());
+                    } else {
+                        value_map.insert("rsn_pairwise".to_string(), "CCMP".to_string());
+                    }
                 }
 
                 let key_value_config = KeyValueConfigFile::new(self.filename.clone());
@@ -202,16 +320,18 @@ mod tests {
     #[test]
     fn test_hostapd_service() {
         let interface = NetworkInterface::new_with_name("wlan0");
-        let mut wifi_config = WirelessConfiguration::default();
-        wifi_config.mode = WirelessMode::AccessPoint;
-        wifi_config.ssid = "HoneyBadgerHut".to_string();
-        wifi_config.password = Some("NUTHUT".to_string());
-        wifi_config.standard = WirelessStandard::N;
-        wifi_config.channel = 5;
-        wifi_config.wpa_mode = 8;
-        wifi_config.wpa_key_mgmt = Some("WPA-PSK".to_string());
-        wifi_config.wpa_pairwise = Some("BUBBA".to_string());
-        wifi_config.rsn_pairwise = Some("FLUBBA".to_string());
+        let wifi_config = WirelessConfiguration {
+            mode: WirelessMode::AccessPoint,
+            ssid: "HoneyBadgerHut".to_string(),
+            password: Some("NUTHUT".to_string()),
+            standard: WirelessStandard::N,
+            channel: 5,
+            wpa_mode: 8,
+            wpa_key_mgmt: Some("WPA-PSK".to_string()),
+            wpa_pairwise: Some("BUBBA".to_string()),
+            rsn_pairwise: Some("FLUBBA".to_string()),
+            ..Default::default()
+        };
         let config =
             NetworkConfiguration::new(AddressMode::DHCP, interface, true, Some(wifi_config), None);
         let mut config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
@@ -232,5 +352,239 @@ mod tests {
         other_config_map.insert("wlan0".to_string(), other_config);
         let result = hostapd_service.load_configuration(&mut other_config_map);
         assert!(result.is_ok());
+        assert_eq!(other_config_map, config_map);
+
+        hostapd_service.remove_config_file().unwrap();
+    }
+
+    #[test]
+    fn test_hostapd_service_round_trip() {
+        let interface = NetworkInterface::new_with_name("wlan1");
+        let wifi_config = WirelessConfiguration {
+            ssid: "BadgerDen".to_string(),
+            standard: WirelessStandard::N,
+            mode: WirelessMode::AccessPoint,
+            password: Some("Honey Comb".to_string()),
+            channel: 11,
+            wpa_mode: 2,
+            wpa_key_mgmt: Some("WPA-PSK".to_string()),
+            wpa_pairwise: Some("CCMP".to_string()),
+            rsn_pairwise: Some("CCMP".to_string()),
+            country_code: Some("US".to_string()),
+            ..Default::default()
+        };
+        let config =
+            NetworkConfiguration::new(AddressMode::DHCP, interface, true, Some(wifi_config), None);
+        let mut config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        config_map.insert("wlan1".to_string(), config);
+
+        let path = PathBuf::from("/tmp/hostapd_round_trip.conf");
+
+        // Leave a longer file in place to make sure writing replaces the old contents.
+        std::fs::write(&path, "#".repeat(4096)).unwrap();
+
+        let mut hostapd_service = HostAPDService::new(path.clone());
+        hostapd_service.write_configuration(&config_map).unwrap();
+
+        let written = KeyValueConfigFile::new(path.clone())
+            .load_configuration()
+            .unwrap();
+        assert_eq!(written.get("hw_mode").unwrap(), "g");
+        assert_eq!(written.get("ieee80211n").unwrap(), "1");
+        assert_eq!(written.get("wmm_enabled").unwrap(), "1");
+        assert_eq!(written.get("channel").unwrap(), "11");
+        assert_eq!(written.get("country_code").unwrap(), "US");
+        assert_eq!(written.get("wpa").unwrap(), "2");
+        assert_eq!(written.get("wpa_passphrase").unwrap(), "Honey Comb");
+
+        let mut other_config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        let other_interface = NetworkInterface::new_with_name("wlan1");
+        let other_config =
+            NetworkConfiguration::new(AddressMode::DHCP, other_interface, true, None, None);
+        other_config_map.insert("wlan1".to_string(), other_config);
+        hostapd_service
+            .load_configuration(&mut other_config_map)
+            .unwrap();
+        assert_eq!(other_config_map, config_map);
+
+        hostapd_service.remove_config_file().unwrap();
+    }
+
+    #[test]
+    fn test_hostapd_service_5ghz_round_trip() {
+        let interface = NetworkInterface::new_with_name("wlan2");
+        let wifi_config = WirelessConfiguration {
+            mode: WirelessMode::AccessPoint,
+            ssid: "BadgerBurrow".to_string(),
+            password: Some("Honey Comb".to_string()),
+            standard: WirelessStandard::N,
+            channel: 36,
+            security_protocol: Some(SecurityProtocol::WPA2Personal),
+            ..Default::default()
+        };
+        let config =
+            NetworkConfiguration::new(AddressMode::DHCP, interface, true, Some(wifi_config), None);
+        let mut config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        config_map.insert("wlan2".to_string(), config);
+
+        let path = PathBuf::from("/tmp/hostapd_5ghz_round_trip.conf");
+        std::fs::write(
+            &path,
+            "interface=wlan2\nssid=BadgerBurrow\nhw_mode=a\nieee80211n=1\nchannel=36\n\
+             wpa=2\nwpa_key_mgmt=WPA-PSK\nrsn_pairwise=CCMP\nwpa_passphrase=Honey Comb\n",
+        )
+        .unwrap();
+
+        let mut hostapd_service = HostAPDService::new(path.clone());
+        let mut loaded_config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        loaded_config_map.insert(
+            "wlan2".to_string(),
+            NetworkConfiguration::new(
+                AddressMode::DHCP,
+                NetworkInterface::new_with_name("wlan2"),
+                true,
+                None,
+                None,
+            ),
+        );
+        hostapd_service
+            .load_configuration(&mut loaded_config_map)
+            .unwrap();
+        assert_eq!(loaded_config_map, config_map);
+
+        // Writing the loaded configuration keeps the 5 GHz band.
+        hostapd_service
+            .write_configuration(&loaded_config_map)
+            .unwrap();
+        let written = KeyValueConfigFile::new(path).load_configuration().unwrap();
+        assert_eq!(written.get("hw_mode").unwrap(), "a");
+        assert_eq!(written.get("ieee80211n").unwrap(), "1");
+        assert_eq!(written.get("channel").unwrap(), "36");
+
+        hostapd_service.remove_config_file().unwrap();
+    }
+
+    #[test]
+    fn test_security_protocol_values() {
+        let expected: [(SecurityProtocol, &[(&str, &str)]); 4] = [
+            (SecurityProtocol::Open, &[]),
+            (
+                SecurityProtocol::WPA2Personal,
+                &[
+                    ("wpa", "2"),
+                    ("wpa_key_mgmt", "WPA-PSK"),
+                    ("rsn_pairwise", "CCMP"),
+                ],
+            ),
+            (
+                SecurityProtocol::WPA3Personal,
+                &[
+                    ("wpa", "2"),
+                    ("wpa_key_mgmt", "SAE"),
+                    ("rsn_pairwise", "CCMP"),
+                    ("ieee80211w", "2"),
+                ],
+            ),
+            (
+                SecurityProtocol::WPA2WPA3Mixed,
+                &[
+                    ("wpa", "2"),
+                    ("wpa_key_mgmt", "WPA-PSK SAE"),
+                    ("rsn_pairwise", "CCMP"),
+                    ("ieee80211w", "1"),
+                ],
+            ),
+        ];
+
+        for (index, (protocol, values)) in expected.into_iter().enumerate() {
+            let interface = NetworkInterface::new_with_name("wlan0");
+            let wifi_config = WirelessConfiguration {
+                mode: WirelessMode::AccessPoint,
+                ssid: "HoneyBadgerHut".to_string(),
+                password: Some("NUTHUT".to_string()),
+                security_protocol: Some(protocol),
+                ..Default::default()
+            };
+            let config = NetworkConfiguration::new(
+                AddressMode::DHCP,
+                interface,
+                true,
+                Some(wifi_config),
+                None,
+            );
+            let mut config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+            config_map.insert("wlan0".to_string(), config);
+
+            let path = PathBuf::from(format!("/tmp/hostapd_security_{}.conf", index));
+            let mut hostapd_service = HostAPDService::new(path.clone());
+            hostapd_service.write_configuration(&config_map).unwrap();
+
+            // The written file contains exactly the protocol's keys.
+            let written = KeyValueConfigFile::new(path).load_configuration().unwrap();
+            for key in SECURITY_PROTOCOL_KEYS {
+                let expected_value = values
+                    .iter()
+                    .find(|(expected_key, _)| *expected_key == key)
+                    .map(|(_, value)| value.to_string());
+                assert_eq!(written.get(key).cloned(), expected_value, "{}", protocol);
+            }
+            assert_eq!(
+                written.contains_key("wpa_passphrase"),
+                protocol != SecurityProtocol::Open
+            );
+            assert_eq!(security_protocol_from_values(&written), Some(protocol));
+
+            // Reading the file back infers the same protocol.
+            let mut other_config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+            hostapd_service
+                .load_configuration(&mut other_config_map)
+                .unwrap();
+            let other_wifi_config = other_config_map
+                .get("wlan0")
+                .unwrap()
+                .wifi_configuration
+                .as_ref()
+                .unwrap();
+            assert_eq!(other_wifi_config.security_protocol, Some(protocol));
+            if protocol != SecurityProtocol::Open {
+                assert_eq!(
+                    other_wifi_config,
+                    config_map
+                        .get("wlan0")
+                        .unwrap()
+                        .wifi_configuration
+                        .as_ref()
+                        .unwrap()
+                );
+            }
+
+            hostapd_service.remove_config_file().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_security_protocol_from_values() {
+        let mut values: HashMap<String, String> = HashMap::new();
+        values.insert("wpa".to_string(), "2".to_string());
+        values.insert("wpa_key_mgmt".to_string(), "WPA-PSK".to_string());
+        values.insert("wpa_pairwise".to_string(), "TKIP".to_string());
+        values.insert("rsn_pairwise".to_string(), "CCMP".to_string());
+
+        // Explicit settings that do not match a protocol are not inferred as one.
+        assert_eq!(security_protocol_from_values(&values), None);
+
+        values.remove("wpa_pairwise");
+        assert_eq!(
+            security_protocol_from_values(&values),
+            Some(SecurityProtocol::WPA2Personal)
+        );
+
+        values.insert("wpa_key_mgmt".to_string(), "SAE".to_string());
+        assert_eq!(security_protocol_from_values(&values), None);
+        values.insert("ieee80211w".to_string(), "2".to_string());
+        assert_eq!(
+            security_protocol_from_values(&values),
+            Some(SecurityProtocol::WPA3Personal)
+        );
     }
 }
@@ -4,11 +4,13 @@
 use crate::error::FoundationError;
 use crate::network::ipaddrquery::IpAddrQuery;
 use crate::network::netmask::{netmask_from_bits_ipv4, netmask_from_bits_ipv6};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 /// The `InterfaceAddr` struct represents an IP address, broadcast address, and netmask for a
-/// network interface.
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// network interface. The addresses serialize as strings.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct InterfaceAddr {
     /// An IP address of a network interface.
     pub ip: IpAddr,
@@ -36,17 +38,107 @@ impl InterfaceAddr {
         }
     }
 
+    /// Create a new `InterfaceAddr` instance from an IP address in CIDR notation, for example
+    /// `192.168.1.5/24` or `2001:db8::1/64`. The netmask is derived from the prefix length. For
+    /// canonical inputs, `get_in_cidr_notation` returns the original string.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The IP address in CIDR notation.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `InterfaceAddr` if the string is valid CIDR notation, otherwise
+    /// a `FoundationError` is returned. A prefix length larger than the number of bits in the
+    /// address is an error.
+    pub fn from_cidr(s: &str) -> Result<InterfaceAddr, FoundationError> {
+        let (ip, mask_bits) = match s.split_once('/') {
+            Some((ip, mask_bits)) if !mask_bits.contains('/') => (ip, mask_bits),
+            _ => {
+                return Err(FoundationError::InvalidConversion(
+                    s.to_string(),
+                    "InterfaceAddr",
+                ))
+            }
+        };
+
+        let ip: IpAddr = ip.parse()?;
+        let mask_bits: u8 = mask_bits.parse()?;
+        let netmask = match ip {
+            IpAddr::V4(_) => netmask_from_bits_ipv4(mask_bits)
+                .map(|netmask| IpAddr::V4(<Ipv4Addr as From<[u8; 4]>>::from(netmask))),
+            IpAddr::V6(_) => netmask_from_bits_ipv6(mask_bits)
+                .map(|netmask| IpAddr::V6(<Ipv6Addr as From<[u8; 16]>>::from(netmask))),
+        };
+        let netmask = match netmask {
+            Some(netmask) => netmask,
+            None => {
+                return Err(FoundationError::OperationFailed(format!(
+                    "Invalid prefix length {} for {}",
+                    mask_bits, ip
+                )))
+            }
+        };
+
+        Ok(InterfaceAddr::new(ip, None, Some(netmask)))
+    }
+
+    /// Get the prefix length of the interface address, the number of bits set in the netmask.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the prefix length if the interface address contains a valid
+    /// netmask. Otherwise, `None` is returned. A netmask with holes, such as `255.0.255.0`, has
+    /// no prefix length.
+    pub fn prefix_len(&self) -> Option<u8> {
+        self.netmask
+            .filter(|netmask| netmask.is_valid_netmask())
+            .map(|netmask| netmask.bits_in_mask())
+    }
+
     /// Get the IP address in CIDR notation.
     ///
     /// # Returns
     ///
     /// An `Option` containing the IP address in CIDR notation if the interface address contains
-    /// a netmask. Otherwise, `None` is returned.
+    /// a valid netmask. Otherwise, `None` is returned.
     pub fn get_in_cidr_notation(&self) -> Option<String> {
-        if let Some(netmask) = self.netmask {
-            Some(format!("{}/{}", self.ip, netmask.bits_in_mask()))
-        } else {
-            None
+        self.prefix_len()
+            .map(|prefix_len| format!("{}/{}", self.ip, prefix_len))
+    }
+
+    /// Get the network address of the interface address, computed by masking the IP address with
+    /// the netmask.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the network address if the interface address contains a netmask of
+    /// the same IP version as the IP address. Otherwise, `None` is returned.
+    pub fn network_address(&self) -> Option<IpAddr> {
+        match (self.ip, self.netmask?) {
+            (IpAddr::V4(ip), IpAddr::V4(netmask)) => Some(IpAddr::V4(Ipv4Addr::from_integer(
+                ip.to_integer() & netmask.to_integer(),
+            ))),
+            (IpAddr::V6(ip), IpAddr::V6(netmask)) => Some(IpAddr::V6(Ipv6Addr::from_integer(
+                ip.to_integer() & netmask.to_integer(),
+            ))),
+            _ => None,
+        }
+    }
+
+    /// Get the broadcast address of the interface address, computed by setting every host bit of
+    /// the IP address. IPv6 has no broadcast addresses.
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the broadcast address if the interface address is an IPv4 address
+    /// with an IPv4 netmask. Otherwise, `None` is returned.
+    pub fn broadcast_address(&self) -> Option<IpAddr> {
+        match (self.ip, self.netmask?) {
+            (IpAddr::V4(ip), IpAddr::V4(netmask)) => Some(IpAddr::V4(Ipv4Addr::from_integer(
+                ip.to_integer() | !netmask.to_integer(),
+            ))),
+            _ => None,
         }
     }
 }
@@ -96,6 +188,17 @@ impl From<network_interface::Addr> for InterfaceAddr {
     }
 }
 
+impl Display for InterfaceAddr {
+    /// Format the interface address in CIDR notation, `ip/prefix`, or as just the IP address if
+    /// there is no valid netmask.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.prefix_len() {
+            Some(prefix_len) => write!(f, "{}/{}", self.ip, prefix_len),
+            None => write!(f, "{}", self.ip),
+        }
+    }
+}
+
 impl TryFrom<&str> for InterfaceAddr {
     type Error = FoundationError;
 
@@ -105,26 +208,7 @@ impl TryFrom<&str> for InterfaceAddr {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         // Check to see if value is an IP address with a netmask in CIDR notation.
         if value.contains('/') {
-            let parts = value.split('/').collect::<Vec<&str>>();
-            if parts.len() != 2 {
-                return Err(FoundationError::OperationFailed(format!(
-                    "Failed to convert {} to InterfaceAddr",
-                    value
-                )));
-            }
-            let ip: IpAddr = parts[0].parse()?;
-            let mask_bits: u8 = parts[1].parse()?;
-            let netmask = match ip {
-                IpAddr::V4(_) => {
-                    let netmask = netmask_from_bits_ipv4(mask_bits);
-                    Some(IpAddr::V4(<Ipv4Addr as From<[u8; 4]>>::from(netmask)))
-                }
-                IpAddr::V6(_) => {
-                    let netmask = netmask_from_bits_ipv6(mask_bits);
-                    Some(IpAddr::V6(<Ipv6Addr as From<[u8; 16]>>::from(netmask)))
-                }
-            };
-            return Ok(InterfaceAddr::new(ip, None, netmask));
+            return InterfaceAddr::from_cidr(value);
         }
 
         // The value is not a string with CIDR notation, just try to parse the value
@@ -133,3 +217,124 @@ impl TryFrom<&str> for InterfaceAddr {
         Ok(InterfaceAddr::new(ip, None, None))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_and_broadcast_address() {
+        let addr = InterfaceAddr::try_from("192.168.1.37/24").unwrap();
+        assert_eq!(
+            addr.network_address(),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 0)))
+        );
+        assert_eq!(
+            addr.broadcast_address(),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 255)))
+        );
+
+        let addr = InterfaceAddr::try_from("10.20.30.40/12").unwrap();
+        assert_eq!(
+            addr.network_address(),
+            Some(IpAddr::V4(Ipv4Addr::new(10, 16, 0, 0)))
+        );
+        assert_eq!(
+            addr.broadcast_address(),
+            Some(IpAddr::V4(Ipv4Addr::new(10, 31, 255, 255)))
+        );
+
+        let addr = InterfaceAddr::try_from("2001:db8::1234:5678/64").unwrap();
+        assert_eq!(
+            addr.network_address(),
+            Some(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0)))
+        );
+        assert_eq!(addr.broadcast_address(), None);
+
+        let addr = InterfaceAddr::try_from("192.168.1.37").unwrap();
+        assert_eq!(addr.network_address(), None);
+        assert_eq!(addr.broadcast_address(), None);
+    }
+
+    #[test]
+    fn test_from_cidr() {
+        let addr = InterfaceAddr::from_cidr("192.168.1.5/24").unwrap();
+        assert_eq!(addr.ip, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 5)));
+        assert_eq!(
+            addr.netmask,
+            Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0)))
+        );
+        assert_eq!(addr.broadcast, None);
+        assert_eq!(
+            addr.get_in_cidr_notation(),
+            Some("192.168.1.5/24".to_string())
+        );
+
+        let addr = InterfaceAddr::from_cidr("2001:db8::1/64").unwrap();
+        assert_eq!(
+            addr.ip,
+            IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1))
+        );
+        assert_eq!(
+            addr.netmask,
+            Some(IpAddr::V6(Ipv6Addr::new(
+                0xffff, 0xffff, 0xffff, 0xffff, 0, 0, 0, 0
+            )))
+        );
+        assert_eq!(
+            addr.get_in_cidr_notation(),
+            Some("2001:db8::1/64".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_cidr_round_trip() {
+        for cidr in [
+            "10.0.0.1/8",
+            "172.16.4.20/12",
+            "192.168.1.5/24",
+            "192.168.1.5/32",
+            "0.0.0.0/0",
+            "fe80::1/10",
+            "2001:db8::1/64",
+            "::1/128",
+        ] {
+            let addr = InterfaceAddr::from_cidr(cidr).unwrap();
+            assert_eq!(addr.get_in_cidr_notation(), Some(cidr.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_from_cidr_invalid() {
+        assert!(InterfaceAddr::from_cidr("192.168.1.5/33").is_err());
+        assert!(InterfaceAddr::from_cidr("2001:db8::1/129").is_err());
+        assert!(InterfaceAddr::from_cidr("192.168.1.5/-1").is_err());
+        assert!(InterfaceAddr::from_cidr("192.168.1.5/24/8").is_err());
+        assert!(InterfaceAddr::from_cidr("192.168.1.5").is_err());
+        assert!(InterfaceAddr::from_cidr("192.168.1/24").is_err());
+        assert!(InterfaceAddr::try_from("192.168.1.5/33").is_err());
+    }
+
+    #[test]
+    fn test_prefix_len_and_display() {
+        let addr = InterfaceAddr::new(
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 5)),
+            None,
+            Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+        );
+        assert_eq!(addr.prefix_len(), Some(24));
+        assert_eq!(addr.to_string(), "192.168.1.5/24");
+
+        let addr = InterfaceAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 5)), None, None);
+        assert_eq!(addr.prefix_len(), None);
+        assert_eq!(addr.to_string(), "192.168.1.5");
+
+        let addr = InterfaceAddr::new(
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 5)),
+            None,
+            Some(IpAddr::V4(Ipv4Addr::new(255, 0, 255, 0))),
+        );
+        assert_eq!(addr.prefix_len(), None);
+        assert_eq!(addr.get_in_cidr_notation(), None);
+    }
+}
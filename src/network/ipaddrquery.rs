@@ -4,8 +4,25 @@
 use crate::error::FoundationError;
 use crate::network::netmask::bits_in_mask;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
+// Private IPv4 subnets (10.0.0.0/8, 172.16.0.0/12, 192.168.0.0/16).
+const IPV4_PRIVATE_10: RangeInclusive<u32> = 167772160..=184549375;
+const IPV4_PRIVATE_172: RangeInclusive<u32> = 2886729728..=2887778303;
+const IPV4_PRIVATE_192: RangeInclusive<u32> = 3232235520..=3232301055;
+
+// Link local IPv4 addresses (169.254.0.0/16).
+const IPV4_LINK_LOCAL: RangeInclusive<u32> = 2851995648..=2852061183;
+
+// Unique local IPv6 addresses (fc00::/7).
+const IPV6_UNIQUE_LOCAL: RangeInclusive<u128> =
+    334965454937798799971759379190646833152..=337623910929368631717566993311207522303;
+
+// Unique IPv6 addresses with link local scope (fe80::/10).
+const IPV6_LINK_LOCAL: RangeInclusive<u128> =
+    338288524927261089654018896841347694592..=338620831926207318622244848606417780735;
+
 // A trait designed to add functionality to IpAddr, Ipv4Addr, and Ipv6Addr from the std::net module.
 pub trait IpAddrQuery {
     /// The integer type capable of holding every value of the IP address.
@@ -37,6 +54,43 @@ pub trait IpAddrQuery {
         Self: Sized;
 
     fn bits_in_mask(&self) -> u8;
+
+    /// Check if the IP address is a valid netmask, a run of set bits followed only by clear
+    /// bits. A netmask with holes, such as `255.0.255.0`, is not valid.
+    ///
+    /// The default implementation expects `Integer` to have exactly as many bits as the address.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the IP address is a valid netmask, `false` otherwise.
+    fn is_valid_netmask(&self) -> bool
+    where
+        Self::Integer: Into<u128>,
+    {
+        // Move the address bits to the top of a u128, where a valid netmask is a run of set bits
+        // from the most significant bit followed by clear bits to the least significant bit.
+        let width = std::mem::size_of::<Self::Integer>() as u32 * 8;
+        let mask: u128 = self.to_integer().into();
+        u128::BITS
+            .checked_sub(width)
+            .and_then(|shift| mask.checked_shl(shift))
+            .is_some_and(|mask| mask.leading_ones() + mask.trailing_zeros() == u128::BITS)
+    }
+
+    /// Check if the IP address is in a private address range (10.0.0.0/8, 172.16.0.0/12,
+    /// 192.168.0.0/16, or the IPv6 unique local range fc00::/7).
+    ///
+    /// # Returns
+    ///
+    /// `true` if the IP address is a private address, `false` otherwise.
+    fn is_private_address(&self) -> bool;
+
+    /// Check if the IP address is a link local address (169.254.0.0/16 or fe80::/10).
+    ///
+    /// # Returns
+    ///
+    /// `true` if the IP address is a link local address, `false` otherwise.
+    fn is_link_local(&self) -> bool;
 }
 
 impl IpAddrQuery for Ipv4Addr {
@@ -80,11 +134,9 @@ impl IpAddrQuery for Ipv4Addr {
         // Reserved Addresses
         // 4026531840 -> 240.0.0.0
         // 4294967295 -> 255.255.255.255
-        if (ip >= 2886729728u32 && ip <= 2887778303u32)
+        if self.is_private_address()
             || (ip >= 2130706432u32 && ip <= 2147483647u32)
-            || (ip >= 2851995648u32 && ip <= 2852061183u32)
-            || (ip >= 167772160u32 && ip <= 184549375u32)
-            || (ip >= 3232235520u32 && ip <= 3232301055u32)
+            || self.is_link_local()
             || (ip >= 3221225984 && ip <= 3221226239)
             || (ip >= 3325256704 && ip <= 3325256959)
             || (ip >= 3405803776 && ip <= 3405804031)
@@ -99,11 +151,9 @@ impl IpAddrQuery for Ipv4Addr {
     }
 
     fn from_integer(ip: Self::Integer) -> Ipv4Addr {
-        let bytes: [u8; 4] = if cfg!(target_endian = "little") {
-            ip.to_le_bytes()
-        } else {
-            ip.to_be_bytes()
-        };
+        // The first octet of the address is the most significant byte of the integer, matching
+        // `to_integer`, regardless of the byte order of the host.
+        let bytes: [u8; 4] = ip.to_be_bytes();
         Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])
     }
 
@@ -124,6 +174,17 @@ impl IpAddrQuery for Ipv4Addr {
     fn bits_in_mask(&self) -> u8 {
         bits_in_mask(&self.octets())
     }
+
+    fn is_private_address(&self) -> bool {
+        let ip = self.to_integer();
+        IPV4_PRIVATE_10.contains(&ip)
+            || IPV4_PRIVATE_172.contains(&ip)
+            || IPV4_PRIVATE_192.contains(&ip)
+    }
+
+    fn is_link_local(&self) -> bool {
+        IPV4_LINK_LOCAL.contains(&self.to_integer())
+    }
 }
 
 impl IpAddrQuery for Ipv6Addr {
@@ -164,10 +225,8 @@ impl IpAddrQuery for Ipv6Addr {
                 && ip <= 42540488320433376714898646241562853375)
             || (ip >= 42540766411282592856903984951653826560
                 && ip <= 42540766490510755371168322545197776895)
-            || (ip >= 334965454937798799971759379190646833152
-                && ip <= 337623910929368631717566993311207522303)
-            || (ip >= 338288524927261089654018896841347694592
-                && ip <= 338620831926207318622244848606417780735)
+            || self.is_private_address()
+            || self.is_link_local()
         {
             return false;
         }
@@ -175,20 +234,11 @@ impl IpAddrQuery for Ipv6Addr {
     }
 
     fn from_integer(ip: Self::Integer) -> Self {
-        let bytes: [u8; 16] = if cfg!(target_endian = "little") {
-            ip.to_le_bytes()
-        } else {
-            ip.to_be_bytes()
-        };
+        // As with IPv4, the big endian bytes of the integer are the octets of the address.
+        let bytes: [u8; 16] = ip.to_be_bytes();
 
         let u16_values: Vec<u16> = (0..8)
-            .map(|i| {
-                if cfg!(target_endian = "little") {
-                    u16::from_le_bytes([bytes[i * 2], bytes[i * 2 + 1]])
-                } else {
-                    u16::from_be_bytes([bytes[i * 2], bytes[i * 2 + 1]])
-                }
-            })
+            .map(|i| u16::from_be_bytes([bytes[i * 2], bytes[i * 2 + 1]]))
             .collect::<Vec<u16>>();
         Self::new(
             u16_values[0],
@@ -220,6 +270,14 @@ impl IpAddrQuery for Ipv6Addr {
     fn bits_in_mask(&self) -> u8 {
         bits_in_mask(&self.octets())
     }
+
+    fn is_private_address(&self) -> bool {
+        IPV6_UNIQUE_LOCAL.contains(&self.to_integer())
+    }
+
+    fn is_link_local(&self) -> bool {
+        IPV6_LINK_LOCAL.contains(&self.to_integer())
+    }
 }
 
 impl IpAddrQuery for IpAddr {
@@ -267,6 +325,27 @@ impl IpAddrQuery for IpAddr {
             IpAddr::V6(ip) => ip.bits_in_mask(),
         }
     }
+
+    fn is_valid_netmask(&self) -> bool {
+        match self {
+            IpAddr::V4(ip) => ip.is_valid_netmask(),
+            IpAddr::V6(ip) => ip.is_valid_netmask(),
+        }
+    }
+
+    fn is_private_address(&self) -> bool {
+        match self {
+            IpAddr::V4(ip) => ip.is_private_address(),
+            IpAddr::V6(ip) => ip.is_private_address(),
+        }
+    }
+
+    fn is_link_local(&self) -> bool {
+        match self {
+            IpAddr::V4(ip) => ip.is_link_local(),
+            IpAddr::V6(ip) => ip.is_link_local(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -278,19 +357,68 @@ mod tests {
     fn test_bits_in_mask() {
         for i in 1..33u8 {
             assert_eq!(
-                <Ipv4Addr as From<[u8; 4]>>::from(netmask_from_bits_ipv4(i)).bits_in_mask(),
+                <Ipv4Addr as From<[u8; 4]>>::from(netmask_from_bits_ipv4(i).unwrap())
+                    .bits_in_mask(),
                 i
             );
         }
 
         for i in 1..129u8 {
             assert_eq!(
-                <Ipv6Addr as From<[u8; 16]>>::from(netmask_from_bits_ipv6(i)).bits_in_mask(),
+                <Ipv6Addr as From<[u8; 16]>>::from(netmask_from_bits_ipv6(i).unwrap())
+                    .bits_in_mask(),
                 i
             );
         }
     }
 
+    #[test]
+    fn test_is_valid_netmask() {
+        for i in 0..33u8 {
+            assert!(
+                <Ipv4Addr as From<[u8; 4]>>::from(netmask_from_bits_ipv4(i).unwrap())
+                    .is_valid_netmask()
+            );
+        }
+
+        for i in 0..129u8 {
+            assert!(
+                <Ipv6Addr as From<[u8; 16]>>::from(netmask_from_bits_ipv6(i).unwrap())
+                    .is_valid_netmask()
+            );
+        }
+
+        assert!(!Ipv4Addr::new(255, 255, 0b1011_0000, 0).is_valid_netmask());
+        assert!(!Ipv4Addr::new(0, 0, 0, 1).is_valid_netmask());
+        assert!(!Ipv4Addr::new(255, 254, 255, 0).is_valid_netmask());
+        assert!(Ipv4Addr::new(255, 255, 255, 0).is_valid_netmask());
+        assert!(Ipv4Addr::new(0, 0, 0, 0).is_valid_netmask());
+        assert!(!Ipv4Addr::new(255, 0, 255, 0).is_valid_netmask());
+        assert!(!Ipv4Addr::new(0, 255, 255, 255).is_valid_netmask());
+        assert!(Ipv4Addr::new(255, 255, 255, 255).is_valid_netmask());
+        assert!(Ipv6Addr::from_integer(u128::MAX).is_valid_netmask());
+        assert!(Ipv6Addr::new(0xffff, 0xffff, 0xffff, 0xffff, 0, 0, 0, 0).is_valid_netmask());
+        assert!(!Ipv6Addr::new(0xffff, 0, 0xffff, 0, 0, 0, 0, 0).is_valid_netmask());
+        assert!(!IpAddr::V4(Ipv4Addr::new(255, 255, 0, 255)).is_valid_netmask());
+    }
+
+    #[test]
+    fn test_integer_round_trip() {
+        let ipv4 = Ipv4Addr::new(192, 168, 1, 37);
+        assert_eq!(ipv4.to_integer(), 3232235813);
+        assert_eq!(Ipv4Addr::from_integer(3232235813), ipv4);
+
+        let ipv6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0x1234, 0x5678);
+        assert_eq!(Ipv6Addr::from_integer(ipv6.to_integer()), ipv6);
+        assert_eq!(
+            Ipv6Addr::from_integer(42540766411282592856903984951653826560),
+            Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0)
+        );
+
+        let ip = IpAddr::V4(ipv4);
+        assert_eq!(<IpAddr as IpAddrQuery>::from_integer(ip.to_integer()), ip);
+    }
+
     #[test]
     fn test_ipv4_is_global_address() {
         assert_eq!(Ipv4Addr::new(8, 8, 8, 8).is_global_address(), true);
@@ -365,4 +493,81 @@ mod tests {
             false
         );
     }
+
+    #[test]
+    fn test_ipv4_is_private_address() {
+        assert!(!Ipv4Addr::new(8, 8, 8, 8).is_private_address());
+        assert!(!Ipv4Addr::UNSPECIFIED.is_private_address());
+
+        // Addresses reserved for private use: (10.0.0.0/8, 172.16.0.0/12, 192.168.0.0/16)
+        assert!(Ipv4Addr::new(10, 254, 0, 0).is_private_address());
+        assert!(Ipv4Addr::new(172, 16, 0, 65).is_private_address());
+        assert!(Ipv4Addr::new(172, 31, 255, 255).is_private_address());
+        assert!(!Ipv4Addr::new(172, 32, 0, 0).is_private_address());
+        assert!(Ipv4Addr::new(192, 168, 10, 65).is_private_address());
+
+        // The link-local addresses (169.254.0.0/16)
+        assert!(!Ipv4Addr::new(169, 254, 45, 1).is_private_address());
+
+        // Addresses reserved for documentation (192.0.2.0/24)
+        assert!(!Ipv4Addr::new(192, 0, 2, 255).is_private_address());
+
+        // The loopback addresses (127.0.0.0/8)
+        assert!(!Ipv4Addr::LOCALHOST.is_private_address());
+    }
+
+    #[test]
+    fn test_ipv4_is_link_local() {
+        assert!(!Ipv4Addr::new(8, 8, 8, 8).is_link_local());
+
+        // The link-local addresses (169.254.0.0/16)
+        assert!(Ipv4Addr::new(169, 254, 0, 0).is_link_local());
+        assert!(Ipv4Addr::new(169, 254, 45, 1).is_link_local());
+        assert!(!Ipv4Addr::new(169, 255, 0, 0).is_link_local());
+
+        // Addresses reserved for private use
+        assert!(!Ipv4Addr::new(192, 168, 10, 65).is_link_local());
+
+        // Addresses reserved for documentation (198.51.100.0/24)
+        assert!(!Ipv4Addr::new(198, 51, 100, 65).is_link_local());
+    }
+
+    #[test]
+    fn test_ipv6_is_private_address() {
+        assert!(!Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888).is_private_address());
+        assert!(!Ipv6Addr::LOCALHOST.is_private_address());
+
+        // Unique local addresses (fc00::/7)
+        assert!(Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0).is_private_address());
+        assert!(Ipv6Addr::new(0xfd12, 0x3456, 0, 0, 0, 0, 0, 1).is_private_address());
+
+        // Unique addresses with link local scope (fe80::/10)
+        assert!(!Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0).is_private_address());
+
+        // Addresses reserved for documentation (2001:db8::/32)
+        assert!(!Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0).is_private_address());
+    }
+
+    #[test]
+    fn test_ipv6_is_link_local() {
+        assert!(!Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888).is_link_local());
+
+        // Unique addresses with link local scope (fe80::/10)
+        assert!(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1).is_link_local());
+        assert!(Ipv6Addr::new(0xfebf, 0xffff, 0, 0, 0, 0, 0, 0).is_link_local());
+        assert!(!Ipv6Addr::new(0xfec0, 0, 0, 0, 0, 0, 0, 0).is_link_local());
+
+        // Unique local addresses (fc00::/7)
+        assert!(!Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0).is_link_local());
+    }
+
+    #[test]
+    fn test_ip_is_private_address_and_link_local() {
+        assert!(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)).is_private_address());
+        assert!(IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1)).is_private_address());
+        assert!(IpAddr::V4(Ipv4Addr::new(169, 254, 1, 1)).is_link_local());
+        assert!(IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1)).is_link_local());
+        assert!(!IpAddr::V4(Ipv4Addr::new(8, 8, 4, 4)).is_private_address());
+        assert!(!IpAddr::V4(Ipv4Addr::new(8, 8, 4, 4)).is_link_local());
+    }
 }
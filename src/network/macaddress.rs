@@ -0,0 +1,146 @@
+//! The `macaddress` module provides the `MacAddress` structure to represent the hardware (MAC)
+//! address of a network interface.
+
+use crate::error::FoundationError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// The `MacAddress` struct represents a 48-bit hardware (MAC) address. A `MacAddress` displays
+/// and serializes in the canonical lowercase, colon separated form, `aa:bb:cc:dd:ee:ff`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct MacAddress {
+    /// The six octets of the address.
+    octets: [u8; 6],
+}
+
+impl MacAddress {
+    /// Create a new `MacAddress` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `octets` - The six octets of the address.
+    pub fn new(octets: [u8; 6]) -> Self {
+        MacAddress { octets }
+    }
+
+    /// Get the octets of the address.
+    ///
+    /// # Returns
+    ///
+    /// The six octets of the address.
+    pub fn octets(&self) -> [u8; 6] {
+        self.octets
+    }
+}
+
+impl From<[u8; 6]> for MacAddress {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddress::new(octets)
+    }
+}
+
+impl FromStr for MacAddress {
+    type Err = FoundationError;
+
+    /// Attempt to parse a `MacAddress` from a string.
+    ///
+    /// The string should contain six pairs of hex digits separated by either `:` or `-`, for
+    /// example `AA:BB:CC:DD:EE:FF` or `aa-bb-cc-dd-ee-ff`. Mixed separators are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || FoundationError::InvalidConversion(s.to_string(), "MacAddress");
+
+        let separator = if s.contains(':') { ':' } else { '-' };
+        let parts = s.split(separator).collect::<Vec<&str>>();
+        if parts.len() != 6 {
+            return Err(invalid());
+        }
+
+        let mut octets = [0u8; 6];
+        for (octet, part) in octets.iter_mut().zip(parts) {
+            if part.len() != 2 || !part.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            *octet = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+        }
+
+        Ok(MacAddress { octets })
+    }
+}
+
+impl TryFrom<&str> for MacAddress {
+    type Error = FoundationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let o = &self.octets;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            o[0], o[1], o[2], o[3], o[4], o[5]
+        )
+    }
+}
+
+impl Serialize for MacAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MacAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let upper: MacAddress = "AA:BB:CC:DD:EE:FF".parse().unwrap();
+        let dashed: MacAddress = "aa-bb-cc-dd-ee-ff".parse().unwrap();
+        assert_eq!(upper, dashed);
+        assert_eq!(upper.octets(), [0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]);
+        assert_eq!(upper.to_string(), "aa:bb:cc:dd:ee:ff");
+
+        let addr = MacAddress::try_from("00:11:22:33:44:55").unwrap();
+        assert_eq!(addr, MacAddress::new([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]));
+        assert_eq!(addr.to_string(), "00:11:22:33:44:55");
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        for text in [
+            "",
+            "aa:bb:cc:dd:ee",
+            "aa:bb:cc:dd:ee:ff:00",
+            "aa:bb:cc:dd:ee:fg",
+            "aa:bb:cc:dd:ee:f",
+            "aa:bb:cc:dd:ee:fff",
+            "aa:bb-cc:dd:ee:ff",
+            "aabbccddeeff",
+            "+a:bb:cc:dd:ee:ff",
+        ] {
+            assert!(text.parse::<MacAddress>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let addr: MacAddress = "AA-BB-CC-00-11-22".parse().unwrap();
+        let json = serde_json::to_string(&addr).unwrap();
+        assert_eq!(json, "\"aa:bb:cc:00:11:22\"");
+        let decoded: MacAddress = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, addr);
+        assert!(serde_json::from_str::<MacAddress>("\"not a mac\"").is_err());
+    }
+}
@@ -40,6 +40,10 @@ const BITS_IN_MASK_ARRAY: [BitMaskBits; 7] = [
 
 /// Return the number of bits set in the netmask.
 ///
+/// Only the leading bits are counted, so a netmask with holes, such as `255.0.255.0`, counts as
+/// the leading bits alone. Use `IpAddrQuery::is_valid_netmask` to check that the netmask has no
+/// holes.
+///
 /// # Arguments
 ///
 /// * `bytes` - The netmask bytes.
@@ -110,8 +114,13 @@ const NETMASK_FROM_BITS_MASK_ARRAY: [BitMaskBits; 7] = [
 ///
 /// # Returns
 ///
-/// The netmask bytes for an Ipv4Addr.
-pub fn netmask_from_bits_ipv4(cidr: u8) -> [u8; 4] {
+/// An `Option` containing the netmask bytes for an Ipv4Addr, or `None` if `cidr` is larger
+/// than 32.
+pub fn netmask_from_bits_ipv4(cidr: u8) -> Option<[u8; 4]> {
+    if cidr > 32 {
+        return None;
+    }
+
     let bytes = cidr / 8;
     let bits = cidr % 8;
 
@@ -122,7 +131,7 @@ pub fn netmask_from_bits_ipv4(cidr: u8) -> [u8; 4] {
     if bytes < 4 && bits != 0 {
         octet[bytes as usize] = NETMASK_FROM_BITS_MASK_ARRAY[(bits - 1) as usize].mask;
     }
-    octet
+    Some(octet)
 }
 
 /// Return the netmask bytes from the number of bits.
@@ -133,8 +142,13 @@ pub fn netmask_from_bits_ipv4(cidr: u8) -> [u8; 4] {
 ///
 /// # Returns
 ///
-/// The netmask bytes for an Ipv6Addr.
-pub fn netmask_from_bits_ipv6(cidr: u8) -> [u8; 16] {
+/// An `Option` containing the netmask bytes for an Ipv6Addr, or `None` if `cidr` is larger
+/// than 128.
+pub fn netmask_from_bits_ipv6(cidr: u8) -> Option<[u8; 16]> {
+    if cidr > 128 {
+        return None;
+    }
+
     let bytes = cidr / 8;
     let bits = cidr % 8;
 
@@ -145,7 +159,7 @@ pub fn netmask_from_bits_ipv6(cidr: u8) -> [u8; 16] {
     if bytes < 16 && bits != 0 {
         octet[bytes as usize] = NETMASK_FROM_BITS_MASK_ARRAY[(bits - 1) as usize].mask;
     }
-    octet
+    Some(octet)
 }
 
 #[cfg(test)]
@@ -155,11 +169,25 @@ mod tests {
     #[test]
     fn test_bits_in_mask() {
         for i in 1..33u8 {
-            assert_eq!(bits_in_mask(&netmask_from_bits_ipv4(i)), i);
+            assert_eq!(bits_in_mask(&netmask_from_bits_ipv4(i).unwrap()), i);
         }
 
         for i in 1..129u8 {
-            assert_eq!(bits_in_mask(&netmask_from_bits_ipv6(i)), i);
+            assert_eq!(bits_in_mask(&netmask_from_bits_ipv6(i).unwrap()), i);
         }
     }
+
+    #[test]
+    fn test_netmask_from_bits_bounds() {
+        assert_eq!(netmask_from_bits_ipv4(0), Some([0u8; 4]));
+        assert_eq!(netmask_from_bits_ipv6(0), Some([0u8; 16]));
+        assert_eq!(bits_in_mask(&netmask_from_bits_ipv4(0).unwrap()), 0);
+        assert_eq!(bits_in_mask(&netmask_from_bits_ipv6(0).unwrap()), 0);
+
+        assert_eq!(netmask_from_bits_ipv4(32), Some([0xff; 4]));
+        assert_eq!(netmask_from_bits_ipv4(33), None);
+        assert_eq!(netmask_from_bits_ipv4(u8::MAX), None);
+        assert_eq!(netmask_from_bits_ipv6(128), Some([0xff; 16]));
+        assert_eq!(netmask_from_bits_ipv6(129), None);
+    }
 }
@@ -6,12 +6,13 @@ use crate::network::interfaceaddr::InterfaceAddr;
 use crate::network::ipaddrquery::IpAddrQuery;
 use crate::network::networkconfiguration::{AddressMode, NetworkConfiguration};
 use crate::network::networkservice::NetworkService;
+use crate::network::route::Route;
 use crate::network::wireless::configuration::{WirelessConfiguration, WirelessMode};
 use crate::systemctlservice::SystemCTLService;
 use log::{debug, error};
 use serde::ser::{SerializeMap, SerializeSeq};
 use serde::{Deserialize, Serializer};
-use serde_yaml::Value;
+use serde_yaml::{Mapping, Value};
 use std::collections::HashMap;
 use std::net::IpAddr;
 use std::os::unix::fs::PermissionsExt;
@@ -78,6 +79,112 @@ fn load_wifi_config_helper(
     return interface_name;
 }
 
+/// Read the routes from a Netplan `routes` sequence. Entries that do not have a valid `to` and
+/// `via` address are skipped.
+///
+/// # Arguments
+///
+/// * `interface_name` - The name of the interface the routes belong to.
+/// * `routes_value` - The value of the `routes` key.
+///
+/// # Returns
+///
+/// A vector of the routes in the sequence.
+fn load_routes_helper(interface_name: &str, routes_value: &Value) -> Vec<Route> {
+    let mut routes = vec![];
+    let route_values = match routes_value.as_sequence() {
+        Some(route_values) => route_values,
+        None => {
+            debug!(
+                "The {} mapping contains a 'routes' key that is not a sequence",
+                interface_name
+            );
+            return routes;
+        }
+    };
+
+    for route_value in route_values {
+        let (to, via) = match (
+            route_value.get("to").and_then(|to| to.as_str()),
+            route_value.get("via").and_then(|via| via.as_str()),
+        ) {
+            (Some(to), Some(via)) => (to, via),
+            _ => {
+                debug!(
+                    "The {} mapping contains a route without 'to' and 'via' strings",
+                    interface_name
+                );
+                continue;
+            }
+        };
+
+        let via = match <IpAddr as IpAddrQuery>::from(via) {
+            Ok(via) => via,
+            Err(e) => {
+                debug!(
+                    "Invalid route gateway {} for {}: {}",
+                    via, interface_name, e
+                );
+                continue;
+            }
+        };
+
+        let metric = route_value
+            .get("metric")
+            .and_then(|metric| metric.as_u64())
+            .and_then(|metric| u32::try_from(metric).ok());
+
+        if to == "default" {
+            routes.push(Route::default_route(via, metric));
+        } else {
+            match InterfaceAddr::try_from(to) {
+                Ok(to) => routes.push(Route::new(to, via, metric)),
+                Err(e) => debug!(
+                    "Invalid route destination {} for {}: {}",
+                    to, interface_name, e
+                ),
+            }
+        }
+    }
+
+    routes
+}
+
+/// Convert routes to a Netplan `routes` sequence.
+///
+/// # Arguments
+///
+/// * `routes` - The routes to convert.
+///
+/// # Returns
+///
+/// A YAML sequence with a mapping for each route.
+fn routes_to_yaml_helper(routes: &[Route]) -> Value {
+    Value::Sequence(
+        routes
+            .iter()
+            .map(|route| {
+                let to = if route.is_default() {
+                    "default".to_string()
+                } else {
+                    route
+                        .to
+                        .get_in_cidr_notation()
+                        .unwrap_or_else(|| route.to.ip.to_string())
+                };
+
+                let mut route_map = Mapping::new();
+                route_map.insert(Value::from("to"), Value::from(to));
+                route_map.insert(Value::from("via"), Value::from(route.via.to_string()));
+                if let Some(metric) = route.metric {
+                    route_map.insert(Value::from("metric"), Value::from(metric));
+                }
+                Value::Mapping(route_map)
+            })
+            .collect(),
+    )
+}
+
 impl NetworkService for NetplanService {
     /// Load the network configurations from the Netplan configuration file.
     /// Insert a new configuration file in the configuration map or update the existing configuration
@@ -206,6 +313,19 @@ impl NetworkService for NetplanService {
                                                     }
                                                 }
                                             }
+                                        } else if inner_key == "routes" {
+                                            configuration.routes.extend(load_routes_helper(
+                                                interface_name,
+                                                inner_value,
+                                            ));
+                                        } else if inner_key == "mtu" {
+                                            match inner_value
+                                                .as_u64()
+                                                .and_then(|mtu| u32::try_from(mtu).ok())
+                                            {
+                                                Some(mtu) => configuration.mtu = Some(mtu),
+                                                None => debug!("The {} mapping contains an 'mtu' key with a value that is not a valid MTU", interface_name),
+                                            }
                                         }
                                     }
 
@@ -328,6 +448,11 @@ impl NetworkService for NetplanService {
                                                     }
                                                 }
                                             }
+                                        } else if inner_key == "routes" {
+                                            configuration.routes.extend(load_routes_helper(
+                                                &interface_name,
+                                                inner_value,
+                                            ));
                                         }
                                     }
 
@@ -432,6 +557,15 @@ impl NetworkService for NetplanService {
                                     SerializeMap::end(nameservers_map)?;
                                 }
                             }
+                            if let Some(mtu) = config.mtu {
+                                inner_map.serialize_entry("mtu", &mtu)?;
+                            }
+                            if !config.routes.is_empty() {
+                                inner_map.serialize_entry(
+                                    "routes",
+                                    &routes_to_yaml_helper(&config.routes),
+                                )?;
+                            }
                             inner_map.serialize_entry("optional", &true)?;
                             SerializeMap::end(inner_map)?;
                         }
@@ -460,6 +594,12 @@ impl NetworkService for NetplanService {
                             individual_wifi_map
                                 .serialize_entry(&format!("{}", config.address_mode), &true)?;
                         }
+                        if !config.routes.is_empty() {
+                            individual_wifi_map.serialize_entry(
+                                "routes",
+                                &routes_to_yaml_helper(&config.routes),
+                            )?;
+                        }
                         individual_wifi_map.serialize_key("access-points")?;
                         let mut access_points_map = individual_wifi_map.serialize_map(None)?;
                         if let Some(wifi_config) = config.wifi_configuration.as_ref() {
@@ -576,10 +716,12 @@ mod tests {
         interface
             .nameserver_addresses
             .push(IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)));
-        let mut wifi_config = WirelessConfiguration::default();
-        wifi_config.ssid = "PeanutButter".to_string();
-        wifi_config.password = Some("Jelly Time".to_string());
-        wifi_config.mode = WirelessMode::Client;
+        let wifi_config = WirelessConfiguration {
+            ssid: "PeanutButter".to_string(),
+            password: Some("Jelly Time".to_string()),
+            mode: WirelessMode::Client,
+            ..Default::default()
+        };
         let config = NetworkConfiguration::new(
             AddressMode::Static,
             interface,
@@ -590,10 +732,12 @@ mod tests {
         config_map.insert("wlan0".to_string(), config);
 
         let interface2 = NetworkInterface::new_with_name("wlan1");
-        let mut wifi_config2 = WirelessConfiguration::default();
-        wifi_config2.ssid = "HamSandwich".to_string();
-        wifi_config2.password = Some("RhyBreadWithCrust".to_string());
-        wifi_config2.mode = WirelessMode::Client;
+        let wifi_config2 = WirelessConfiguration {
+            ssid: "HamSandwich".to_string(),
+            password: Some("RhyBreadWithCrust".to_string()),
+            mode: WirelessMode::Client,
+            ..Default::default()
+        };
         let config2 = NetworkConfiguration::new(
             AddressMode::DHCP,
             interface2,
@@ -617,4 +761,103 @@ mod tests {
 
         netplan_service.remove_config_file().unwrap();
     }
+
+    #[test]
+    fn test_routes_configuration() {
+        let mut config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+
+        let mut interface = NetworkInterface::new_with_name("eth0");
+        interface.addresses.push(InterfaceAddr::new(
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+            None,
+            Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+        ));
+        let mut config =
+            NetworkConfiguration::new(AddressMode::Static, interface, true, None, None);
+        config.routes.push(Route::default_route(
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+            None,
+        ));
+        config.routes.push(Route::new(
+            InterfaceAddr::try_from("10.10.0.0/16").unwrap(),
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 254)),
+            Some(200),
+        ));
+        config_map.insert("eth0".to_string(), config);
+
+        let interface2 = NetworkInterface::new_with_name("wlan0");
+        let wifi_config = WirelessConfiguration {
+            ssid: "PeanutButter".to_string(),
+            password: Some("Jelly Time".to_string()),
+            mode: WirelessMode::Client,
+            ..Default::default()
+        };
+        let mut config2 =
+            NetworkConfiguration::new(AddressMode::DHCP, interface2, true, Some(wifi_config), None);
+        config2.routes.push(Route::default_route(
+            IpAddr::V4(Ipv4Addr::new(192, 168, 2, 1)),
+            Some(600),
+        ));
+        config_map.insert("wlan0".to_string(), config2);
+
+        let mut netplan_service = NetplanService::new(PathBuf::from("/tmp/routes_netplan.yaml"));
+        let result = netplan_service.write_configuration(&config_map);
+        assert!(result.is_ok());
+
+        let contents = std::fs::read_to_string("/tmp/routes_netplan.yaml").unwrap();
+        assert!(contents.contains("to: default"));
+        assert!(contents.contains("to: 10.10.0.0/16"));
+
+        // Now try to read the configuration back in.
+        let mut read_config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        let result = netplan_service.load_configuration(&mut read_config_map);
+        assert!(result.is_ok());
+
+        assert_eq!(read_config_map.len(), 2);
+        assert_eq!(read_config_map, config_map);
+
+        netplan_service.remove_config_file().unwrap();
+    }
+
+    #[test]
+    fn test_mtu_configuration() {
+        let mut config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+
+        let mut interface = NetworkInterface::new_with_name("eth0");
+        interface.addresses.push(InterfaceAddr::new(
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+            None,
+            Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+        ));
+        let mut config =
+            NetworkConfiguration::new(AddressMode::Static, interface, true, None, None);
+        config.mtu = Some(9000);
+        config_map.insert("eth0".to_string(), config);
+
+        let eth1_interface = NetworkInterface::new_with_name("eth1");
+        let config2 =
+            NetworkConfiguration::new(AddressMode::DHCP, eth1_interface, true, None, None);
+        config_map.insert("eth1".to_string(), config2);
+
+        let mut netplan_service = NetplanService::new(PathBuf::from("/tmp/mtu_netplan.yaml"));
+        let result = netplan_service.write_configuration(&config_map);
+        assert!(result.is_ok());
+
+        // Only the interface with an MTU gets an mtu key.
+        let contents = std::fs::read_to_string("/tmp/mtu_netplan.yaml").unwrap();
+        assert_eq!(contents.matches("mtu:").count(), 1);
+        assert!(contents.contains("mtu: 9000"));
+
+        // Now try to read the configuration back in.
+        let mut read_config_map: HashMap<String, NetworkConfiguration> = HashMap::new();
+        let result = netplan_service.load_configuration(&mut read_config_map);
+        assert!(result.is_ok());
+
+        assert_eq!(read_config_map.len(), 2);
+        assert_eq!(read_config_map, config_map);
+        assert_eq!(read_config_map.get("eth0").unwrap().mtu, Some(9000));
+        assert_eq!(read_config_map.get("eth1").unwrap().mtu, None);
+
+        netplan_service.remove_config_file().unwrap();
+    }
 }
@@ -1,19 +1,23 @@
 //! The `networkconfiguration` module contains the `NetworkConfiguration` struct and the
 //! `AddressMode` enum. The `NetworkConfiguration` struct represents the configuration of a network
 //! interface, including the address mode, the interface, whether the interface is enabled, the
-//! wireless configuration, and the DHCP range. The `AddressMode` enum represents the address mode
-//! of a network interface, which can be DHCP4, DHCP6, or Static.
+//! wireless configuration, the DHCP range, and the static routes. The `AddressMode` enum
+//! represents the address mode of a network interface, which can be DHCP4, DHCP6, or Static.
 
 use crate::error::FoundationError;
 use crate::network::dhcprange::DHCPRange;
 use crate::network::networkinterface::NetworkInterface;
+use crate::network::route::Route;
 use crate::network::wireless::configuration::WirelessConfiguration;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 /// The `AddressMode` enum represents the address mode of a network interface, which can be DHCP4,
 /// DHCP6, or Static.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AddressMode {
     /// The interface receives an IP address from a DHCP server.
     DHCP,
@@ -24,6 +28,7 @@ pub enum AddressMode {
 
 /// The `NetworkConfiguration` struct represents the configuration of a network interface.
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NetworkConfiguration {
     /// The address mode of the network interface.
     pub address_mode: AddressMode,
@@ -39,6 +44,12 @@ pub struct NetworkConfiguration {
 
     /// The DHCP range of the network interface if configured.
     pub dhcp_range: Option<DHCPRange>,
+
+    /// The static routes of the network interface.
+    pub routes: Vec<Route>,
+
+    /// The MTU of the network interface if configured, otherwise the system default is used.
+    pub mtu: Option<u32>,
 }
 
 impl NetworkConfiguration {
@@ -50,6 +61,8 @@ impl NetworkConfiguration {
             enabled: false,
             wifi_configuration: None,
             dhcp_range: None,
+            routes: vec![],
+            mtu: None,
         }
     }
 
@@ -63,6 +76,8 @@ impl NetworkConfiguration {
     /// * `enabled` - Whether the network interface is enabled.
     /// * `wifi_configuration` - The wireless configuration of the network interface if configured.
     /// * `dhcp_range` - The DHCP range of the network interface if configured.
+    ///
+    /// The configuration has no static routes and uses the default MTU.
     pub fn new(
         address_mode: AddressMode,
         interface: NetworkInterface,
@@ -76,6 +91,8 @@ impl NetworkConfiguration {
             enabled,
             wifi_configuration,
             dhcp_range,
+            routes: vec![],
+            mtu: None,
         }
     }
 
@@ -2,12 +2,36 @@
 
 use crate::network::interfaceaddr::InterfaceAddr;
 use crate::network::ipaddrquery::IpAddrQuery;
+use crate::network::macaddress::MacAddress;
 use crate::network::wireless::is_wireless_interface;
 use network_interface::NetworkInterfaceConfig;
+use serde::{Deserialize, Serialize};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
-/// The `NetworkInterface` struct represents a network interface.
-#[derive(Debug, Clone, Eq, PartialEq)]
+cfg_if! {
+    if #[cfg(target_os = "linux")] {
+        use crate::error::FoundationError;
+        use crate::shell::Shell;
+
+        const IP_COMMAND: &str = "/usr/sbin/ip";
+        const PROC_NET_ROUTE: &str = "/proc/net/route";
+        const PROC_NET_IPV6_ROUTE: &str = "/proc/net/ipv6_route";
+
+        /// The `RTF_GATEWAY` route flag, set on routes that go through a gateway.
+        const RTF_GATEWAY: u32 = 0x0002;
+    } else if #[cfg(target_os = "macos")] {
+        use crate::shell::Shell;
+
+        const ROUTE_COMMAND: &str = "/sbin/route";
+    }
+}
+
+#[cfg(unix)]
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+
+/// The `NetworkInterface` struct represents a network interface. The struct supports `serde`
+/// serialization, and IP addresses serialize as strings.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct NetworkInterface {
     /// The name of the network interface.
     pub name: String,
@@ -16,7 +40,7 @@ pub struct NetworkInterface {
     pub addresses: Vec<InterfaceAddr>,
 
     /// The MAC address of the network interface.
-    pub mac_addr: Option<String>,
+    pub mac_addr: Option<MacAddress>,
 
     /// The index of the network interface.
     pub index: u32,
@@ -57,7 +81,7 @@ impl NetworkInterface {
     pub fn new(
         name: &str,
         addresses: Vec<InterfaceAddr>,
-        mac_addr: Option<String>,
+        mac_addr: Option<MacAddress>,
         index: u32,
         nameserver_addresses: Vec<IpAddr>,
         gateway_addresses: Vec<IpAddr>,
@@ -442,6 +466,278 @@ impl NetworkInterface {
             vec![]
         }
     }
+
+    /// Load the default gateway addresses of the interface from the system routing table into
+    /// `gateway_addresses`, replacing the addresses already there.
+    ///
+    /// On Linux the routes come from `/proc/net/route` and `/proc/net/ipv6_route`, on macOS from
+    /// `route -n get default`. On other platforms no gateways are found.
+    pub fn load_gateways(&mut self) {
+        self.gateway_addresses = default_gateways()
+            .into_iter()
+            .filter(|(name, _)| *name == self.name)
+            .map(|(_, gateway)| gateway)
+            .collect();
+    }
+
+    /// Load the nameserver addresses from `/etc/resolv.conf` into `nameserver_addresses`,
+    /// replacing the addresses already there. The system resolver is shared by every interface,
+    /// so every interface loads the same addresses. On platforms without `/etc/resolv.conf` no
+    /// nameservers are found.
+    pub fn load_nameservers(&mut self) {
+        cfg_if! {
+            if #[cfg(unix)] {
+                self.nameserver_addresses = match std::fs::read_to_string(RESOLV_CONF) {
+                    Ok(contents) => parse_resolv_conf(&contents),
+                    Err(_) => vec![],
+                };
+            } else {
+                self.nameserver_addresses = vec![];
+            }
+        }
+    }
+
+    /// Add an address to the interface on the running system. The address is applied
+    /// immediately with `ip addr add`, but it is not added to `addresses` and does not persist
+    /// across reboots.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to add. The address must have a netmask.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the address was added, otherwise a `FoundationError`.
+    #[cfg(target_os = "linux")]
+    pub fn add_address_live(&self, addr: &InterfaceAddr) -> Result<(), FoundationError> {
+        self.change_address_live("add", addr)
+    }
+
+    /// Remove an address from the interface on the running system with `ip addr del`. The
+    /// address is not removed from `addresses`.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to remove. The address must have a netmask.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the address was removed, otherwise a `FoundationError`.
+    #[cfg(target_os = "linux")]
+    pub fn remove_address_live(&self, addr: &InterfaceAddr) -> Result<(), FoundationError> {
+        self.change_address_live("del", addr)
+    }
+
+    /// Run `ip addr <action> <cidr> dev <name>` for the interface.
+    #[cfg(target_os = "linux")]
+    fn change_address_live(
+        &self,
+        action: &str,
+        addr: &InterfaceAddr,
+    ) -> Result<(), FoundationError> {
+        let cidr = match addr.get_in_cidr_notation() {
+            Some(cidr) => cidr,
+            None => {
+                return Err(FoundationError::OperationFailed(format!(
+                    "Address {} has no netmask",
+                    addr.ip
+                )))
+            }
+        };
+
+        let arguments = vec![
+            "addr".to_string(),
+            action.to_string(),
+            cidr,
+            "dev".to_string(),
+            self.name.clone(),
+        ];
+        let output = Shell::execute_command(IP_COMMAND, arguments)?;
+        if !output.status.success() {
+            return Err(FoundationError::OperationFailed(format!(
+                "{} addr {} failed for {}: {}",
+                IP_COMMAND,
+                action,
+                self.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Find the default routes on the running system.
+///
+/// # Returns
+///
+/// The interface name and gateway address of each default route.
+fn default_gateways() -> Vec<(String, IpAddr)> {
+    cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            let mut gateways = vec![];
+            if let Ok(contents) = std::fs::read_to_string(PROC_NET_ROUTE) {
+                gateways.extend(parse_proc_net_route(&contents));
+            }
+            if let Ok(contents) = std::fs::read_to_string(PROC_NET_IPV6_ROUTE) {
+                gateways.extend(parse_proc_net_ipv6_route(&contents));
+            }
+            gateways
+        } else if #[cfg(target_os = "macos")] {
+            let mut gateways = vec![];
+            for family in ["-inet", "-inet6"] {
+                let arguments = vec![
+                    "-n".to_string(),
+                    "get".to_string(),
+                    family.to_string(),
+                    "default".to_string(),
+                ];
+                if let (Some(output), _) = Shell::execute(ROUTE_COMMAND, arguments) {
+                    gateways.extend(parse_route_get(&output));
+                }
+            }
+            gateways
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Parse the IPv4 default routes from the contents of `/proc/net/route`.
+///
+/// Each line after the header holds the interface name, destination, gateway, flags, reference
+/// count, use count, metric, and mask. Addresses are hexadecimal in host byte order.
+///
+/// # Arguments
+///
+/// * `contents` - The contents of `/proc/net/route`.
+///
+/// # Returns
+///
+/// The interface name and gateway address of each default route.
+#[cfg(target_os = "linux")]
+fn parse_proc_net_route(contents: &str) -> Vec<(String, IpAddr)> {
+    let parse_address = |field: &str| u32::from_str_radix(field, 16).ok();
+
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.len() < 8 {
+                return None;
+            }
+
+            let destination = parse_address(fields[1])?;
+            let gateway = parse_address(fields[2])?;
+            let flags = parse_address(fields[3])?;
+            let mask = parse_address(fields[7])?;
+            if destination != 0 || mask != 0 || flags & RTF_GATEWAY == 0 {
+                return None;
+            }
+
+            // The kernel prints the address bytes as a native integer, so convert it back.
+            let gateway = Ipv4Addr::from_integer(u32::from_be(gateway));
+            Some((fields[0].to_string(), IpAddr::V4(gateway)))
+        })
+        .collect()
+}
+
+/// Parse the IPv6 default routes from the contents of `/proc/net/ipv6_route`.
+///
+/// Each line holds the destination, destination prefix length, source, source prefix length,
+/// next hop, metric, reference count, use count, flags, and interface name. Addresses are
+/// hexadecimal in network byte order.
+///
+/// # Arguments
+///
+/// * `contents` - The contents of `/proc/net/ipv6_route`.
+///
+/// # Returns
+///
+/// The interface name and gateway address of each default route.
+#[cfg(target_os = "linux")]
+fn parse_proc_net_ipv6_route(contents: &str) -> Vec<(String, IpAddr)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.len() < 10 {
+                return None;
+            }
+
+            let destination = u128::from_str_radix(fields[0], 16).ok()?;
+            let prefix_len = u8::from_str_radix(fields[1], 16).ok()?;
+            let next_hop = u128::from_str_radix(fields[4], 16).ok()?;
+            let flags = u32::from_str_radix(fields[8], 16).ok()?;
+            if destination != 0 || prefix_len != 0 || next_hop == 0 || flags & RTF_GATEWAY == 0 {
+                return None;
+            }
+
+            Some((
+                fields[9].to_string(),
+                IpAddr::V6(Ipv6Addr::from_integer(next_hop)),
+            ))
+        })
+        .collect()
+}
+
+/// Parse the default route from the output of `route -n get default`.
+///
+/// # Arguments
+///
+/// * `output` - The output of `route -n get default`.
+///
+/// # Returns
+///
+/// The interface name and gateway address of the default route, or `None` if the output does
+/// not name both.
+#[cfg(target_os = "macos")]
+fn parse_route_get(output: &str) -> Option<(String, IpAddr)> {
+    let mut gateway = None;
+    let mut interface = None;
+    for line in output.lines() {
+        match line.trim().split_once(':') {
+            Some(("gateway", value)) => gateway = parse_scoped_address(value.trim()),
+            Some(("interface", value)) => interface = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    Some((interface?, gateway?))
+}
+
+/// Parse the nameserver addresses from the contents of a `resolv.conf` file. Duplicate addresses
+/// are only listed once.
+///
+/// # Arguments
+///
+/// * `contents` - The contents of the `resolv.conf` file.
+///
+/// # Returns
+///
+/// The nameserver addresses in the order they appear in the file.
+#[cfg(unix)]
+fn parse_resolv_conf(contents: &str) -> Vec<IpAddr> {
+    let mut nameservers = vec![];
+    for line in contents.lines() {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some("nameserver") {
+            continue;
+        }
+
+        if let Some(address) = fields.next().and_then(parse_scoped_address) {
+            if !nameservers.contains(&address) {
+                nameservers.push(address);
+            }
+        }
+    }
+    nameservers
+}
+
+/// Parse an IP address, ignoring any `%zone` suffix on an IPv6 link-local address.
+#[cfg(unix)]
+fn parse_scoped_address(s: &str) -> Option<IpAddr> {
+    let address = s.split_once('%').map_or(s, |(address, _)| address);
+    address.parse().ok()
 }
 
 impl From<network_interface::NetworkInterface> for NetworkInterface {
@@ -455,7 +751,10 @@ impl From<network_interface::NetworkInterface> for NetworkInterface {
         NetworkInterface {
             name: value.name.clone(),
             addresses,
-            mac_addr: value.mac_addr.clone(),
+            mac_addr: value
+                .mac_addr
+                .as_deref()
+                .and_then(|mac_addr| mac_addr.parse().ok()),
             index: value.index,
             nameserver_addresses: vec![],
             gateway_addresses: vec![],
@@ -487,7 +786,7 @@ mod tests {
             None,
             None,
         ));
-        interface.mac_addr = Some("00:00:00:00:00:00".to_string());
+        interface.mac_addr = Some(MacAddress::new([0; 6]));
         interface.index = 1;
         interface
             .nameserver_addresses
@@ -1248,6 +1547,63 @@ mod tests {
         assert_eq!(interface.has_ipv6_address(), true);
     }
 
+    #[test]
+    fn test_serde_round_trip() {
+        let interface = NetworkInterface::new(
+            "eth0",
+            vec![
+                InterfaceAddr::try_from("192.168.1.37/24").unwrap(),
+                InterfaceAddr::new(
+                    IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+                    Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 255))),
+                    Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0))),
+                ),
+                InterfaceAddr::try_from("fe80::1/64").unwrap(),
+            ],
+            Some("00:11:22:33:44:55".parse().unwrap()),
+            2,
+            vec![
+                IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)),
+                IpAddr::V6(Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888)),
+            ],
+            vec![
+                IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+                IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1)),
+            ],
+        );
+
+        let json = serde_json::to_string(&interface).unwrap();
+        assert!(json.contains("\"192.168.1.37\""));
+        assert!(json.contains("\"2001:4860:4860::8888\""));
+        assert!(json.contains("\"00:11:22:33:44:55\""));
+
+        let decoded: NetworkInterface = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, interface);
+        assert_eq!(decoded.nameserver_addresses, interface.nameserver_addresses);
+        assert_eq!(decoded.gateway_addresses, interface.gateway_addresses);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_resolv_conf() {
+        let contents = "\
+# Generated by NetworkManager
+search example.com
+nameserver 192.168.1.1
+nameserver fe80::1%eth0
+; nameserver 10.0.0.1
+nameserver 192.168.1.1
+nameserver not-an-address
+";
+        assert_eq!(
+            parse_resolv_conf(contents),
+            vec![
+                IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)),
+                IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1)),
+            ]
+        );
+    }
+
     cfg_if! {
         if #[cfg(target_os = "linux")] {
             #[tokio::test]
@@ -1255,6 +1611,78 @@ mod tests {
                 let interface = NetworkInterface::new_with_name("eth0");
                 assert_eq!(interface.is_wireless_interface().await, false);
             }
+
+            #[test]
+            fn test_parse_proc_net_route() {
+                let contents = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+eth0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+wlan0\t0000FEA9\t00000000\t0001\t0\t0\t1000\t0000FFFF\t0\t0\t0
+";
+                let gateways = parse_proc_net_route(contents);
+                assert_eq!(
+                    gateways,
+                    vec![(
+                        "eth0".to_string(),
+                        IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))
+                    )]
+                );
+            }
+
+            #[test]
+            fn test_parse_proc_net_ipv6_route() {
+                let contents = "\
+fe800000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+";
+                let gateways = parse_proc_net_ipv6_route(contents);
+                assert_eq!(
+                    gateways,
+                    vec![(
+                        "eth0".to_string(),
+                        IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))
+                    )]
+                );
+            }
+
+            #[test]
+            fn test_address_live_requires_netmask() {
+                let interface = NetworkInterface::new_with_name("lo");
+                let addr = InterfaceAddr::try_from("198.51.100.7").unwrap();
+                assert!(interface.add_address_live(&addr).is_err());
+                assert!(interface.remove_address_live(&addr).is_err());
+            }
+
+            // Changing addresses needs CAP_NET_ADMIN, so this test only runs when
+            // FOUNDATION_LIVE_NETWORK_TESTS is set. FOUNDATION_LIVE_NETWORK_INTERFACE picks the
+            // interface to use (default "lo").
+            #[test]
+            fn test_add_remove_address_live() {
+                if std::env::var("FOUNDATION_LIVE_NETWORK_TESTS").is_err() {
+                    return;
+                }
+
+                let name = std::env::var("FOUNDATION_LIVE_NETWORK_INTERFACE")
+                    .unwrap_or_else(|_| "lo".to_string());
+                let interface = NetworkInterface::new_with_name(&name);
+                let addr = InterfaceAddr::from_cidr("198.51.100.7/32").unwrap();
+                let has_address = || {
+                    NetworkInterface::load()
+                        .iter()
+                        .filter(|interface| interface.name == name)
+                        .any(|interface| interface.get_addresses().contains(&&addr.ip))
+                };
+
+                interface.add_address_live(&addr).unwrap();
+                assert!(has_address());
+                assert!(interface.add_address_live(&addr).is_err());
+
+                interface.remove_address_live(&addr).unwrap();
+                assert!(!has_address());
+                assert!(interface.remove_address_live(&addr).is_err());
+            }
         }
     }
 }
@@ -1,11 +1,15 @@
 //! The `networkinterfaces` module provides the `NetworkInterfaces` struct to store network interfaces.
 
+use crate::network::interfaceaddr::InterfaceAddr;
 use crate::network::networkinterface::NetworkInterface;
 use network_interface::NetworkInterfaceConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::{Values, ValuesMut};
 use std::collections::HashMap;
+use std::net::IpAddr;
 
 /// The `NetworkInterfaces` struct stores network interfaces.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct NetworkInterfaces {
     /// A HashMap that stores network interfaces. The key is the name of the interface.
     interfaces: HashMap<String, NetworkInterface>,
@@ -75,6 +79,24 @@ impl NetworkInterfaces {
             .collect()
     }
 
+    /// Get an iterator over the interfaces in the container.
+    ///
+    /// # Returns
+    ///
+    /// An iterator that yields a reference to each interface in the container.
+    pub fn iter(&self) -> Values<'_, String, NetworkInterface> {
+        self.interfaces.values()
+    }
+
+    /// Get an iterator over mutable references to the interfaces in the container.
+    ///
+    /// # Returns
+    ///
+    /// An iterator that yields a mutable reference to each interface in the container.
+    pub fn iter_mut(&mut self) -> ValuesMut<'_, String, NetworkInterface> {
+        self.interfaces.values_mut()
+    }
+
     /// Get a vector of interface names.
     pub fn get_interface_names(&self) -> Vec<&str> {
         self.interfaces.keys().map(|name| name.as_str()).collect()
@@ -164,6 +186,35 @@ impl NetworkInterfaces {
             .collect()
     }
 
+    /// Get a vector of references to interfaces that have an address inside a subnet.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - An IP address in the subnet, normally the network address.
+    /// * `prefix` - The prefix length of the subnet in bits.
+    ///
+    /// # Returns
+    ///
+    /// A vector of references to interfaces with at least one address in the subnet. If the
+    /// prefix length is too large for the IP address, the vector is empty.
+    pub fn get_interfaces_in_subnet(&self, network: IpAddr, prefix: u8) -> Vec<&NetworkInterface> {
+        let subnet = match InterfaceAddr::from_cidr(&format!("{}/{}", network, prefix)) {
+            Ok(subnet) => subnet,
+            Err(_) => return vec![],
+        };
+        let subnet_address = subnet.network_address();
+
+        self.interfaces
+            .values()
+            .filter(|interface| {
+                interface.addresses.iter().any(|address| {
+                    InterfaceAddr::new(address.ip, None, subnet.netmask).network_address()
+                        == subnet_address
+                })
+            })
+            .collect()
+    }
+
     /// Get a vector of mutable references to interfaces with wireless addresses.
     pub async fn get_wireless_interfaces(&self) -> Vec<&NetworkInterface> {
         let mut wireless_interfaces: Vec<&NetworkInterface> = Vec::new();
@@ -226,11 +277,19 @@ impl NetworkInterfaces {
     }
 }
 
+impl<'a> IntoIterator for &'a NetworkInterfaces {
+    type Item = &'a NetworkInterface;
+    type IntoIter = Values<'a, String, NetworkInterface>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::network::interfaceaddr::InterfaceAddr;
-    use std::net::{IpAddr, Ipv4Addr};
+    use std::net::{Ipv4Addr, Ipv6Addr};
 
     #[test]
     fn test_network_interfaces() {
@@ -449,4 +508,102 @@ mod tests {
         assert_eq!(interfaces_with_global_addresses.len(), 1);
         assert!(interfaces_with_global_addresses.contains(&&interface2));
     }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut interfaces = NetworkInterfaces::new();
+        let mut interface = NetworkInterface::new_with_name("eth0");
+        interface.addresses.push(InterfaceAddr::new(
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+            None,
+            None,
+        ));
+        interface
+            .nameserver_addresses
+            .push(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)));
+        interfaces.add_interface(interface);
+        interfaces.add_interface(NetworkInterface::new_with_name("lo"));
+
+        let json = serde_json::to_string(&interfaces).unwrap();
+        let decoded: NetworkInterfaces = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, interfaces);
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut interfaces = NetworkInterfaces::new();
+        interfaces.add_interface(NetworkInterface::new_with_name("eth0"));
+        interfaces.add_interface(NetworkInterface::new_with_name("wlan0"));
+        interfaces.add_interface(NetworkInterface::new_with_name("lo"));
+
+        let mut names = interfaces
+            .iter()
+            .map(|interface| interface.name.as_str())
+            .collect::<Vec<&str>>();
+        names.sort();
+        assert_eq!(names, vec!["eth0", "lo", "wlan0"]);
+
+        let mut count = 0;
+        for interface in &interfaces {
+            assert!(interfaces.get_interface(&interface.name).is_some());
+            count += 1;
+        }
+        assert_eq!(count, 3);
+
+        for interface in interfaces.iter_mut() {
+            interface.index = 10;
+        }
+        assert!(interfaces.iter().all(|interface| interface.index == 10));
+    }
+
+    #[test]
+    fn test_get_interfaces_in_subnet() {
+        let mut interfaces = NetworkInterfaces::new();
+        let mut interface1 = NetworkInterface::new_with_name("eth0");
+        interface1
+            .addresses
+            .push(InterfaceAddr::try_from("192.168.1.10/24").unwrap());
+        let mut interface2 = NetworkInterface::new_with_name("eth1");
+        interface2
+            .addresses
+            .push(InterfaceAddr::try_from("10.0.0.5/8").unwrap());
+        interface2
+            .addresses
+            .push(InterfaceAddr::try_from("192.168.1.200/24").unwrap());
+        let mut interface3 = NetworkInterface::new_with_name("wlan0");
+        interface3
+            .addresses
+            .push(InterfaceAddr::try_from("10.1.2.3/8").unwrap());
+        interface3
+            .addresses
+            .push(InterfaceAddr::try_from("2001:db8::1/64").unwrap());
+        interfaces.add_interface(interface1.clone());
+        interfaces.add_interface(interface2.clone());
+        interfaces.add_interface(interface3.clone());
+
+        let in_subnet =
+            interfaces.get_interfaces_in_subnet(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 0)), 24);
+        assert_eq!(in_subnet.len(), 2);
+        assert!(in_subnet.contains(&&interface1));
+        assert!(in_subnet.contains(&&interface2));
+
+        let in_subnet =
+            interfaces.get_interfaces_in_subnet(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), 8);
+        assert_eq!(in_subnet.len(), 2);
+        assert!(in_subnet.contains(&&interface2));
+        assert!(in_subnet.contains(&&interface3));
+
+        let in_subnet = interfaces.get_interfaces_in_subnet(
+            IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0)),
+            64,
+        );
+        assert_eq!(in_subnet, vec![&interface3]);
+
+        assert!(interfaces
+            .get_interfaces_in_subnet(IpAddr::V4(Ipv4Addr::new(172, 16, 0, 0)), 12)
+            .is_empty());
+        assert!(interfaces
+            .get_interfaces_in_subnet(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 0)), 33)
+            .is_empty());
+    }
 }
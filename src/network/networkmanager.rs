@@ -1,9 +1,16 @@
 //! The `networkmanager` module provides the `NetworkManager` type, which is responsible for
 //! managing network configurations and services on a machine.
 
-use crate::network::networkconfiguration::NetworkConfiguration;
+use crate::error::FoundationError;
+use crate::network::dhcprange::DHCPRange;
+use crate::network::interfaceaddr::InterfaceAddr;
+use crate::network::networkconfiguration::{AddressMode, NetworkConfiguration};
 use crate::network::networkinterface::NetworkInterface;
+use crate::network::route::Route;
+use crate::network::wireless::configuration::WirelessConfiguration;
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
 
 cfg_if! {
     if #[cfg(target_os = "linux")] {
@@ -12,9 +19,11 @@ cfg_if! {
         use crate::network::hostapdservice::HostAPDService;
         use crate::network::netplanservice::NetplanService;
         use crate::network::networkservice::NetworkService;
-        use crate::platformid::{PlatformId, ProcessorArchitecture};
+        use crate::network::nmcliservice::NMCLIService;
+        use crate::platformid::{find_path_to_binary, PlatformId, ProcessorArchitecture};
         use crate::shell::Shell;
         use log::{debug, error};
+        use std::path::Path;
 
         const NETPLAN_DIR: &str = "/etc/netplan";
         const NETPLAN_CONF: &str = "/etc/netplan/99-network-manager-config.yaml";
@@ -23,6 +32,335 @@ cfg_if! {
         const DNSMASQ_CONF: &str = "/etc/dnsmasq.conf";
         const HOSTAPD_CONF: &str = "/etc/hostapd/hostapd.conf";
         const SYSTEMCTL_COMMAND: &str = "/usr/bin/systemctl";
+        const NMCLI_COMMAND: &str = "nmcli";
+    }
+}
+
+/// The `ConfigChange` enum describes one difference between two sets of network configurations,
+/// as reported by `NetworkManager::diff`. Each change names the interface it applies to, and
+/// per-field changes hold the old value (`from`) and the new value (`to`).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ConfigChange {
+    /// A configuration for the interface was added.
+    InterfaceAdded(String),
+
+    /// The configuration for the interface was removed.
+    InterfaceRemoved(String),
+
+    /// The address mode of the interface changed.
+    AddressModeChanged {
+        name: String,
+        from: AddressMode,
+        to: AddressMode,
+    },
+
+    /// The interface was enabled or disabled.
+    EnabledChanged { name: String, from: bool, to: bool },
+
+    /// The addresses of the interface changed.
+    AddressesChanged {
+        name: String,
+        from: Vec<InterfaceAddr>,
+        to: Vec<InterfaceAddr>,
+    },
+
+    /// The nameserver addresses of the interface changed.
+    NameserversChanged {
+        name: String,
+        from: Vec<IpAddr>,
+        to: Vec<IpAddr>,
+    },
+
+    /// The gateway addresses of the interface changed.
+    GatewaysChanged {
+        name: String,
+        from: Vec<IpAddr>,
+        to: Vec<IpAddr>,
+    },
+
+    /// The SSID of the interface's wireless configuration changed. `None` means the interface has
+    /// no wireless configuration.
+    WirelessSsidChanged {
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+
+    /// A wireless setting other than the SSID changed, such as the password or the channel. The
+    /// configurations are boxed to keep the size of `ConfigChange` down.
+    WirelessSettingsChanged {
+        name: String,
+        from: Option<Box<WirelessConfiguration>>,
+        to: Option<Box<WirelessConfiguration>>,
+    },
+
+    /// The DHCP range of the interface changed.
+    DHCPRangeChanged {
+        name: String,
+        from: Option<DHCPRange>,
+        to: Option<DHCPRange>,
+    },
+
+    /// The static routes of the interface changed.
+    RoutesChanged {
+        name: String,
+        from: Vec<Route>,
+        to: Vec<Route>,
+    },
+
+    /// The MTU of the interface changed.
+    MtuChanged {
+        name: String,
+        from: Option<u32>,
+        to: Option<u32>,
+    },
+}
+
+/// Compare two configurations for the same interface.
+///
+/// # Arguments
+///
+/// * `name` - The name of the interface.
+/// * `from` - The current configuration.
+/// * `to` - The new configuration.
+///
+/// # Returns
+///
+/// The changes needed to turn `from` into `to`.
+fn diff_configuration(
+    name: &str,
+    from: &NetworkConfiguration,
+    to: &NetworkConfiguration,
+) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    let name = name.to_string();
+
+    if from.address_mode != to.address_mode {
+        changes.push(ConfigChange::AddressModeChanged {
+            name: name.clone(),
+            from: from.address_mode,
+            to: to.address_mode,
+        });
+    }
+
+    if from.enabled != to.enabled {
+        changes.push(ConfigChange::EnabledChanged {
+            name: name.clone(),
+            from: from.enabled,
+            to: to.enabled,
+        });
+    }
+
+    if from.interface.addresses != to.interface.addresses {
+        changes.push(ConfigChange::AddressesChanged {
+            name: name.clone(),
+            from: from.interface.addresses.clone(),
+            to: to.interface.addresses.clone(),
+        });
+    }
+
+    if from.interface.nameserver_addresses != to.interface.nameserver_addresses {
+        changes.push(ConfigChange::NameserversChanged {
+            name: name.clone(),
+            from: from.interface.nameserver_addresses.clone(),
+            to: to.interface.nameserver_addresses.clone(),
+        });
+    }
+
+    if from.interface.gateway_addresses != to.interface.gateway_addresses {
+        changes.push(ConfigChange::GatewaysChanged {
+            name: name.clone(),
+            from: from.interface.gateway_addresses.clone(),
+            to: to.interface.gateway_addresses.clone(),
+        });
+    }
+
+    let from_ssid = from.wifi_configuration.as_ref().map(|w| w.ssid.clone());
+    let to_ssid = to.wifi_configuration.as_ref().map(|w| w.ssid.clone());
+    if from_ssid != to_ssid {
+        changes.push(ConfigChange::WirelessSsidChanged {
+            name: name.clone(),
+            from: from_ssid,
+            to: to_ssid,
+        });
+    }
+
+    // Compare the remaining wireless settings with the SSIDs made equal, so an SSID change is
+    // not reported twice. Adding or removing the wireless configuration counts as a settings
+    // change as well.
+    let settings_changed = match (&from.wifi_configuration, &to.wifi_configuration) {
+        (Some(from_wifi), Some(to_wifi)) => {
+            let mut from_wifi = from_wifi.clone();
+            from_wifi.ssid = to_wifi.ssid.clone();
+            from_wifi != *to_wifi
+        }
+        (None, None) => false,
+        _ => true,
+    };
+    if settings_changed {
+        changes.push(ConfigChange::WirelessSettingsChanged {
+            name: name.clone(),
+            from: from.wifi_configuration.clone().map(Box::new),
+            to: to.wifi_configuration.clone().map(Box::new),
+        });
+    }
+
+    if from.dhcp_range != to.dhcp_range {
+        changes.push(ConfigChange::DHCPRangeChanged {
+            name: name.clone(),
+            from: from.dhcp_range.clone(),
+            to: to.dhcp_range.clone(),
+        });
+    }
+
+    if from.routes != to.routes {
+        changes.push(ConfigChange::RoutesChanged {
+            name: name.clone(),
+            from: from.routes.clone(),
+            to: to.routes.clone(),
+        });
+    }
+
+    if from.mtu != to.mtu {
+        changes.push(ConfigChange::MtuChanged {
+            name,
+            from: from.mtu,
+            to: to.mtu,
+        });
+    }
+
+    changes
+}
+
+/// The `ConfigurationService` enum names the services whose configuration files
+/// `NetworkManager::save_settings_to_system` writes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ConfigurationService {
+    DHCPCD,
+    DNSMasq,
+    HostAPD,
+    Netplan,
+}
+
+impl std::fmt::Display for ConfigurationService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigurationService::DHCPCD => write!(f, "DHCPCD"),
+            ConfigurationService::DNSMasq => write!(f, "DNSMasq"),
+            ConfigurationService::HostAPD => write!(f, "HostAPD"),
+            ConfigurationService::Netplan => write!(f, "Netplan"),
+        }
+    }
+}
+
+/// The `PlannedAction` enum describes one step `NetworkManager::save_settings_to_system` takes to
+/// apply the configurations to the system, as reported by
+/// `NetworkManager::save_settings_to_system_dry_run`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PlannedAction {
+    /// Write the configurations to a service's configuration file.
+    WriteConfiguration {
+        service: ConfigurationService,
+        path: PathBuf,
+    },
+
+    /// Rename a file to move it out of the way.
+    RenameFile { from: PathBuf, to: PathBuf },
+
+    /// Restart a systemd service.
+    RestartService(String),
+
+    /// Run `netplan apply` to apply the Netplan configuration.
+    ApplyNetplan,
+
+    /// Write the configurations as NetworkManager connection profiles with the nmcli binary at
+    /// the path.
+    WriteNetworkManagerProfiles(PathBuf),
+}
+
+cfg_if! {
+    if #[cfg(target_os = "linux")] {
+        /// Plan the steps needed to save network settings to the system.
+        ///
+        /// # Arguments
+        ///
+        /// * `platform_id` - The platform the settings are saved on.
+        /// * `find_netplan_files` - Finds the existing Netplan `.yaml` files. Only called on
+        ///   platforms that use Netplan.
+        /// * `find_nmcli` - Finds the nmcli binary. Only called on platforms that use neither
+        ///   Netplan nor dhcpcd.
+        ///
+        /// # Returns
+        ///
+        /// The steps, in the order they should run.
+        fn plan_save_actions(
+            platform_id: &PlatformId,
+            find_netplan_files: impl FnOnce() -> Vec<PathBuf>,
+            find_nmcli: impl FnOnce() -> Option<PathBuf>,
+        ) -> Vec<PlannedAction> {
+            let mut actions = vec![
+                PlannedAction::WriteConfiguration {
+                    service: ConfigurationService::DNSMasq,
+                    path: PathBuf::from(DNSMASQ_CONF),
+                },
+                PlannedAction::RestartService("dnsmasq".to_string()),
+                PlannedAction::WriteConfiguration {
+                    service: ConfigurationService::HostAPD,
+                    path: PathBuf::from(HOSTAPD_CONF),
+                },
+                PlannedAction::RestartService("hostapd".to_string()),
+            ];
+
+            if platform_id.vendor == "Ubuntu" &&
+                (platform_id.processor_architecture == ProcessorArchitecture::X86_64 ||
+                    platform_id.processor_architecture == ProcessorArchitecture::ARM64) {
+                // Move the existing netplan files out of the way so that only our file applies.
+                for yaml_path in find_netplan_files() {
+                    let new_yaml_path = yaml_path.with_extension("yaml.orig");
+                    actions.push(PlannedAction::RenameFile {
+                        from: yaml_path,
+                        to: new_yaml_path,
+                    });
+                }
+
+                actions.push(PlannedAction::WriteConfiguration {
+                    service: ConfigurationService::Netplan,
+                    path: PathBuf::from(NETPLAN_CONF),
+                });
+                actions.push(PlannedAction::ApplyNetplan);
+            } else if platform_id.name == "Debian" &&
+                (platform_id.processor_architecture == ProcessorArchitecture::ARM64 || platform_id.processor_architecture == ProcessorArchitecture::ARM) {
+                actions.push(PlannedAction::WriteConfiguration {
+                    service: ConfigurationService::DHCPCD,
+                    path: PathBuf::from(DHCPCD_CONF),
+                });
+                actions.push(PlannedAction::RestartService("dhcpcd".to_string()));
+            } else if let Some(nmcli_path) = find_nmcli() {
+                actions.push(PlannedAction::WriteNetworkManagerProfiles(nmcli_path));
+            }
+
+            actions
+        }
+
+        /// Find the Netplan `.yaml` files in a directory.
+        ///
+        /// # Arguments
+        ///
+        /// * `netplan_dir` - The directory holding the Netplan `.yaml` files.
+        ///
+        /// # Returns
+        ///
+        /// The paths of the `.yaml` files, sorted by name, or a `FoundationError` if the directory
+        /// could not be read.
+        fn find_netplan_yaml_files(netplan_dir: &Path) -> Result<Vec<PathBuf>, FoundationError> {
+            let mut yaml_files = std::fs::read_dir(netplan_dir)?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().unwrap_or_default() == "yaml")
+                .collect::<Vec<_>>();
+            yaml_files.sort();
+            Ok(yaml_files)
+        }
     }
 }
 
@@ -170,13 +508,68 @@ impl NetworkManager {
             .map(|c| c.get_name())
     }
 
+    /// Compare the configurations in this manager with the configurations in another manager.
+    ///
+    /// Interface details that describe the hardware rather than the configuration, such as the
+    /// MAC address and the interface index, are not compared.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The manager holding the new configurations.
+    ///
+    /// # Returns
+    ///
+    /// The changes needed to turn the configurations in this manager into the configurations in
+    /// `other`, ordered by interface name. An empty list means the configurations match.
+    pub fn diff(&self, other: &NetworkManager) -> Vec<ConfigChange> {
+        let mut names: Vec<&String> = self
+            .configurations
+            .keys()
+            .chain(other.configurations.keys())
+            .collect();
+        names.sort();
+        names.dedup();
+
+        let mut changes = Vec::new();
+        for name in names {
+            match (
+                self.configurations.get(name),
+                other.configurations.get(name),
+            ) {
+                (Some(from), Some(to)) => changes.extend(diff_configuration(name, from, to)),
+                (Some(_), None) => changes.push(ConfigChange::InterfaceRemoved(name.clone())),
+                (None, Some(_)) => changes.push(ConfigChange::InterfaceAdded(name.clone())),
+                (None, None) => {}
+            }
+        }
+        changes
+    }
+
     /// Remove all the network configurations from the manager.
     pub fn clear(&mut self) {
         self.configurations.clear();
     }
 
     /// Load network settings from the system configuration into the manager.
+    ///
+    /// Failures are logged and otherwise ignored. Use `try_load_settings_from_system` to find
+    /// out whether loading succeeded.
     pub fn load_settings_from_system(&mut self) {
+        let _ = self.try_load_settings_from_system();
+    }
+
+    /// Load network settings from the system configuration into the manager.
+    ///
+    /// A failure to load one service's configuration does not stop the others from loading.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if every configuration loaded, otherwise the errors from the configurations
+    /// that failed to load.
+    pub fn try_load_settings_from_system(&mut self) -> Result<(), Vec<FoundationError>> {
+        #[allow(unused_mut)]
+        let mut errors = vec![];
+
         // Load network interfaces currently running on the system.
         let interfaces = NetworkInterface::load();
 
@@ -196,28 +589,7 @@ impl NetworkManager {
                 if platform_id.vendor == "Ubuntu" &&
                     platform_id.processor_architecture == ProcessorArchitecture::X86_64 {
                     // We are running on Ubuntu 64-bit, assume we have access to the Netplan service.
-
-                    // Get the netplan .yaml files.
-                    let mut netplan_yaml_files = match std::fs::read_dir(NETPLAN_DIR) {
-                        Ok(entries) => {
-                            entries.into_iter()
-                                .filter(|entry| entry.is_ok())
-                                .filter(|entry| entry.as_ref().unwrap().path().extension().unwrap_or_default() == "yaml")
-                                .map(|entry| entry.unwrap().path())
-                                .collect::<Vec<_>>()
-                        },
-                        Err(_) => return,
-                    };
-
-                    netplan_yaml_files.sort();
-
-                    for yaml_path in netplan_yaml_files {
-                        debug!("Loading {:?}", yaml_path);
-                        let mut netplan_service = NetplanService::new(yaml_path.clone());
-                        if let Err(e) = netplan_service.load_configuration(&mut self.configurations) {
-                            error!("Failed to load Netplan configuration from {}: {}", yaml_path.to_string_lossy(), e);
-                        }
-                    }
+                    errors.extend(self.load_netplan_settings(Path::new(NETPLAN_DIR)));
                 } else if platform_id.name == "Debian" &&
                     (platform_id.processor_architecture == ProcessorArchitecture::ARM64 || platform_id.processor_architecture == ProcessorArchitecture::ARM) {
                     // We are running on Debian ARM box, probably a Raspberry Pi. Assume we have access to the dhcpcd service.
@@ -227,8 +599,16 @@ impl NetworkManager {
                         let mut dhcpcd_service = DHCPCDService::new(config_file.clone());
                         if let Err(e) = dhcpcd_service.load_configuration(&mut self.configurations)  {
                             error!("Failed to load DHCPCD configuration from {}: {}", config_file.to_string_lossy(), e);
+                            errors.push(e);
                         }
                     }
+                } else if let Some(nmcli_path) = find_path_to_binary(NMCLI_COMMAND) {
+                    // NetworkManager manages the interfaces, read its connection profiles.
+                    let mut nmcli_service = NMCLIService::new(nmcli_path);
+                    if let Err(e) = nmcli_service.load_configuration(&mut self.configurations) {
+                        error!("Failed to load NetworkManager configuration: {}", e);
+                        errors.push(e);
+                    }
                 }
 
                 let dnsmasq_config_file = std::path::PathBuf::from(DNSMASQ_CONF);
@@ -236,6 +616,7 @@ impl NetworkManager {
                     let mut dnsmasq_service = DNSMasqService::new(dnsmasq_config_file.clone());
                     if let Err(e) = dnsmasq_service.load_configuration(&mut self.configurations) {
                         error!("Failed to load DNSMasq configuration from {}: {}", dnsmasq_config_file.to_string_lossy(), e);
+                        errors.push(e);
                     }
                 }
 
@@ -244,83 +625,409 @@ impl NetworkManager {
                     let mut hostapd_service = HostAPDService::new(hostapd_config_file.clone());
                     if let Err(e) = hostapd_service.load_configuration(&mut self.configurations) {
                         error!("Failed to load HostAPD configuration from {}: {}", hostapd_config_file.to_string_lossy(), e);
+                        errors.push(e);
                     }
                 }
             }
         }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Load the Netplan configuration files in a directory into the manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `netplan_dir` - The directory holding the Netplan `.yaml` files.
+    ///
+    /// # Returns
+    ///
+    /// The errors from reading the directory and from the files that failed to load.
+    #[cfg(target_os = "linux")]
+    fn load_netplan_settings(&mut self, netplan_dir: &Path) -> Vec<FoundationError> {
+        let netplan_yaml_files = match find_netplan_yaml_files(netplan_dir) {
+            Ok(netplan_yaml_files) => netplan_yaml_files,
+            Err(e) => {
+                error!(
+                    "Failed to read directory {}: {}",
+                    netplan_dir.to_string_lossy(),
+                    e
+                );
+                return vec![e];
+            }
+        };
+
+        let mut errors = vec![];
+        for yaml_path in netplan_yaml_files {
+            debug!("Loading {:?}", yaml_path);
+            let mut netplan_service = NetplanService::new(yaml_path.clone());
+            if let Err(e) = netplan_service.load_configuration(&mut self.configurations) {
+                error!(
+                    "Failed to load Netplan configuration from {}: {}",
+                    yaml_path.to_string_lossy(),
+                    e
+                );
+                errors.push(e);
+            }
+        }
+        errors
     }
 
     /// Save network settings from the manager to the system configuration.
     ///
     /// This method will write the network configurations to the system configuration files and
-    /// restart the necessary services to apply the changes.
+    /// restart the necessary services to apply the changes. Use `save_settings_to_system_dry_run`
+    /// to see the steps without taking them.
     pub fn save_settings_to_system(&self) {
+        for action in self.save_settings_to_system_dry_run() {
+            self.perform_action(&action);
+        }
+    }
+
+    /// Report what `save_settings_to_system` would do, without writing any files or restarting
+    /// any services.
+    ///
+    /// # Returns
+    ///
+    /// The steps `save_settings_to_system` would take, in order. The list is empty on platforms
+    /// where saving settings is not supported.
+    pub fn save_settings_to_system_dry_run(&self) -> Vec<PlannedAction> {
         cfg_if! {
             if #[cfg(target_os = "linux")] {
-                let dnsmasq_config_file = std::path::PathBuf::from(DNSMASQ_CONF);
-                let dnsmasq_service = DNSMasqService::new(dnsmasq_config_file.clone());
-                if let Err(e) = dnsmasq_service.write_configuration(&self.configurations) {
-                    error!("Failed to write DNSMasq configuration to {}: {}", dnsmasq_config_file.to_string_lossy(), e);
+                let platform_id = PlatformId::new();
+                let find_netplan_files = || {
+                    find_netplan_yaml_files(Path::new(NETPLAN_DIR)).unwrap_or_else(|e| {
+                        error!("Failed to read directory {}: {}", NETPLAN_DIR, e);
+                        vec![]
+                    })
+                };
+                plan_save_actions(&platform_id, find_netplan_files, || {
+                    find_path_to_binary(NMCLI_COMMAND)
+                })
+            } else {
+                vec![]
+            }
+        }
+    }
+
+    /// Perform one step of saving network settings to the system. Failures are logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The step to perform.
+    #[allow(unused_variables)]
+    fn perform_action(&self, action: &PlannedAction) {
+        cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                match action {
+                    PlannedAction::WriteConfiguration { service, path } => {
+                        let result = match service {
+                            ConfigurationService::DHCPCD => DHCPCDService::new(path.clone()).write_configuration(&self.configurations),
+                            ConfigurationService::DNSMasq => DNSMasqService::new(path.clone()).write_configuration(&self.configurations),
+                            ConfigurationService::HostAPD => HostAPDService::new(path.clone()).write_configuration(&self.configurations),
+                            ConfigurationService::Netplan => NetplanService::new(path.clone()).write_configuration(&self.configurations),
+                        };
+                        if let Err(e) = result {
+                            error!("Failed to write {} configuration to {}: {}", service, path.to_string_lossy(), e);
+                        }
+                    }
+                    PlannedAction::RenameFile { from, to } => {
+                        if let Err(e) = std::fs::rename(from, to) {
+                            error!("Failed to rename {} to {}: {}", from.to_string_lossy(), to.to_string_lossy(), e);
+                        }
+                    }
+                    PlannedAction::RestartService(service) => {
+                        Shell::execute(SYSTEMCTL_COMMAND, vec!["restart".to_string(), service.clone()]);
+                    }
+                    PlannedAction::ApplyNetplan => {
+                        Shell::execute(NETPLAN_COMMAND, vec!["apply".to_string()]);
+                    }
+                    PlannedAction::WriteNetworkManagerProfiles(nmcli_path) => {
+                        let nmcli_service = NMCLIService::new(nmcli_path.clone());
+                        if let Err(e) = nmcli_service.write_configuration(&self.configurations) {
+                            error!("Failed to write NetworkManager configuration: {}", e);
+                        }
+                    }
                 }
+            }
+        }
+    }
+}
 
-                Shell::execute(SYSTEMCTL_COMMAND, vec!["restart".to_string(), "dnsmasq".to_string()]);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::macaddress::MacAddress;
+    use crate::network::wireless::configuration::{WirelessMode, WirelessStandard};
 
-                let hostapd_config_file = std::path::PathBuf::from(HOSTAPD_CONF);
-                let hostapd_service = HostAPDService::new(hostapd_config_file.clone());
-                if let Err(e) = hostapd_service.write_configuration(&self.configurations) {
-                    error!("Failed to write HostAPD configuration to {}: {}", hostapd_config_file.to_string_lossy(), e);
-                }
+    fn manager_with(configurations: Vec<NetworkConfiguration>) -> NetworkManager {
+        let mut manager = NetworkManager::new();
+        for configuration in configurations {
+            manager.add_configuration(configuration);
+        }
+        manager
+    }
 
-                Shell::execute(SYSTEMCTL_COMMAND, vec!["restart".to_string(), "hostapd".to_string()]);
+    fn wireless(ssid: &str, channel: u32) -> WirelessConfiguration {
+        WirelessConfiguration {
+            ssid: ssid.to_string(),
+            standard: WirelessStandard::G,
+            mode: WirelessMode::AccessPoint,
+            password: Some("password".to_string()),
+            channel,
+            wpa_mode: 2,
+            wpa_key_mgmt: None,
+            wpa_pairwise: None,
+            rsn_pairwise: None,
+            country_code: None,
+            security_protocol: None,
+        }
+    }
 
-                let platform_id = PlatformId::new();
+    #[test]
+    fn test_diff_identical() {
+        let current = manager_with(vec![
+            NetworkConfiguration::new_with_name("eth0"),
+            NetworkConfiguration::new_with_name("wlan0"),
+        ]);
+        assert!(current.diff(&current.clone()).is_empty());
+    }
 
-                if platform_id.vendor == "Ubuntu" &&
-                    (platform_id.processor_architecture == ProcessorArchitecture::X86_64 ||
-                        platform_id.processor_architecture == ProcessorArchitecture::ARM64) {
-
-                    // Find the .yaml netplan files.
-                    let netplan_yaml_files = match std::fs::read_dir(NETPLAN_DIR) {
-                        Ok(entries) => {
-                            entries.into_iter()
-                                .filter(|entry| entry.is_ok())
-                                .filter(|entry| entry.as_ref().unwrap().path().extension().unwrap_or_default() == "yaml")
-                                .filter(|entry| entry.as_ref().unwrap().path().exists())
-                                .map(|entry| entry.unwrap().path())
-                                .collect::<Vec<_>>()
-                        },
-                        Err(e) => {
-                            error!("Failed to read directory {}: {}", NETPLAN_DIR, e);
-                            vec![]
-                        },
-                    };
-
-                    for yaml_path in netplan_yaml_files {
-                        let new_yaml_path = yaml_path.with_extension("yaml.orig");
-                        if let Err(e) = std::fs::rename(&yaml_path, &new_yaml_path) {
-                            error!("Failed to rename {} to {}: {}", yaml_path.to_string_lossy(), new_yaml_path.to_string_lossy(), e);
-                            continue;
-                        }
-                    }
+    #[test]
+    fn test_diff_address_mode() {
+        let current = manager_with(vec![
+            NetworkConfiguration::new_with_name("eth0"),
+            NetworkConfiguration::new_with_name("wlan0"),
+        ]);
 
-                    let netplan_config_file = std::path::PathBuf::from(NETPLAN_CONF);
-                    let netplan_service = NetplanService::new(netplan_config_file.clone());
-                    if let Err(e) = netplan_service.write_configuration(&self.configurations) {
-                        error!("Failed to write Netplan configuration to {}: {}", netplan_config_file.to_string_lossy(), e);
-                    }
+        let mut eth0 = NetworkConfiguration::new_with_name("eth0");
+        eth0.address_mode = AddressMode::Static;
+        let updated = manager_with(vec![eth0, NetworkConfiguration::new_with_name("wlan0")]);
 
-                    Shell::execute(NETPLAN_COMMAND, vec!["apply".to_string()]);
-                } else if platform_id.name == "Debian" &&
-                    (platform_id.processor_architecture == ProcessorArchitecture::ARM64 || platform_id.processor_architecture == ProcessorArchitecture::ARM) {
-                    let dhcpcd_config_file = std::path::PathBuf::from(DHCPCD_CONF);
-                    let dhcpcd_service = DHCPCDService::new(dhcpcd_config_file.clone());
-                    if let Err(e) = dhcpcd_service.write_configuration(&self.configurations) {
-                        error!("Failed to write DHCPCD configuration to {}: {}", dhcpcd_config_file.to_string_lossy(), e);
-                    }
+        assert_eq!(
+            current.diff(&updated),
+            vec![ConfigChange::AddressModeChanged {
+                name: "eth0".to_string(),
+                from: AddressMode::DHCP,
+                to: AddressMode::Static,
+            }]
+        );
+    }
 
-                    Shell::execute(SYSTEMCTL_COMMAND, vec!["restart".to_string(), "dhcpcd".to_string()]);
-                }
-            }
+    #[test]
+    fn test_diff_interfaces_added_and_removed() {
+        let current = manager_with(vec![
+            NetworkConfiguration::new_with_name("eth0"),
+            NetworkConfiguration::new_with_name("eth1"),
+        ]);
+        let updated = manager_with(vec![
+            NetworkConfiguration::new_with_name("eth0"),
+            NetworkConfiguration::new_with_name("wlan0"),
+        ]);
+
+        assert_eq!(
+            current.diff(&updated),
+            vec![
+                ConfigChange::InterfaceRemoved("eth1".to_string()),
+                ConfigChange::InterfaceAdded("wlan0".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_wireless() {
+        let mut wlan0 = NetworkConfiguration::new_with_name("wlan0");
+        wlan0.wifi_configuration = Some(wireless("home", 6));
+        let current = manager_with(vec![wlan0.clone()]);
+
+        // Changing only the SSID reports only the SSID.
+        wlan0.wifi_configuration = Some(wireless("office", 6));
+        let updated = manager_with(vec![wlan0.clone()]);
+        assert_eq!(
+            current.diff(&updated),
+            vec![ConfigChange::WirelessSsidChanged {
+                name: "wlan0".to_string(),
+                from: Some("home".to_string()),
+                to: Some("office".to_string()),
+            }]
+        );
+
+        // Changing another wireless setting reports the settings.
+        wlan0.wifi_configuration = Some(wireless("home", 11));
+        wlan0.mtu = Some(1400);
+        let updated = manager_with(vec![wlan0]);
+        assert_eq!(
+            current.diff(&updated),
+            vec![
+                ConfigChange::WirelessSettingsChanged {
+                    name: "wlan0".to_string(),
+                    from: Some(Box::new(wireless("home", 6))),
+                    to: Some(Box::new(wireless("home", 11))),
+                },
+                ConfigChange::MtuChanged {
+                    name: "wlan0".to_string(),
+                    from: None,
+                    to: Some(1400),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_every_field() {
+        let base = NetworkConfiguration::new_with_name("eth0");
+
+        // Destructure exhaustively, so that a new field fails to compile until it is covered here.
+        let NetworkConfiguration {
+            address_mode: _,
+            interface,
+            enabled: _,
+            wifi_configuration: _,
+            dhcp_range: _,
+            routes: _,
+            mtu: _,
+        } = base.clone();
+        let NetworkInterface {
+            name: _,
+            addresses: _,
+            mac_addr: _,
+            index: _,
+            nameserver_addresses: _,
+            gateway_addresses: _,
+        } = interface;
+
+        let changes: Vec<fn(&mut NetworkConfiguration)> = vec![
+            |c| c.address_mode = AddressMode::Static,
+            |c| c.enabled = !c.enabled,
+            |c| c.wifi_configuration = Some(wireless("home", 6)),
+            |c| {
+                c.dhcp_range = Some(DHCPRange::new(
+                    IpAddr::V4(std::net::Ipv4Addr::new(192, 168, 1, 2)),
+                    IpAddr::V4(std::net::Ipv4Addr::new(192, 168, 1, 20)),
+                ))
+            },
+            |c| {
+                c.routes.push(Route::default_route(
+                    IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+                    None,
+                ))
+            },
+            |c| c.mtu = Some(1400),
+            |c| {
+                c.interface
+                    .addresses
+                    .push(InterfaceAddr::from_cidr("192.168.1.1/24").unwrap())
+            },
+            |c| {
+                c.interface
+                    .nameserver_addresses
+                    .push(IpAddr::V4(std::net::Ipv4Addr::LOCALHOST))
+            },
+            |c| {
+                c.interface
+                    .gateway_addresses
+                    .push(IpAddr::V4(std::net::Ipv4Addr::LOCALHOST))
+            },
+        ];
+        for change in changes {
+            let mut updated = base.clone();
+            change(&mut updated);
+            assert_ne!(base, updated);
+            assert!(!diff_configuration("eth0", &base, &updated).is_empty());
+        }
+
+        // The hardware details of the interface are the only fields that are not compared.
+        let mut updated = base.clone();
+        updated.interface.mac_addr = Some(MacAddress::new([2, 0, 0, 0, 0, 1]));
+        updated.interface.index += 1;
+        assert_ne!(base, updated);
+        assert!(diff_configuration("eth0", &base, &updated).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_plan_save_actions() {
+        let mut platform_id = PlatformId::new();
+        platform_id.vendor = "Ubuntu".to_string();
+        platform_id.processor_architecture = ProcessorArchitecture::X86_64;
+
+        let actions = plan_save_actions(
+            &platform_id,
+            || vec![PathBuf::from("/etc/netplan/50-cloud-init.yaml")],
+            || panic!("nmcli is not used on Ubuntu"),
+        );
+        assert_eq!(
+            actions,
+            vec![
+                PlannedAction::WriteConfiguration {
+                    service: ConfigurationService::DNSMasq,
+                    path: PathBuf::from("/etc/dnsmasq.conf"),
+                },
+                PlannedAction::RestartService("dnsmasq".to_string()),
+                PlannedAction::WriteConfiguration {
+                    service: ConfigurationService::HostAPD,
+                    path: PathBuf::from("/etc/hostapd/hostapd.conf"),
+                },
+                PlannedAction::RestartService("hostapd".to_string()),
+                PlannedAction::RenameFile {
+                    from: PathBuf::from("/etc/netplan/50-cloud-init.yaml"),
+                    to: PathBuf::from("/etc/netplan/50-cloud-init.yaml.orig"),
+                },
+                PlannedAction::WriteConfiguration {
+                    service: ConfigurationService::Netplan,
+                    path: PathBuf::from("/etc/netplan/99-network-manager-config.yaml"),
+                },
+                PlannedAction::ApplyNetplan,
+            ]
+        );
+
+        platform_id.vendor = "Raspberry Pi".to_string();
+        platform_id.name = "Debian".to_string();
+        platform_id.processor_architecture = ProcessorArchitecture::ARM64;
+        let actions = plan_save_actions(
+            &platform_id,
+            || panic!("netplan is not used on Debian"),
+            || panic!("nmcli is not used on Debian"),
+        );
+        assert!(actions.contains(&PlannedAction::WriteConfiguration {
+            service: ConfigurationService::DHCPCD,
+            path: PathBuf::from("/etc/dhcpcd.conf"),
+        }));
+        assert_eq!(
+            actions.last(),
+            Some(&PlannedAction::RestartService("dhcpcd".to_string()))
+        );
+
+        platform_id.name = "Fedora".to_string();
+        platform_id.vendor = "Fedora".to_string();
+        platform_id.processor_architecture = ProcessorArchitecture::X86_64;
+        let actions = plan_save_actions(
+            &platform_id,
+            || panic!("netplan is not used on Fedora"),
+            || Some(PathBuf::from("/usr/bin/nmcli")),
+        );
+        assert_eq!(
+            actions.last(),
+            Some(&PlannedAction::WriteNetworkManagerProfiles(PathBuf::from(
+                "/usr/bin/nmcli"
+            )))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_load_netplan_settings_missing_dir() {
+        let mut manager = NetworkManager::new();
+        let errors = manager.load_netplan_settings(Path::new("/nonexistent/foundation/netplan"));
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            FoundationError::IO(e) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            e => panic!("unexpected error: {}", e),
         }
+        assert!(manager.configurations.is_empty());
     }
 }
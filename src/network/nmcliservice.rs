@@ -0,0 +1,809 @@
+//! The `nmcliservice` module provides code that interacts with the NetworkManager service on a
+//! Linux machine through the `nmcli` command line tool.
+
+use crate::error::FoundationError;
+use crate::network::interfaceaddr::InterfaceAddr;
+use crate::network::ipaddrquery::IpAddrQuery;
+use crate::network::networkconfiguration::{AddressMode, NetworkConfiguration};
+use crate::network::networkservice::NetworkService;
+use crate::network::wireless::configuration::{WirelessConfiguration, WirelessMode};
+use crate::shell::{Shell, ShellCommand};
+use crate::systemctlservice::SystemCTLService;
+use log::{debug, error};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// The directory where NetworkManager keeps its connection profiles.
+const SYSTEM_CONNECTIONS_DIR: &str = "/etc/NetworkManager/system-connections";
+
+/// The connection type `nmcli` reports for ethernet connections.
+const ETHERNET_TYPE: &str = "802-3-ethernet";
+
+/// The connection type `nmcli` reports for wireless connections.
+const WIRELESS_TYPE: &str = "802-11-wireless";
+
+/// The fields requested from `nmcli` for an ethernet connection.
+const ETHERNET_FIELDS: &str =
+    "connection.interface-name,connection.autoconnect,ipv4.method,ipv4.addresses,ipv4.dns,ipv4.gateway";
+
+/// The field that holds the interface a connection profile is bound to.
+const INTERFACE_NAME_FIELD: &str = "connection.interface-name";
+
+/// The fields requested from `nmcli` for a wireless connection.
+const WIRELESS_FIELDS: &str = "connection.interface-name,connection.autoconnect,ipv4.method,ipv4.addresses,ipv4.dns,ipv4.gateway,802-11-wireless.ssid,802-11-wireless.mode,802-11-wireless-security.psk";
+
+/// The `NMCLIConnection` struct holds one line of `nmcli -t -f NAME,TYPE,DEVICE connection show`
+/// output.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct NMCLIConnection {
+    /// The name of the connection profile.
+    name: String,
+
+    /// The type of the connection, for example `802-3-ethernet`.
+    connection_type: String,
+
+    /// The device the connection is active on, or an empty string if it is not active.
+    device: String,
+}
+
+/// The service object.
+pub struct NMCLIService {
+    /// The path to the `nmcli` binary.
+    nmcli: PathBuf,
+    service: SystemCTLService,
+}
+
+impl NMCLIService {
+    /// Create a new NMCLIService object.
+    ///
+    /// # Arguments
+    ///
+    /// * `nmcli` - The path to the `nmcli` binary.
+    pub fn new(nmcli: PathBuf) -> NMCLIService {
+        NMCLIService {
+            nmcli,
+            service: SystemCTLService::new("NetworkManager".to_string()),
+        }
+    }
+
+    /// Run `nmcli` with the given arguments.
+    ///
+    /// # Returns
+    ///
+    /// The standard output of the command on success, or a `FoundationError` if the command could
+    /// not run or exited with a non-zero status.
+    fn run(&self, arguments: Vec<String>) -> Result<String, FoundationError> {
+        let output = Shell::execute_command(&self.nmcli.to_string_lossy(), arguments.clone())?;
+        if !output.status.success() {
+            return Err(FoundationError::OperationFailed(format!(
+                "nmcli {} failed: {}",
+                arguments.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Get the connection profiles known to NetworkManager.
+    fn connections(&self) -> Result<Vec<NMCLIConnection>, FoundationError> {
+        let output = self.run(vec![
+            "-t".to_string(),
+            "-f".to_string(),
+            "NAME,TYPE,DEVICE".to_string(),
+            "connection".to_string(),
+            "show".to_string(),
+        ])?;
+        Ok(parse_connection_list(&output))
+    }
+
+    /// Get the details of a connection profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection` - The connection profile.
+    /// * `fields` - The comma separated fields to request from `nmcli`.
+    ///
+    /// # Returns
+    ///
+    /// The details returned by `parse_connection_details` or a `FoundationError` if `nmcli`
+    /// failed.
+    fn connection_details(
+        &self,
+        connection: &NMCLIConnection,
+        fields: &str,
+    ) -> Result<HashMap<String, String>, FoundationError> {
+        let output = self.run(vec![
+            "-t".to_string(),
+            "-s".to_string(),
+            "-f".to_string(),
+            fields.to_string(),
+            "connection".to_string(),
+            "show".to_string(),
+            connection.name.clone(),
+        ])?;
+        Ok(parse_connection_details(&output))
+    }
+
+    /// Set the Wi-Fi password of a connection profile.
+    ///
+    /// The password is passed to the `nmcli` connection editor on stdin so that it does not
+    /// appear on the command line, where any user could read it in the process list.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The name of the connection profile.
+    /// * `password` - The password.
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) on success or a FoundationError if the password could not be set.
+    fn set_password(&self, profile: &str, password: &str) -> Result<(), FoundationError> {
+        // Each line on stdin is an editor command, so a line break in the password would let it
+        // run other commands.
+        if password.contains(['\n', '\r']) {
+            return Err(FoundationError::OperationFailed(format!(
+                "The Wi-Fi password for {} contains a line break",
+                profile
+            )));
+        }
+
+        let output = ShellCommand::new(&self.nmcli.to_string_lossy())
+            .args(["connection", "edit", "id", profile])
+            .stdin(format!(
+                "set 802-11-wireless-security.psk {}\nsave persistent\nquit\n",
+                password
+            ))
+            .run()?;
+        if output.status != 0 {
+            return Err(FoundationError::OperationFailed(format!(
+                "Failed to set the Wi-Fi password for {}: {}",
+                profile,
+                output.stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Split a line of `nmcli -t` output into its fields. In terse mode `nmcli` separates fields with
+/// `:` and escapes literal colons and backslashes in values with a backslash.
+///
+/// # Arguments
+///
+/// * `line` - The line to split.
+/// * `max_fields` - The maximum number of fields to return. The final field holds the rest of the
+///   line.
+fn split_terse_fields(line: &str, max_fields: usize) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    field.push(escaped);
+                }
+            }
+            ':' if fields.len() + 1 < max_fields => {
+                fields.push(std::mem::take(&mut field));
+            }
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parse the output of `nmcli -t -f NAME,TYPE,DEVICE connection show`.
+fn parse_connection_list(output: &str) -> Vec<NMCLIConnection> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let fields = split_terse_fields(line, 3);
+            if fields.len() != 3 {
+                debug!("Skipping malformed nmcli connection line: {}", line);
+                return None;
+            }
+            Some(NMCLIConnection {
+                name: fields[0].clone(),
+                connection_type: fields[1].clone(),
+                device: fields[2].clone(),
+            })
+        })
+        .collect()
+}
+
+/// Parse the output of `nmcli -t -f <fields> connection show <name>` into a map of field names to
+/// values. Fields without a value are left out of the map.
+fn parse_connection_details(output: &str) -> HashMap<String, String> {
+    let mut details = HashMap::new();
+    for line in output.lines() {
+        let fields = split_terse_fields(line, 2);
+        if fields.len() != 2 {
+            continue;
+        }
+        let value = fields[1].trim();
+        if value.is_empty() || value == "--" {
+            continue;
+        }
+        details.insert(fields[0].clone(), value.to_string());
+    }
+    details
+}
+
+/// Find the interface a connection profile is bound to. A profile that does not name an
+/// interface is bound to the device it is active on, if any.
+///
+/// # Arguments
+///
+/// * `connection` - The connection profile.
+/// * `details` - The connection details returned by `parse_connection_details`.
+fn bound_interface(
+    connection: &NMCLIConnection,
+    details: &HashMap<String, String>,
+) -> Option<String> {
+    match details.get(INTERFACE_NAME_FIELD) {
+        Some(interface_name) => Some(interface_name.clone()),
+        None if !connection.device.is_empty() => Some(connection.device.clone()),
+        None => None,
+    }
+}
+
+/// Find the connection profile of a given type that is bound to an interface.
+///
+/// # Arguments
+///
+/// * `profiles` - The connection profiles and the interfaces they are bound to.
+/// * `connection_type` - The type of connection profile to find.
+/// * `interface_name` - The name of the interface.
+fn find_profile<'a>(
+    profiles: &'a [(NMCLIConnection, Option<String>)],
+    connection_type: &str,
+    interface_name: &str,
+) -> Option<&'a NMCLIConnection> {
+    profiles
+        .iter()
+        .find(|(connection, bound)| {
+            connection.connection_type == connection_type
+                && bound.as_deref() == Some(interface_name)
+        })
+        .map(|(connection, _)| connection)
+}
+
+/// Update a network configuration from the details of a NetworkManager connection.
+///
+/// # Arguments
+///
+/// * `configuration` - The configuration to update.
+/// * `details` - The connection details returned by `parse_connection_details`.
+fn apply_connection_details(
+    configuration: &mut NetworkConfiguration,
+    details: &HashMap<String, String>,
+) -> Result<(), FoundationError> {
+    if let Some(method) = details.get("ipv4.method") {
+        match method.as_str() {
+            "auto" => configuration.address_mode = AddressMode::DHCP,
+            "manual" => configuration.address_mode = AddressMode::Static,
+            _ => {}
+        }
+    }
+
+    if let Some(addresses) = details.get("ipv4.addresses") {
+        for address in addresses.split(',').map(str::trim) {
+            let address = InterfaceAddr::try_from(address)?;
+            if !configuration.interface.addresses.contains(&address) {
+                configuration.interface.addresses.push(address);
+            }
+        }
+    }
+
+    if let Some(nameservers) = details.get("ipv4.dns") {
+        for nameserver in nameservers.split(',').map(str::trim) {
+            let nameserver = <IpAddr as IpAddrQuery>::from(nameserver)?;
+            if !configuration
+                .interface
+                .nameserver_addresses
+                .contains(&nameserver)
+            {
+                configuration
+                    .interface
+                    .nameserver_addresses
+                    .push(nameserver);
+            }
+        }
+    }
+
+    if let Some(gateway) = details.get("ipv4.gateway") {
+        let gateway = <IpAddr as IpAddrQuery>::from(gateway)?;
+        if !configuration.interface.gateway_addresses.contains(&gateway) {
+            configuration.interface.gateway_addresses.push(gateway);
+        }
+    }
+
+    if let Some(ssid) = details.get("802-11-wireless.ssid") {
+        let wireless_config = configuration
+            .wifi_configuration
+            .get_or_insert_with(WirelessConfiguration::default);
+        wireless_config.ssid = ssid.clone();
+        wireless_config.mode = match details.get("802-11-wireless.mode").map(String::as_str) {
+            Some("ap") => WirelessMode::AccessPoint,
+            _ => WirelessMode::Client,
+        };
+        if let Some(psk) = details.get("802-11-wireless-security.psk") {
+            wireless_config.password = Some(psk.clone());
+        }
+    }
+
+    configuration.enabled = details
+        .get("connection.autoconnect")
+        .is_none_or(|autoconnect| autoconnect == "yes");
+
+    Ok(())
+}
+
+/// Build the `nmcli connection add/modify` property arguments for a network configuration. The
+/// Wi-Fi password is left out, see `NMCLIService::set_password`.
+///
+/// # Arguments
+///
+/// * `configuration` - The configuration to convert to `nmcli` properties.
+///
+/// # Returns
+///
+/// A vector of alternating property names and values, or a `FoundationError` if the
+/// configuration is static but has no IPv4 addresses.
+fn connection_properties(
+    configuration: &NetworkConfiguration,
+) -> Result<Vec<String>, FoundationError> {
+    let mut properties = vec![
+        "connection.autoconnect".to_string(),
+        if configuration.enabled { "yes" } else { "no" }.to_string(),
+    ];
+
+    let join = |addresses: Vec<String>| addresses.join(",");
+
+    if configuration.address_mode == AddressMode::DHCP {
+        properties.extend([
+            "ipv4.method".to_string(),
+            "auto".to_string(),
+            "ipv4.addresses".to_string(),
+            String::new(),
+            "ipv4.gateway".to_string(),
+            String::new(),
+        ]);
+    } else {
+        let addresses: Vec<String> = configuration
+            .interface
+            .addresses
+            .iter()
+            .filter(|address| address.ip.is_ipv4())
+            .filter_map(|address| address.get_in_cidr_notation())
+            .collect();
+        if addresses.is_empty() {
+            return Err(FoundationError::OperationFailed(format!(
+                "The static configuration for {} has no IPv4 addresses",
+                configuration.interface.name
+            )));
+        }
+
+        properties.extend([
+            "ipv4.method".to_string(),
+            "manual".to_string(),
+            "ipv4.addresses".to_string(),
+            join(addresses),
+            "ipv4.gateway".to_string(),
+            configuration
+                .interface
+                .gateway_addresses
+                .iter()
+                .find(|address| address.is_ipv4())
+                .map(|address| address.to_string())
+                .unwrap_or_default(),
+        ]);
+    }
+
+    properties.extend([
+        "ipv4.dns".to_string(),
+        join(
+            configuration
+                .interface
+                .nameserver_addresses
+                .iter()
+                .filter(|address| address.is_ipv4())
+                .map(|address| address.to_string())
+                .collect(),
+        ),
+    ]);
+
+    if let Some(wifi_config) = &configuration.wifi_configuration {
+        properties.extend([
+            "802-11-wireless.ssid".to_string(),
+            wifi_config.ssid.clone(),
+            "802-11-wireless.mode".to_string(),
+            match wifi_config.mode {
+                WirelessMode::Client => "infrastructure".to_string(),
+                WirelessMode::AccessPoint => "ap".to_string(),
+            },
+        ]);
+        if wifi_config.password.is_some() {
+            properties.extend([
+                "802-11-wireless-security.key-mgmt".to_string(),
+                "wpa-psk".to_string(),
+            ]);
+        }
+    }
+
+    Ok(properties)
+}
+
+impl NetworkService for NMCLIService {
+    /// Load the network configurations from the NetworkManager ethernet and wireless connection
+    /// profiles. Insert a new configuration in the configuration map or update the existing
+    /// configuration if the map already has an entry for a given network interface. A profile
+    /// that cannot be read is logged and skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `config_map` - A map of configuration names to network configuration objects.
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) on success or a FoundationError if the connection profiles cannot be listed.
+    fn load_configuration(
+        &mut self,
+        config_map: &mut HashMap<String, NetworkConfiguration>,
+    ) -> Result<(), FoundationError> {
+        for connection in self.connections()? {
+            let fields = match connection.connection_type.as_str() {
+                ETHERNET_TYPE => ETHERNET_FIELDS,
+                WIRELESS_TYPE => WIRELESS_FIELDS,
+                _ => continue,
+            };
+
+            let details = match self.connection_details(&connection, fields) {
+                Ok(details) => details,
+                Err(e) => {
+                    error!("Failed to read connection {}: {}", connection.name, e);
+                    continue;
+                }
+            };
+
+            let interface_name = match bound_interface(&connection, &details) {
+                Some(interface_name) => interface_name,
+                None => {
+                    debug!(
+                        "Skipping connection {} that is not bound to an interface",
+                        connection.name
+                    );
+                    continue;
+                }
+            };
+
+            // Update a copy so that a profile that fails part way through leaves the map alone.
+            let mut configuration = config_map
+                .get(&interface_name)
+                .cloned()
+                .unwrap_or_else(|| NetworkConfiguration::new_with_name(&interface_name));
+            if let Err(e) = apply_connection_details(&mut configuration, &details) {
+                error!("Failed to load connection {}: {}", connection.name, e);
+                continue;
+            }
+            config_map.insert(interface_name, configuration);
+        }
+
+        Ok(())
+    }
+
+    /// Write a set of network configuration settings to NetworkManager. Existing connection
+    /// profiles bound to an interface are modified, otherwise a new profile named after the
+    /// interface is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `configurations` - A map of interface names to network configurations.
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) on success or a FoundationError if a problem occurs.
+    fn write_configuration(
+        &self,
+        configurations: &HashMap<String, NetworkConfiguration>,
+    ) -> Result<(), FoundationError> {
+        // Find the interface each ethernet and wireless profile is bound to. Inactive profiles
+        // have no device, so the interface comes from the profile itself.
+        let profiles: Vec<(NMCLIConnection, Option<String>)> = self
+            .connections()?
+            .into_iter()
+            .filter(|connection| {
+                connection.connection_type == ETHERNET_TYPE
+                    || connection.connection_type == WIRELESS_TYPE
+            })
+            .map(|connection| {
+                let details = self
+                    .connection_details(&connection, INTERFACE_NAME_FIELD)
+                    .unwrap_or_else(|e| {
+                        error!("Failed to read connection {}: {}", connection.name, e);
+                        HashMap::new()
+                    });
+                let bound = bound_interface(&connection, &details);
+                (connection, bound)
+            })
+            .collect();
+
+        for (name, configuration) in configurations {
+            if configuration.interface.is_loopback_interface() {
+                continue;
+            }
+
+            let connection_type = if configuration.wifi_configuration.is_some() {
+                WIRELESS_TYPE
+            } else {
+                ETHERNET_TYPE
+            };
+
+            let existing = find_profile(&profiles, connection_type, name);
+
+            let mut arguments = vec!["connection".to_string()];
+            let profile = match existing {
+                Some(connection) => {
+                    arguments.push("modify".to_string());
+                    arguments.push(connection.name.clone());
+                    connection.name.clone()
+                }
+                None => {
+                    arguments.extend([
+                        "add".to_string(),
+                        "type".to_string(),
+                        connection_type.to_string(),
+                        "con-name".to_string(),
+                        name.clone(),
+                        "ifname".to_string(),
+                        name.clone(),
+                    ]);
+                    name.clone()
+                }
+            };
+            arguments.extend(connection_properties(configuration)?);
+
+            self.run(arguments)?;
+
+            if let Some(password) = configuration
+                .wifi_configuration
+                .as_ref()
+                .and_then(|wifi_config| wifi_config.password.as_ref())
+            {
+                self.set_password(&profile, password)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the path to the directory that holds the NetworkManager connection profiles.
+    fn get_configuration_file(&self) -> PathBuf {
+        PathBuf::from(SYSTEM_CONNECTIONS_DIR)
+    }
+
+    /// NetworkManager keeps one profile per connection, so there is no single configuration file
+    /// to remove.
+    fn remove_config_file(&self) -> Result<(), FoundationError> {
+        Err(FoundationError::OperationFailed(
+            "NetworkManager does not use a single configuration file".to_string(),
+        ))
+    }
+
+    fn start(&self) -> Result<(), FoundationError> {
+        self.service.start()
+    }
+
+    fn stop(&self) -> Result<(), FoundationError> {
+        self.service.stop()
+    }
+
+    fn restart(&self) -> Result<(), FoundationError> {
+        self.service.restart()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::networkinterface::NetworkInterface;
+    use std::net::Ipv4Addr;
+
+    const CONNECTION_LIST: &str = "Wired connection 1:802-3-ethernet:eth0
+HomeWifi:802-11-wireless:wlan0
+lo:loopback:lo
+Office\\: Desk:802-3-ethernet:
+";
+
+    const ETHERNET_DETAILS: &str = "connection.interface-name:eth0
+connection.autoconnect:yes
+ipv4.method:manual
+ipv4.addresses:192.168.1.2/24,10.0.0.2/8
+ipv4.dns:8.8.8.8,1.1.1.1
+ipv4.gateway:192.168.1.1
+";
+
+    const WIRELESS_DETAILS: &str = "connection.interface-name:wlan0
+connection.autoconnect:no
+ipv4.method:auto
+ipv4.addresses:
+ipv4.dns:
+ipv4.gateway:--
+802-11-wireless.ssid:Peanut\\:Butter
+802-11-wireless.mode:infrastructure
+802-11-wireless-security.psk:Jelly Time
+";
+
+    #[test]
+    fn test_split_terse_fields() {
+        assert_eq!(
+            split_terse_fields("a:b:c", 3),
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+        assert_eq!(
+            split_terse_fields("ipv6.addresses:fe80\\:\\:1/64", 2),
+            vec!["ipv6.addresses".to_string(), "fe80::1/64".to_string()]
+        );
+        assert_eq!(
+            split_terse_fields("a:b:c", 2),
+            vec!["a".to_string(), "b:c".to_string()]
+        );
+        assert_eq!(
+            split_terse_fields("back\\\\slash:", 2),
+            vec!["back\\slash".to_string(), String::new()]
+        );
+    }
+
+    #[test]
+    fn test_parse_connection_list() {
+        let connections = parse_connection_list(CONNECTION_LIST);
+        assert_eq!(connections.len(), 4);
+        assert_eq!(
+            connections[0],
+            NMCLIConnection {
+                name: "Wired connection 1".to_string(),
+                connection_type: ETHERNET_TYPE.to_string(),
+                device: "eth0".to_string(),
+            }
+        );
+        assert_eq!(connections[1].connection_type, WIRELESS_TYPE);
+        assert_eq!(connections[3].name, "Office: Desk");
+        assert_eq!(connections[3].device, "");
+    }
+
+    #[test]
+    fn test_ethernet_connection_details() {
+        let details = parse_connection_details(ETHERNET_DETAILS);
+        let mut configuration = NetworkConfiguration::new_with_name("eth0");
+        apply_connection_details(&mut configuration, &details).unwrap();
+
+        let mut interface = NetworkInterface::new_with_name("eth0");
+        interface
+            .addresses
+            .push(InterfaceAddr::from_cidr("192.168.1.2/24").unwrap());
+        interface
+            .addresses
+            .push(InterfaceAddr::from_cidr("10.0.0.2/8").unwrap());
+        interface
+            .nameserver_addresses
+            .push(IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)));
+        interface
+            .nameserver_addresses
+            .push(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)));
+        interface
+            .gateway_addresses
+            .push(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)));
+        let expected = NetworkConfiguration::new(AddressMode::Static, interface, true, None, None);
+
+        assert_eq!(configuration, expected);
+
+        // Applying the same details again does not duplicate addresses.
+        apply_connection_details(&mut configuration, &details).unwrap();
+        assert_eq!(configuration, expected);
+    }
+
+    #[test]
+    fn test_wireless_connection_details() {
+        let details = parse_connection_details(WIRELESS_DETAILS);
+        assert!(!details.contains_key("ipv4.addresses"));
+        assert!(!details.contains_key("ipv4.gateway"));
+
+        let mut configuration = NetworkConfiguration::new_with_name("wlan0");
+        configuration.enabled = true;
+        apply_connection_details(&mut configuration, &details).unwrap();
+
+        assert_eq!(configuration.address_mode, AddressMode::DHCP);
+        assert!(!configuration.enabled);
+        assert!(configuration.interface.addresses.is_empty());
+        let wifi_config = configuration.wifi_configuration.unwrap();
+        assert_eq!(wifi_config.ssid, "Peanut:Butter");
+        assert_eq!(wifi_config.mode, WirelessMode::Client);
+        assert_eq!(wifi_config.password, Some("Jelly Time".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_connection_details() {
+        let details = parse_connection_details("ipv4.addresses:192.168.1/24\n");
+        let mut configuration = NetworkConfiguration::new_with_name("eth0");
+        assert!(apply_connection_details(&mut configuration, &details).is_err());
+    }
+
+    #[test]
+    fn test_connection_properties() {
+        let details = parse_connection_details(ETHERNET_DETAILS);
+        let mut configuration = NetworkConfiguration::new_with_name("eth0");
+        apply_connection_details(&mut configuration, &details).unwrap();
+
+        let properties = connection_properties(&configuration).unwrap();
+        let properties = properties
+            .chunks(2)
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+            .collect::<HashMap<&str, &str>>();
+        assert_eq!(properties["connection.autoconnect"], "yes");
+        assert_eq!(properties["ipv4.method"], "manual");
+        assert_eq!(properties["ipv4.addresses"], "192.168.1.2/24,10.0.0.2/8");
+        assert_eq!(properties["ipv4.dns"], "8.8.8.8,1.1.1.1");
+        assert_eq!(properties["ipv4.gateway"], "192.168.1.1");
+        assert!(!properties.contains_key("802-11-wireless.ssid"));
+
+        let details = parse_connection_details(WIRELESS_DETAILS);
+        let mut configuration = NetworkConfiguration::new_with_name("wlan0");
+        apply_connection_details(&mut configuration, &details).unwrap();
+
+        let properties = connection_properties(&configuration).unwrap();
+        let properties = properties
+            .chunks(2)
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+            .collect::<HashMap<&str, &str>>();
+        assert_eq!(properties["connection.autoconnect"], "no");
+        assert_eq!(properties["ipv4.method"], "auto");
+        assert_eq!(properties["ipv4.addresses"], "");
+        assert_eq!(properties["802-11-wireless.ssid"], "Peanut:Butter");
+        assert_eq!(properties["802-11-wireless.mode"], "infrastructure");
+        assert_eq!(properties["802-11-wireless-security.key-mgmt"], "wpa-psk");
+
+        // The password never goes on the command line.
+        assert!(!properties.contains_key("802-11-wireless-security.psk"));
+
+        // A static configuration needs an address.
+        let mut configuration = NetworkConfiguration::new_with_name("eth1");
+        configuration.address_mode = AddressMode::Static;
+        assert!(connection_properties(&configuration).is_err());
+    }
+
+    #[test]
+    fn test_find_profile() {
+        let connections = parse_connection_list(CONNECTION_LIST);
+        let profiles: Vec<(NMCLIConnection, Option<String>)> = connections
+            .into_iter()
+            .map(|connection| {
+                // The inactive "Office: Desk" profile is bound to eth1 but has no device.
+                let details = if connection.name == "Office: Desk" {
+                    parse_connection_details("connection.interface-name:eth1\n")
+                } else {
+                    HashMap::new()
+                };
+                let bound = bound_interface(&connection, &details);
+                (connection, bound)
+            })
+            .collect();
+
+        assert_eq!(
+            find_profile(&profiles, ETHERNET_TYPE, "eth0").map(|c| c.name.as_str()),
+            Some("Wired connection 1")
+        );
+        assert_eq!(
+            find_profile(&profiles, ETHERNET_TYPE, "eth1").map(|c| c.name.as_str()),
+            Some("Office: Desk")
+        );
+        assert!(find_profile(&profiles, ETHERNET_TYPE, "wlan0").is_none());
+        assert_eq!(
+            find_profile(&profiles, WIRELESS_TYPE, "wlan0").map(|c| c.name.as_str()),
+            Some("HomeWifi")
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! The `route` module provides a structure to represent a static route for a network interface.
+
+use crate::network::interfaceaddr::InterfaceAddr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// The `Route` struct represents a static route for a network interface.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Route {
+    /// The destination network of the route. A prefix length of zero is the default route.
+    pub to: InterfaceAddr,
+
+    /// The gateway that traffic for the destination is sent through.
+    pub via: IpAddr,
+
+    /// The metric of the route if configured. Lower metrics are preferred.
+    pub metric: Option<u32>,
+}
+
+impl Route {
+    /// Create a new `Route` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `to` - The destination network of the route.
+    /// * `via` - The gateway that traffic for the destination is sent through.
+    /// * `metric` - The metric of the route if configured.
+    pub fn new(to: InterfaceAddr, via: IpAddr, metric: Option<u32>) -> Self {
+        Route { to, via, metric }
+    }
+
+    /// Create a default route through a gateway. The destination is `0.0.0.0/0` or `::/0`
+    /// depending on the IP version of the gateway.
+    ///
+    /// # Arguments
+    ///
+    /// * `via` - The gateway that traffic is sent through.
+    /// * `metric` - The metric of the route if configured.
+    pub fn default_route(via: IpAddr, metric: Option<u32>) -> Self {
+        let unspecified = match via {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        // A zero length prefix is always valid, so from_cidr cannot fail here.
+        let to = InterfaceAddr::from_cidr(&format!("{}/0", unspecified)).unwrap();
+        Route::new(to, via, metric)
+    }
+
+    /// Check if the route is a default route.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the destination of the route has a prefix length of zero, `false` otherwise.
+    pub fn is_default(&self) -> bool {
+        self.to
+            .get_in_cidr_notation()
+            .is_some_and(|cidr| cidr.ends_with("/0"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_route() {
+        let route = Route::default_route(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)), None);
+        assert!(route.is_default());
+        assert_eq!(
+            route.to.get_in_cidr_notation(),
+            Some("0.0.0.0/0".to_string())
+        );
+
+        let route = Route::default_route("fe80::1".parse().unwrap(), Some(100));
+        assert!(route.is_default());
+        assert_eq!(route.to.get_in_cidr_notation(), Some("::/0".to_string()));
+        assert_eq!(route.metric, Some(100));
+
+        let route = Route::new(
+            InterfaceAddr::from_cidr("10.0.0.0/8").unwrap(),
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 254)),
+            None,
+        );
+        assert!(!route.is_default());
+    }
+}
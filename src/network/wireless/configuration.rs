@@ -1,11 +1,14 @@
 //! The `configuration` module contains the `WirelessConfiguration` struct and its associated enums.
 
 use crate::error::FoundationError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::str::FromStr;
 
 /// The `WirelessStandard` enum represents the wireless standards used by a wireless network.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WirelessStandard {
     A,
     B,
@@ -15,13 +18,46 @@ pub enum WirelessStandard {
 
 /// The `WirelessMode` enum represents the wireless modes used by a wireless network.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WirelessMode {
     Client,
     AccessPoint,
 }
 
+/// The `SecurityProtocol` enum represents the security protocols an access point can use.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SecurityProtocol {
+    /// No authentication or encryption.
+    Open,
+
+    /// WPA2 with a pre-shared key.
+    WPA2Personal,
+
+    /// WPA3 with simultaneous authentication of equals (SAE) and required management frame
+    /// protection.
+    WPA3Personal,
+
+    /// WPA2 and WPA3 transition mode so that WPA2 only clients can still connect.
+    WPA2WPA3Mixed,
+}
+
 /// The `WirelessConfiguration` struct represents the configuration of a wireless network.
+///
+/// Set specific values by overriding the defaults with struct update syntax.
+///
+/// ```rust
+/// use foundation::network::wireless::configuration::{WirelessConfiguration, WirelessMode};
+/// let config = WirelessConfiguration {
+///     ssid: "BadgerDen".to_string(),
+///     mode: WirelessMode::AccessPoint,
+///     password: Some("Honey Comb".to_string()),
+///     ..Default::default()
+/// };
+/// assert_eq!(config.channel, 1);
+/// ```
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct WirelessConfiguration {
     /// The SSID of the wireless network.
     pub ssid: String,
@@ -49,9 +85,17 @@ pub struct WirelessConfiguration {
 
     /// The RSN pairwise setting of the wireless network.
     pub rsn_pairwise: Option<String>,
+
+    /// The ISO 3166-1 country code that sets the regulatory domain of the wireless network.
+    pub country_code: Option<String>,
+
+    /// The security protocol of the wireless network. When set, the protocol decides the WPA
+    /// settings and the `wpa_mode`, `wpa_key_mgmt`, `wpa_pairwise` and `rsn_pairwise` fields are
+    /// not used.
+    pub security_protocol: Option<SecurityProtocol>,
 }
 
-impl WirelessConfiguration {
+impl Default for WirelessConfiguration {
     /// Returns a new `WirelessConfiguration` instance with default values.
     ///
     /// # Returns
@@ -66,7 +110,9 @@ impl WirelessConfiguration {
     /// wpa_key_mgmt - None.
     /// wpa_pairwise - None.
     /// rsn_pairwise - None.
-    pub fn default() -> WirelessConfiguration {
+    /// country_code - None.
+    /// security_protocol - None.
+    fn default() -> WirelessConfiguration {
         WirelessConfiguration {
             ssid: String::new(),
             standard: WirelessStandard::N,
@@ -77,46 +123,13 @@ impl WirelessConfiguration {
             wpa_key_mgmt: None,
             wpa_pairwise: None,
             rsn_pairwise: None,
+            country_code: None,
+            security_protocol: None,
         }
     }
+}
 
-    /// Returns a new `WirelessConfiguration` instance with the specified values.
-    ///
-    /// # Arguments
-    ///
-    /// * `ssid` - The SSID of the wireless network.
-    /// * `standard` - The wireless standard used by the wireless network.
-    /// * `mode` - The wireless mode used by the wireless network.
-    /// * `password` - The password of the wireless network.
-    /// * `channel` - The channel of the wireless network.
-    /// * `wpa_mode` - The WPA mode of the wireless network.
-    /// * `wpa_key_mgmt` - The WPA key management setting of the wireless network.
-    /// * `wpa_pairwise` - The WPA pairwise setting of the wireless network.
-    /// * `rsn_pairwise` - The RSN pairwise setting of the wireless network.
-    pub fn new(
-        ssid: String,
-        standard: WirelessStandard,
-        mode: WirelessMode,
-        password: Option<String>,
-        channel: u32,
-        wpa_mode: u32,
-        wpa_key_mgmt: Option<String>,
-        wpa_pairwise: Option<String>,
-        rsn_pairwise: Option<String>,
-    ) -> Self {
-        WirelessConfiguration {
-            ssid,
-            standard,
-            mode,
-            password,
-            channel,
-            wpa_mode,
-            wpa_key_mgmt,
-            wpa_pairwise,
-            rsn_pairwise,
-        }
-    }
-
+impl WirelessConfiguration {
     /// Clear the current settings from the configuration and restore to default values.
     pub fn clear(&mut self) {
         *self = WirelessConfiguration::default();
@@ -168,3 +181,31 @@ impl FromStr for WirelessMode {
         }
     }
 }
+
+impl Display for SecurityProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecurityProtocol::Open => write!(f, "open"),
+            SecurityProtocol::WPA2Personal => write!(f, "wpa2_personal"),
+            SecurityProtocol::WPA3Personal => write!(f, "wpa3_personal"),
+            SecurityProtocol::WPA2WPA3Mixed => write!(f, "wpa2_wpa3_mixed"),
+        }
+    }
+}
+
+impl FromStr for SecurityProtocol {
+    type Err = FoundationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(SecurityProtocol::Open),
+            "wpa2_personal" => Ok(SecurityProtocol::WPA2Personal),
+            "wpa3_personal" => Ok(SecurityProtocol::WPA3Personal),
+            "wpa2_wpa3_mixed" => Ok(SecurityProtocol::WPA2WPA3Mixed),
+            _ => Err(FoundationError::InvalidConversion(
+                s.to_string(),
+                "SecurityProtocol",
+            )),
+        }
+    }
+}
@@ -2,9 +2,37 @@
 //! specific to Linux.
 
 use crate::error::FoundationError;
+use crate::network::macaddress::MacAddress;
+use crate::network::wireless::configuration::SecurityProtocol;
+use crate::shell::Shell;
 use futures::TryStreamExt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use wl_nl80211::{new_connection, Nl80211Attr};
 
+const IW_COMMAND: &str = "/usr/sbin/iw";
+
+/// The `ScanResult` struct describes an access point found by a WiFi scan.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanResult {
+    /// The SSID of the access point. Hidden networks have an empty SSID.
+    pub ssid: String,
+
+    /// The BSSID, the MAC address of the access point's radio.
+    pub bssid: MacAddress,
+
+    /// The signal strength of the access point in dBm.
+    pub signal_dbm: f32,
+
+    /// The channel the access point is using.
+    pub channel: u32,
+
+    /// The security protocol of the access point. `None` if the access point uses a protocol
+    /// that `SecurityProtocol` does not describe, such as WEP, WPA, or WPA2 Enterprise.
+    pub security: Option<SecurityProtocol>,
+}
+
 /// Check if the given interface is a wireless interface using the Netlink socket protocol.
 ///
 /// # Arguments
@@ -53,6 +81,151 @@ pub async fn is_wireless_interface(name: &str) -> bool {
     false
 }
 
+/// Scan for the access points visible to a wireless interface with `iw dev <interface> scan`.
+/// Scanning usually needs root privileges.
+///
+/// # Arguments
+///
+/// * `interface` - The name of the wireless interface to scan with.
+///
+/// # Returns
+///
+/// A Result containing the access points found by the scan, or a `FoundationError` if the scan
+/// could not run.
+pub fn scan(interface: &str) -> Result<Vec<ScanResult>, FoundationError> {
+    let arguments = vec!["dev".to_string(), interface.to_string(), "scan".to_string()];
+    let output = Shell::execute_command(IW_COMMAND, arguments)?;
+    if !output.status.success() {
+        return Err(FoundationError::OperationFailed(format!(
+            "{} dev {} scan failed: {}",
+            IW_COMMAND,
+            interface,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(parse_iw_scan(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the output of `iw dev <interface> scan`. Access points without a BSSID, signal, or
+/// frequency are skipped.
+///
+/// # Arguments
+///
+/// * `output` - The output of `iw dev <interface> scan`.
+///
+/// # Returns
+///
+/// The access points in the order they appear in the output.
+fn parse_iw_scan(output: &str) -> Vec<ScanResult> {
+    let mut results = vec![];
+    let mut lines = output.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        // Each access point starts with a `BSS <bssid>(on <interface>)` line.
+        let Some(bssid) = line.strip_prefix("BSS ") else {
+            continue;
+        };
+        let bssid = bssid
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .parse::<MacAddress>();
+
+        let mut ssid = String::new();
+        let mut signal_dbm = None;
+        let mut channel = None;
+        let mut privacy = false;
+        let mut rsn_suites = None;
+        let mut has_wpa = false;
+        let mut section = "";
+
+        while let Some(line) = lines.next_if(|line| !line.starts_with("BSS ")) {
+            let line = line.trim();
+            if line.starts_with('*') {
+                if let Some(suites) = line.strip_prefix("* Authentication suites:") {
+                    if section == "RSN" {
+                        rsn_suites = Some(suites.split_whitespace().collect::<Vec<_>>());
+                    }
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            section = key;
+            let value = value.trim();
+            match key {
+                "SSID" => ssid = value.to_string(),
+                "signal" => signal_dbm = value.trim_end_matches("dBm").trim().parse::<f32>().ok(),
+                "freq" => {
+                    channel = value
+                        .parse::<f32>()
+                        .ok()
+                        .and_then(|freq| channel_from_frequency(freq as u32))
+                }
+                "capability" => privacy = value.split_whitespace().any(|c| c == "Privacy"),
+                "RSN" => {
+                    // The first RSN item shares the line with the section name.
+                    if let Some(suites) = value.strip_prefix("* Authentication suites:") {
+                        rsn_suites = Some(suites.split_whitespace().collect::<Vec<_>>());
+                    }
+                }
+                "WPA" => has_wpa = true,
+                _ => {}
+            }
+        }
+
+        let security = match rsn_suites {
+            Some(suites) => {
+                let psk = suites.contains(&"PSK");
+                let sae = suites.contains(&"SAE");
+                match (psk, sae) {
+                    (true, true) => Some(SecurityProtocol::WPA2WPA3Mixed),
+                    (true, false) => Some(SecurityProtocol::WPA2Personal),
+                    (false, true) => Some(SecurityProtocol::WPA3Personal),
+                    (false, false) => None,
+                }
+            }
+            None if !privacy && !has_wpa => Some(SecurityProtocol::Open),
+            None => None,
+        };
+
+        if let (Ok(bssid), Some(signal_dbm), Some(channel)) = (bssid, signal_dbm, channel) {
+            results.push(ScanResult {
+                ssid,
+                bssid,
+                signal_dbm,
+                channel,
+                security,
+            });
+        }
+    }
+
+    results
+}
+
+/// Get the WiFi channel number for a frequency.
+///
+/// # Arguments
+///
+/// * `freq` - The frequency in MHz.
+///
+/// # Returns
+///
+/// The channel number, or `None` if the frequency is not in the 2.4, 5, or 6 GHz bands.
+fn channel_from_frequency(freq: u32) -> Option<u32> {
+    match freq {
+        2484 => Some(14),
+        2412..=2472 => Some((freq - 2407) / 5),
+        5160..=5885 => Some((freq - 5000) / 5),
+        5955..=7115 => Some((freq - 5950) / 5),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,4 +246,65 @@ mod tests {
         let eth0_wireless = is_wireless_interface("eth0").await;
         assert!(!eth0_wireless);
     }
+
+    #[test]
+    fn test_parse_iw_scan() {
+        let output = "\
+BSS 00:11:22:33:44:55(on wlan0) -- associated
+\tTSF: 1234567890 usec (0d, 00:20:34)
+\tfreq: 2437
+\tbeacon interval: 100 TUs
+\tcapability: ESS Privacy ShortSlotTime (0x0411)
+\tsignal: -45.00 dBm
+\tlast seen: 10 ms ago
+\tSSID: HomeNet
+\tDS Parameter set: channel 6
+\tRSN:\t * Version: 1
+\t\t * Group cipher: CCMP
+\t\t * Pairwise ciphers: CCMP
+\t\t * Authentication suites: PSK SAE
+\t\t * Capabilities: 1-PTKSA-RC 1-GTKSA-RC (0x0000)
+BSS 66:77:88:99:aa:bb(on wlan0)
+\tfreq: 5180.0
+\tcapability: ESS ShortSlotTime (0x0401)
+\tsignal: -71.50 dBm
+\tSSID: Guest
+BSS 01:23:45:67:89:ab(on wlan0)
+\tfreq: 2462
+\tcapability: ESS Privacy (0x0011)
+\tsignal: -80.00 dBm
+\tSSID: Office
+\tWPA:\t * Version: 1
+\t\t * Authentication suites: PSK
+\tRSN:\t * Version: 1
+\t\t * Authentication suites: IEEE 802.1X
+";
+        let results = parse_iw_scan(output);
+        assert_eq!(
+            results,
+            vec![
+                ScanResult {
+                    ssid: "HomeNet".to_string(),
+                    bssid: "00:11:22:33:44:55".parse().unwrap(),
+                    signal_dbm: -45.0,
+                    channel: 6,
+                    security: Some(SecurityProtocol::WPA2WPA3Mixed),
+                },
+                ScanResult {
+                    ssid: "Guest".to_string(),
+                    bssid: "66:77:88:99:aa:bb".parse().unwrap(),
+                    signal_dbm: -71.5,
+                    channel: 36,
+                    security: Some(SecurityProtocol::Open),
+                },
+                ScanResult {
+                    ssid: "Office".to_string(),
+                    bssid: "01:23:45:67:89:ab".parse().unwrap(),
+                    signal_dbm: -80.0,
+                    channel: 11,
+                    security: None,
+                },
+            ]
+        );
+    }
 }
@@ -1,10 +1,11 @@
 //! The `partition` module contains the `PartitionTable` enum which represent partition
-//! table types of a disk.
+//! table types of a disk, and the `Partition` functions for finding the mounted filesystems.
 
 use crate::error::FoundationError;
 use crate::filesystem::FileSystem;
 use std::fmt;
 use std::fmt::Formatter;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 /// The `PartitionTable` enum represents the different types of partition tables that a disk can
@@ -76,6 +77,206 @@ impl TryFrom<FileSystem> for PartitionTable {
     }
 }
 
+/// The `MountInfo` struct describes a mounted filesystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountInfo {
+    /// The device or source of the filesystem, such as `/dev/sda1` or `tmpfs`.
+    pub device: String,
+
+    /// The directory where the filesystem is mounted.
+    pub mount_point: PathBuf,
+
+    /// The filesystem type, such as `ext4` or `apfs`.
+    pub filesystem_type: String,
+
+    /// The mount options, such as `rw` and `noexec`.
+    pub options: Vec<String>,
+}
+
+impl MountInfo {
+    /// Check whether the filesystem is mounted read-only.
+    ///
+    /// # Returns
+    ///
+    /// True if the mount options include `ro`, false otherwise. If the options list both `ro`
+    /// and `rw`, the last one wins, as it does for the mount command.
+    pub fn is_read_only(&self) -> bool {
+        self.options
+            .iter()
+            .rev()
+            .find(|option| *option == "ro" || *option == "rw")
+            .is_some_and(|option| option == "ro")
+    }
+
+    /// Get the type of the filesystem.
+    ///
+    /// # Returns
+    ///
+    /// The `FileSystem` for the mount, or `None` if the filesystem type is not one that
+    /// `FileSystem` represents, such as `proc` or `tmpfs`.
+    pub fn fs_type(&self) -> Option<FileSystem> {
+        FileSystem::from_str(&self.filesystem_type).ok()
+    }
+}
+
+/// The `Partition` struct provides functions for finding the mounted filesystems of the system.
+pub struct Partition {}
+
+impl Partition {
+    /// Get the filesystems currently mounted on the system, in mount order.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the list of mounts, otherwise a `FoundationError` is returned.
+    pub fn list_mounts() -> Result<Vec<MountInfo>, FoundationError> {
+        cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                let contents = std::fs::read_to_string("/proc/mounts")?;
+                Ok(parse_proc_mounts(&contents))
+            } else if #[cfg(target_os = "macos")] {
+                list_mounts_macos()
+            } else {
+                Err(FoundationError::OperationFailed(
+                    "Listing mounts is not supported on this platform".to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Find the mount that holds a path. The path does not need to exist, in which case the
+    /// mount that would hold it is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to look up.
+    ///
+    /// # Returns
+    ///
+    /// The mount with the longest mount point that contains the path, or `None` if the mounts
+    /// could not be read.
+    pub fn for_path(path: &Path) -> Option<MountInfo> {
+        let mounts = Partition::list_mounts().ok()?;
+
+        // Resolve symbolic links so that the path is compared with the real mount points.
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        // When filesystems are stacked on the same mount point, the last one mounted is the one
+        // that is visible, so later mounts win ties.
+        let mut best: Option<MountInfo> = None;
+        for mount in mounts {
+            if !path.starts_with(&mount.mount_point) {
+                continue;
+            }
+            let depth = mount.mount_point.components().count();
+            let deeper = match &best {
+                Some(best) => depth >= best.mount_point.components().count(),
+                None => true,
+            };
+            if deeper {
+                best = Some(mount);
+            }
+        }
+        best
+    }
+}
+
+/// Decode the octal escapes, such as `\040` for a space, that `/proc/mounts` uses for special
+/// characters in its fields.
+///
+/// # Arguments
+///
+/// * `field` - The field to decode.
+#[cfg(target_os = "linux")]
+fn decode_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'\\' && index + 4 <= bytes.len() {
+            let value = std::str::from_utf8(&bytes[index + 1..index + 4])
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+            if let Some(value) = value {
+                decoded.push(value);
+                index += 4;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Parse the contents of `/proc/mounts`.
+///
+/// # Arguments
+///
+/// * `contents` - The contents of the mounts file.
+///
+/// # Returns
+///
+/// The mounts listed in the file. Lines that do not have the expected fields are skipped.
+#[cfg(target_os = "linux")]
+fn parse_proc_mounts(contents: &str) -> Vec<MountInfo> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            let mount_point = fields.next()?;
+            let filesystem_type = fields.next()?;
+            let options = fields.next().unwrap_or_default();
+            Some(MountInfo {
+                device: decode_mount_field(device),
+                mount_point: PathBuf::from(decode_mount_field(mount_point)),
+                filesystem_type: decode_mount_field(filesystem_type),
+                options: options
+                    .split(',')
+                    .filter(|option| !option.is_empty())
+                    .map(decode_mount_field)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Get the mounted filesystems with `getmntinfo`.
+#[cfg(target_os = "macos")]
+fn list_mounts_macos() -> Result<Vec<MountInfo>, FoundationError> {
+    let mut buffer: *mut libc::statfs = std::ptr::null_mut();
+
+    // SAFETY: getmntinfo points the buffer at an array of `count` statfs structures that it
+    // owns, which stays valid until the next call to getmntinfo on this thread.
+    let count = unsafe { libc::getmntinfo(&mut buffer, libc::MNT_NOWAIT) };
+    if count <= 0 || buffer.is_null() {
+        return Err(FoundationError::IO(std::io::Error::last_os_error()));
+    }
+    let entries = unsafe { std::slice::from_raw_parts(buffer, count as usize) };
+
+    let to_string = |chars: &[libc::c_char]| {
+        // SAFETY: the statfs name fields are null terminated C strings.
+        unsafe { std::ffi::CStr::from_ptr(chars.as_ptr()) }
+            .to_string_lossy()
+            .to_string()
+    };
+
+    Ok(entries
+        .iter()
+        .map(|entry| MountInfo {
+            device: to_string(&entry.f_mntfromname),
+            mount_point: PathBuf::from(to_string(&entry.f_mntonname)),
+            filesystem_type: to_string(&entry.f_fstypename),
+            // getmntinfo reports the options as flags, so only the read-only state is listed.
+            options: if entry.f_flags & libc::MNT_RDONLY as u32 != 0 {
+                vec!["ro".to_string()]
+            } else {
+                vec!["rw".to_string()]
+            },
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,4 +326,50 @@ mod tests {
         let unknown = PartitionTable::try_from(FileSystem::CIFS);
         assert!(unknown.is_err());
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_for_path() {
+        let root = Partition::for_path(Path::new("/")).unwrap();
+        assert!(!root.device.is_empty());
+        assert_eq!(root.mount_point, PathBuf::from("/"));
+
+        let mounts = Partition::list_mounts().unwrap();
+        assert!(!mounts.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_proc_mounts() {
+        let contents = "/dev/sda1 / ext4 rw,relatime 0 0\n\
+                        proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0\n\
+                        /dev/sdb1 /media/My\\040Drive vfat rw 0 0\n\
+                        malformed\n";
+        let mounts = parse_proc_mounts(contents);
+        assert_eq!(mounts.len(), 3);
+        assert_eq!(mounts[0].device, "/dev/sda1");
+        assert_eq!(mounts[0].filesystem_type, "ext4");
+        assert_eq!(mounts[1].mount_point, PathBuf::from("/proc"));
+        assert_eq!(mounts[2].mount_point, PathBuf::from("/media/My Drive"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_mount_options() {
+        let contents = "/dev/mmcblk0p2 /etc ext4 ro,relatime 0 0\n\
+                        tmpfs /run tmpfs rw,nosuid,nodev,mode=755 0 0\n\
+                        /dev/sda1 /boot vfat ro,rw 0 0\n";
+        let mounts = parse_proc_mounts(contents);
+        assert_eq!(mounts.len(), 3);
+
+        assert!(mounts[0].is_read_only());
+        assert_eq!(mounts[0].fs_type(), Some(FileSystem::Ext4));
+        assert_eq!(mounts[0].options, vec!["ro", "relatime"]);
+
+        assert!(!mounts[1].is_read_only());
+        assert_eq!(mounts[1].fs_type(), None);
+
+        assert!(!mounts[2].is_read_only());
+        assert_eq!(mounts[2].fs_type(), Some(FileSystem::Fat32));
+    }
 }
@@ -27,7 +27,7 @@ cfg_if! {
         /// # Returns
         ///
         /// An `Option` containing the path to the binary if found, or `None` if the binary was not found.
-        fn find_path_to_binary(binary: &str) -> Option<PathBuf> {
+        pub(crate) fn find_path_to_binary(binary: &str) -> Option<PathBuf> {
             env::var("PATH").ok().and_then(|paths| {
                 env::split_paths(&paths)
                     .map(|path| path.join(binary))
@@ -191,7 +191,332 @@ cfg_if! {
                     ("Unknown".to_string(), SemVer::new("0.0.0").unwrap())
             }
         }
+
+        /// Parse the total and available memory out of the contents of `/proc/meminfo`.
+        ///
+        /// # Arguments
+        ///
+        /// * `meminfo` - The contents of `/proc/meminfo`.
+        ///
+        /// # Returns
+        ///
+        /// A tuple containing the total and available memory in bytes. Kernels that do not report
+        /// `MemAvailable` report `MemFree` as the available memory instead.
+        fn parse_meminfo(meminfo: &str) -> (u128, u128) {
+            let mut total: u128 = 0;
+            let mut available: Option<u128> = None;
+            let mut free: u128 = 0;
+
+            for line in meminfo.lines() {
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+
+                // Values look like `16303428 kB`.
+                let mut value_parts = value.split_whitespace();
+                let Some(Ok(amount)) = value_parts.next().map(|amount| amount.parse::<u128>())
+                else {
+                    continue;
+                };
+                let bytes = match value_parts.next() {
+                    Some("kB") => amount * 1024,
+                    _ => amount,
+                };
+
+                match key.trim() {
+                    "MemTotal" => total = bytes,
+                    "MemAvailable" => available = Some(bytes),
+                    "MemFree" => free = bytes,
+                    _ => {}
+                }
+            }
+
+            (total, available.unwrap_or(free))
+        }
+
+        /// Get the total and available memory of the platform.
+        ///
+        /// # Returns
+        ///
+        /// A tuple containing the total and available memory in bytes.
+        fn get_memory() -> (u128, u128) {
+            match std::fs::read_to_string("/proc/meminfo") {
+                Ok(meminfo) => parse_meminfo(&meminfo),
+                Err(_) => (0, 0),
+            }
+        }
+
+        /// Get the name of an ARM core from the `CPU part` value in `/proc/cpuinfo`.
+        ///
+        /// # Arguments
+        ///
+        /// * `part` - The `CPU part` value, for example `0xd03`.
+        ///
+        /// # Returns
+        ///
+        /// The name and architecture version of the core, or `None` if the part is not known.
+        fn arm_core_name(part: &str) -> Option<&'static str> {
+            match part.to_lowercase().as_str() {
+                // Raspberry Pi 1 and Zero.
+                "0xb76" => Some("ARM1176 (ARMv6)"),
+                // Raspberry Pi 2.
+                "0xc07" => Some("Cortex-A7 (ARMv7)"),
+                // Raspberry Pi 3 and Zero 2.
+                "0xd03" => Some("Cortex-A53 (ARMv8)"),
+                // Raspberry Pi 4.
+                "0xd08" => Some("Cortex-A72 (ARMv8)"),
+                // Raspberry Pi 5.
+                "0xd0b" => Some("Cortex-A76 (ARMv8.2)"),
+                _ => None,
+            }
+        }
+
+        /// Parse a description of the processor out of the contents of `/proc/cpuinfo`.
+        ///
+        /// The description starts with the `model name` value (or the `Processor` value on older
+        /// ARM kernels). On ARM the core named by the `CPU part` value is appended, which tells
+        /// the Raspberry Pi generations apart.
+        ///
+        /// # Arguments
+        ///
+        /// * `cpuinfo` - The contents of `/proc/cpuinfo`.
+        ///
+        /// # Returns
+        ///
+        /// A description of the processor, or `Unknown` if the contents do not describe one.
+        fn parse_cpu_model(cpuinfo: &str) -> String {
+            let mut model_name: Option<String> = None;
+            let mut processor: Option<String> = None;
+            let mut core: Option<&'static str> = None;
+
+            for line in cpuinfo.lines() {
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+                let value = value.trim();
+
+                match key.trim() {
+                    "model name" if model_name.is_none() => model_name = Some(value.to_string()),
+                    "Processor" if processor.is_none() => processor = Some(value.to_string()),
+                    "CPU part" if core.is_none() => core = arm_core_name(value),
+                    _ => {}
+                }
+            }
+
+            match (model_name.or(processor), core) {
+                (Some(name), Some(core)) => format!("{} - {}", name, core),
+                (Some(name), None) => name,
+                (None, Some(core)) => core.to_string(),
+                (None, None) => "Unknown".to_string(),
+            }
+        }
+
+        /// Get a description of the processor of the platform.
+        fn get_cpu_model() -> String {
+            match std::fs::read_to_string("/proc/cpuinfo") {
+                Ok(cpuinfo) => parse_cpu_model(&cpuinfo),
+                Err(_) => "Unknown".to_string(),
+            }
+        }
+    } else if #[cfg(target_os = "macos")] {
+        use crate::shell::Shell;
+
+        /// Get the product name and version of the platform from `sw_vers`.
+        ///
+        /// # Returns
+        ///
+        /// A tuple containing the product name (for example `macOS`) and version of the platform.
+        fn get_product_name_version() -> (String, SemVer) {
+            let name = match Shell::execute("sw_vers", vec!["-productName".to_string()]) {
+                (Some(output), _) if !output.trim().is_empty() => output.trim().to_string(),
+                _ => "macOS".to_string(),
+            };
+
+            let version = match Shell::execute("sw_vers", vec!["-productVersion".to_string()]) {
+                (Some(output), _) => semver_from_version_string(output.trim()),
+                _ => SemVer::new("0.0.0").unwrap(),
+            };
+
+            (name, version)
+        }
+
+        /// Get the total and available memory of the platform.
+        ///
+        /// The total comes from `sysctl hw.memsize`. The available memory is the free, inactive,
+        /// and speculative pages reported by `vm_stat`.
+        ///
+        /// # Returns
+        ///
+        /// A tuple containing the total and available memory in bytes.
+        fn get_memory() -> (u128, u128) {
+            let total_result =
+                Shell::execute("sysctl", vec!["-n".to_string(), "hw.memsize".to_string()]);
+            let total = match total_result {
+                (Some(output), _) => output.trim().parse::<u128>().unwrap_or(0),
+                _ => 0,
+            };
+
+            let available = match Shell::execute("vm_stat", vec![]) {
+                (Some(output), _) => {
+                    // The first line looks like
+                    // `Mach Virtual Memory Statistics: (page size of 16384 bytes)`.
+                    let page_size = output
+                        .split("page size of")
+                        .nth(1)
+                        .and_then(|rest| rest.split_whitespace().next())
+                        .and_then(|size| size.parse::<u128>().ok())
+                        .unwrap_or(4096);
+
+                    // The page lines look like `Pages free:    12345.`.
+                    let pages: u128 = output
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .filter(|(key, _)| {
+                            matches!(
+                                key.trim(),
+                                "Pages free" | "Pages inactive" | "Pages speculative"
+                            )
+                        })
+                        .filter_map(|(_, value)| {
+                            value.trim().trim_end_matches('.').parse::<u128>().ok()
+                        })
+                        .sum();
+
+                    pages * page_size
+                }
+                _ => 0,
+            };
+
+            (total, available)
+        }
+
+        /// Get a description of the processor of the platform from `sysctl`.
+        fn get_cpu_model() -> String {
+            let brand_result = Shell::execute(
+                "sysctl",
+                vec!["-n".to_string(), "machdep.cpu.brand_string".to_string()],
+            );
+            match brand_result {
+                (Some(output), _) if !output.trim().is_empty() => output.trim().to_string(),
+                _ => "Unknown".to_string(),
+            }
+        }
+    } else if #[cfg(target_os = "windows")] {
+        use crate::shell::Shell;
+
+        /// Get the product name and version of the platform.
+        ///
+        /// The version comes from the output of `ver`, which looks like
+        /// `Microsoft Windows [Version 10.0.22631.3447]`. The edition, such as `Professional`,
+        /// comes from the `EditionID` registry value and is appended to the name when present.
+        ///
+        /// # Returns
+        ///
+        /// A tuple containing the product name and version of the platform.
+        fn get_product_name_version() -> (String, SemVer) {
+            let version = match Shell::execute("ver", vec![]) {
+                (Some(output), _) => match output.split("Version").nth(1) {
+                    Some(rest) => semver_from_version_string(rest.trim().trim_end_matches(']')),
+                    None => SemVer::new("0.0.0").unwrap(),
+                },
+                _ => SemVer::new("0.0.0").unwrap(),
+            };
+
+            let edition_result = Shell::execute(
+                "reg",
+                vec![
+                    "query".to_string(),
+                    "HKLM\\SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion".to_string(),
+                    "/v".to_string(),
+                    "EditionID".to_string(),
+                ],
+            );
+
+            // The value line looks like `    EditionID    REG_SZ    Professional`.
+            let edition = if let (Some(output), _) = edition_result {
+                output
+                    .lines()
+                    .find(|line| line.trim_start().starts_with("EditionID"))
+                    .and_then(|line| line.split_whitespace().nth(2))
+                    .map(|edition| edition.to_string())
+            } else {
+                None
+            };
+
+            let name = match edition {
+                Some(edition) => format!("Windows {}", edition),
+                None => "Windows".to_string(),
+            };
+
+            (name, version)
+        }
+
+        /// Get the total and available memory of the platform from `GlobalMemoryStatusEx`.
+        ///
+        /// # Returns
+        ///
+        /// A tuple containing the total and available memory in bytes.
+        fn get_memory() -> (u128, u128) {
+            use windows_sys::Win32::System::SystemInformation::{
+                GlobalMemoryStatusEx, MEMORYSTATUSEX,
+            };
+
+            // SAFETY: MEMORYSTATUSEX is plain data, so all zeros is a valid value, and
+            // GlobalMemoryStatusEx only writes to the struct we pass it once dwLength is set.
+            let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+            status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+            if unsafe { GlobalMemoryStatusEx(&mut status) } == 0 {
+                return (0, 0);
+            }
+
+            (status.ullTotalPhys as u128, status.ullAvailPhys as u128)
+        }
+
+        /// Get a description of the processor of the platform from the `PROCESSOR_IDENTIFIER`
+        /// environment variable.
+        fn get_cpu_model() -> String {
+            std::env::var("PROCESSOR_IDENTIFIER").unwrap_or_else(|_| "Unknown".to_string())
+        }
+    } else {
+        /// Get the total and available memory of the platform. Memory is not reported on this
+        /// platform.
+        fn get_memory() -> (u128, u128) {
+            (0, 0)
+        }
+
+        /// Get a description of the processor of the platform. The processor is not described
+        /// on this platform.
+        fn get_cpu_model() -> String {
+            "Unknown".to_string()
+        }
+    }
+}
+
+/// Convert a dotted version string such as `14.4` or `10.0.22631.3447` to a `SemVer`. Missing
+/// components are filled in with zero and components past the patch number are ignored.
+///
+/// # Arguments
+///
+/// * `version_string` - The version string to convert.
+///
+/// # Returns
+///
+/// The `SemVer` for the version string, or `0.0.0` if the string is not a version.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn semver_from_version_string(version_string: &str) -> SemVer {
+    let mut parts: Vec<&str> = version_string.split('.').take(3).collect();
+    while parts.len() < 3 {
+        parts.push("0");
     }
+
+    SemVer::new(&parts.join(".")).unwrap_or_else(|| SemVer::new("0.0.0").unwrap())
+}
+
+// lazy_static does not carry a `#[cfg]` over to the impls it generates, so a platform specific
+// static needs its own block.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+lazy_static! {
+    static ref PRODUCT_NAME_VERSION: (String, SemVer) = get_product_name_version();
 }
 
 lazy_static! {
@@ -200,9 +525,9 @@ lazy_static! {
             if #[cfg(target_os = "linux")] {
                 "Linux".to_string()
             } else if #[cfg(target_os = "macos")] {
-                "macOS".to_string()
+                PRODUCT_NAME_VERSION.0.clone()
             } else if #[cfg(target_os = "windows")] {
-                "Windows".to_string()
+                PRODUCT_NAME_VERSION.0.clone()
             } else if #[cfg(target_os = "freebsd")] {
                 "FreeBSD".to_string()
             } else {
@@ -231,16 +556,22 @@ lazy_static! {
         cfg_if! {
             if #[cfg(target_os = "linux")] {
                 get_vendor_version().1
+            } else if #[cfg(any(target_os = "macos", target_os = "windows"))] {
+                PRODUCT_NAME_VERSION.1.clone()
             } else {
                 SemVer::new("0.0.0").unwrap()
             }
         }
     };
 
+    static ref MEMORY: (u128, u128) = get_memory();
+
     static ref NUMBER_OF_PROCESSORS: usize = {
         num_cpus::get()
     };
 
+    static ref CPU_MODEL: String = get_cpu_model();
+
     static ref PROCESSOR_ARCHITECTURE: ProcessorArchitecture = {
         cfg_if ! {
             if #[cfg(target_arch = "x86")] {
@@ -260,6 +591,10 @@ lazy_static! {
 }
 
 /// The `PlatformId` struct represents the platform on which the application is running.
+///
+/// The name, vendor, version, processor architecture, and CPU model do not change while the
+/// application runs, so they are computed once and cached. `PlatformId::new` also returns the
+/// cached memory and processor count figures, while `PlatformId::refresh` recomputes them.
 pub struct PlatformId {
     /// The name of the platform.
     pub name: String,
@@ -273,8 +608,19 @@ pub struct PlatformId {
     /// The number of processors on the platform.
     pub number_of_processors: usize,
 
+    /// The total amount of memory on the platform in bytes.
+    pub total_memory_bytes: u128,
+
+    /// The amount of memory available for new allocations on the platform in bytes.
+    pub available_memory_bytes: u128,
+
     /// The processor architecture of the platform.
     pub processor_architecture: ProcessorArchitecture,
+
+    /// A description of the processor, for example `ARMv7 Processor rev 4 (v7l) - Cortex-A53
+    /// (ARMv8)` on a Raspberry Pi 3. On ARM Linux the description names the core, which tells
+    /// ARMv6 and ARMv7 boards apart where `processor_architecture` is just `ARM`.
+    pub cpu_model: String,
 }
 
 impl PlatformId {
@@ -285,9 +631,130 @@ impl PlatformId {
             vendor: VENDOR.to_string(),
             version: VERSION.to_owned(),
             number_of_processors: NUMBER_OF_PROCESSORS.to_owned(),
+            total_memory_bytes: MEMORY.0,
+            available_memory_bytes: MEMORY.1,
             processor_architecture: PROCESSOR_ARCHITECTURE.to_owned(),
+            cpu_model: CPU_MODEL.to_string(),
         }
     }
+
+    /// Create a new `PlatformId` instance with up to date memory and processor counts.
+    ///
+    /// The memory and processor count figures are recomputed instead of read from the cache.
+    /// The name, vendor, version, processor architecture, and CPU model still come from the
+    /// cache.
+    pub fn refresh() -> PlatformId {
+        let (total_memory_bytes, available_memory_bytes) = get_memory();
+        PlatformId {
+            name: NAME.to_string(),
+            vendor: VENDOR.to_string(),
+            version: VERSION.to_owned(),
+            number_of_processors: num_cpus::get(),
+            total_memory_bytes,
+            available_memory_bytes,
+            processor_architecture: PROCESSOR_ARCHITECTURE.to_owned(),
+            cpu_model: CPU_MODEL.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh() {
+        let platform_id = PlatformId::new();
+        let refreshed = PlatformId::refresh();
+        assert_eq!(refreshed.name, platform_id.name);
+        assert_eq!(refreshed.vendor, platform_id.vendor);
+        assert_eq!(refreshed.version, platform_id.version);
+        assert_eq!(
+            refreshed.processor_architecture,
+            platform_id.processor_architecture
+        );
+        assert_eq!(refreshed.cpu_model, platform_id.cpu_model);
+        assert!(refreshed.number_of_processors > 0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_cpu_model() {
+        let pi3_cpuinfo = "processor       : 0
+model name      : ARMv7 Processor rev 4 (v7l)
+BogoMIPS        : 38.40
+Features        : half thumb fastmult vfp edsp neon vfpv3 tls vfpv4 idiva idivt vfpd32 lpae evtstrm crc32
+CPU implementer : 0x41
+CPU architecture: 7
+CPU variant     : 0x0
+CPU part        : 0xd03
+CPU revision    : 4
+
+Hardware        : BCM2835
+Revision        : a02082
+Model           : Raspberry Pi 3 Model B Rev 1.2
+";
+        assert_eq!(
+            parse_cpu_model(pi3_cpuinfo),
+            "ARMv7 Processor rev 4 (v7l) - Cortex-A53 (ARMv8)"
+        );
+
+        let pi_zero_cpuinfo = "processor       : 0
+model name      : ARMv6-compatible processor rev 7 (v6l)
+CPU part        : 0xb76
+";
+        assert_eq!(
+            parse_cpu_model(pi_zero_cpuinfo),
+            "ARMv6-compatible processor rev 7 (v6l) - ARM1176 (ARMv6)"
+        );
+
+        let x86_cpuinfo = "processor       : 0
+vendor_id       : GenuineIntel
+model name      : Intel(R) Core(TM) i7-8650U CPU @ 1.90GHz
+";
+        assert_eq!(
+            parse_cpu_model(x86_cpuinfo),
+            "Intel(R) Core(TM) i7-8650U CPU @ 1.90GHz"
+        );
+
+        assert_eq!(parse_cpu_model(""), "Unknown");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       16303428 kB
+MemFree:         1253392 kB
+MemAvailable:    9876543 kB
+Buffers:          412332 kB
+Cached:          7712340 kB
+HugePages_Total:       0
+";
+        assert_eq!(parse_meminfo(meminfo), (16303428 * 1024, 9876543 * 1024));
+
+        let old_meminfo = "MemTotal:       2048 kB
+MemFree:         1024 kB
+";
+        assert_eq!(parse_meminfo(old_meminfo), (2048 * 1024, 1024 * 1024));
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_macos_version() {
+        let platform_id = PlatformId::new();
+        assert_eq!(platform_id.vendor, "Apple");
+        assert!(platform_id.name.starts_with("mac") || platform_id.name.starts_with("Mac"));
+        assert!(platform_id.version.major > 0);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_windows_version() {
+        let platform_id = PlatformId::new();
+        assert_eq!(platform_id.vendor, "Microsoft");
+        assert!(platform_id.name.starts_with("Windows"));
+        assert!(platform_id.version.major > 0);
+    }
 }
 
 // Testing code that is disabled for now.
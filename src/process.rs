@@ -1,9 +1,204 @@
+//! The `process` module provides platform-specific code for working with processes, including
+//! the `Process` object for querying a running process.
+
+use crate::bytes::{normalize_byte_size, ByteMetricBase};
+use crate::error::FoundationError;
+use crate::process_watcher::ProcessId;
+use std::time::Duration;
+
 cfg_if! {
     if #[cfg(target_os = "linux")] {
+        mod kill_posix;
+        mod tree_linux;
+        mod usage_linux;
         mod watcher_posix;
+        use kill_posix::kill_process_tree;
+        use usage_linux::resource_usage_for_process;
         pub use watcher_posix::watch_processes_for_termination;
     } else if #[cfg(target_os = "macos")] {
+        mod kill_posix;
+        mod tree_macos;
+        mod usage_macos;
         mod watcher_posix;
+        use kill_posix::kill_process_tree;
+        use usage_macos::resource_usage_for_process;
         pub use watcher_posix::watch_processes_for_termination;
+    } else if #[cfg(target_os = "windows")] {
+        fn resource_usage_for_process(_process_id: ProcessId) -> Result<ResourceUsage, FoundationError> {
+            Err(FoundationError::OperationFailed(
+                "Process resource usage is not supported on this platform".to_string(),
+            ))
+        }
+
+        // Windows has no SIGTERM for console processes, so taskkill forcibly ends the tree.
+        fn kill_process_tree(root: ProcessId, _grace_period: Duration) -> Result<(), FoundationError> {
+            let status = std::process::Command::new("taskkill")
+                .args(["/PID", &root.to_string(), "/T", "/F"])
+                .status()?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(FoundationError::OperationFailed(format!(
+                    "taskkill failed to end process tree {}",
+                    root
+                )))
+            }
+        }
+    } else {
+        fn resource_usage_for_process(_process_id: ProcessId) -> Result<ResourceUsage, FoundationError> {
+            Err(FoundationError::OperationFailed(
+                "Process resource usage is not supported on this platform".to_string(),
+            ))
+        }
+
+        fn kill_process_tree(_root: ProcessId, _grace_period: Duration) -> Result<(), FoundationError> {
+            Err(FoundationError::OperationFailed(
+                "Killing a process tree is not supported on this platform".to_string(),
+            ))
+        }
+    }
+}
+
+/// How long `Process::kill_tree` waits for processes to exit after SIGTERM before sending
+/// SIGKILL.
+pub const KILL_TREE_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// The `ResourceUsage` struct holds the memory and CPU usage of a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// The resident set size of the process in bytes.
+    pub rss_bytes: u64,
+
+    /// The total user and system CPU time used by the process.
+    pub cpu_time: Duration,
+}
+
+impl ResourceUsage {
+    /// Get the resident set size in a human-readable format.
+    ///
+    /// # Arguments
+    ///
+    /// * `metric_base` - The base to use when converting bytes to a human-readable format.
+    pub fn rss_display(&self, metric_base: ByteMetricBase) -> String {
+        normalize_byte_size(self.rss_bytes as u128, metric_base)
+    }
+}
+
+/// The `Process` struct represents a running process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Process {
+    /// The ID of the process.
+    id: ProcessId,
+}
+
+impl Process {
+    /// Create a new `Process` for the process with the given ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the process.
+    pub fn new(id: ProcessId) -> Process {
+        Process { id }
+    }
+
+    /// Create a new `Process` for the current process.
+    pub fn current() -> Process {
+        Process::new(std::process::id())
+    }
+
+    /// Get the ID of the process.
+    pub fn id(&self) -> ProcessId {
+        self.id
+    }
+
+    /// Get the memory and CPU usage of the process.
+    ///
+    /// # Returns
+    ///
+    /// The resource usage of the process, or a `FoundationError` if the process does not exist
+    /// or its usage could not be read.
+    pub fn resource_usage(&self) -> Result<ResourceUsage, FoundationError> {
+        resource_usage_for_process(self.id)
+    }
+
+    /// Terminate the process and all of its descendants, so that killing a process does not
+    /// leave its children running. Each process gets SIGTERM first, and the processes still
+    /// running after `KILL_TREE_GRACE_PERIOD` get SIGKILL. On Windows the tree is ended with
+    /// `taskkill`.
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) on success and a `FoundationError` if the process is not running or could not be
+    /// terminated.
+    pub fn kill_tree(&self) -> Result<(), FoundationError> {
+        self.kill_tree_with_grace_period(KILL_TREE_GRACE_PERIOD)
+    }
+
+    /// Terminate the process and all of its descendants with a custom grace period.
+    ///
+    /// # Arguments
+    ///
+    /// * `grace_period` - How long to wait for the processes to exit after SIGTERM before
+    ///   sending SIGKILL.
+    ///
+    /// # Returns
+    ///
+    /// Ok(()) on success and a `FoundationError` if the process is not running or could not be
+    /// terminated.
+    pub fn kill_tree_with_grace_period(
+        &self,
+        grace_period: Duration,
+    ) -> Result<(), FoundationError> {
+        kill_process_tree(self.id, grace_period)
+    }
+}
+
+impl From<&std::process::Child> for Process {
+    fn from(child: &std::process::Child) -> Self {
+        Process::new(child.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_resource_usage() {
+        let usage = Process::current().resource_usage().unwrap();
+        assert!(usage.rss_bytes > 0);
+        assert!(!usage.rss_display(ByteMetricBase::Metric).is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_tree() {
+        use std::io::{BufRead, BufReader};
+
+        let mut shell = std::process::Command::new("sh")
+            .args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut sleep_id = String::new();
+        BufReader::new(shell.stdout.take().unwrap())
+            .read_line(&mut sleep_id)
+            .unwrap();
+        let sleep_id: ProcessId = sleep_id.trim().parse().unwrap();
+        assert!(tree_linux::is_running(sleep_id));
+
+        Process::from(&shell)
+            .kill_tree_with_grace_period(Duration::from_secs(2))
+            .unwrap();
+        shell.wait().unwrap();
+
+        assert!(!tree_linux::is_running(shell.id()));
+        assert!(!tree_linux::is_running(sleep_id));
+    }
+
+    #[test]
+    fn test_resource_usage_missing_process() {
+        assert!(Process::new(2147483647).resource_usage().is_err());
     }
 }
@@ -0,0 +1,84 @@
+use crate::error::FoundationError;
+use crate::process_watcher::ProcessId;
+use std::time::{Duration, Instant};
+
+cfg_if! {
+    if #[cfg(target_os = "linux")] {
+        use crate::process::tree_linux::{child_processes, is_running};
+    } else if #[cfg(target_os = "macos")] {
+        use crate::process::tree_macos::{child_processes, is_running};
+    }
+}
+
+/// How often to check whether the processes have exited during the grace period.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Send a signal to a process, ignoring processes that have already exited.
+///
+/// # Arguments
+///
+/// * `process_id` - The ID of the process.
+/// * `signal` - The signal to send.
+fn send_signal(process_id: ProcessId, signal: libc::c_int) -> Result<(), FoundationError> {
+    let result = unsafe { libc::kill(process_id as libc::pid_t, signal) };
+    if result == -1 {
+        let errno = errno::errno();
+        if errno.0 != libc::ESRCH {
+            return Err(FoundationError::IO(std::io::Error::from_raw_os_error(
+                errno.0,
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Terminate a process and all of its descendants. Every process in the tree gets SIGTERM, and
+/// the processes still running after the grace period get SIGKILL.
+///
+/// # Arguments
+///
+/// * `root` - The ID of the process at the top of the tree.
+/// * `grace_period` - How long to wait for the processes to exit after SIGTERM.
+///
+/// # Returns
+///
+/// Ok(()) on success and a `FoundationError` if the root process is not running or a signal
+/// could not be sent.
+pub fn kill_process_tree(root: ProcessId, grace_period: Duration) -> Result<(), FoundationError> {
+    if !is_running(root) {
+        return Err(FoundationError::OperationFailed(format!(
+            "Process {} is not running",
+            root
+        )));
+    }
+
+    // Collect the whole tree before signalling anything, because the children of a process that
+    // exits are adopted by another process and can no longer be found from the root.
+    let mut tree = vec![root];
+    let mut index = 0;
+    while index < tree.len() {
+        for child in child_processes(tree[index])? {
+            if !tree.contains(&child) {
+                tree.push(child);
+            }
+        }
+        index += 1;
+    }
+
+    for process_id in &tree {
+        send_signal(*process_id, libc::SIGTERM)?;
+    }
+
+    let start = Instant::now();
+    while tree.iter().any(|process_id| is_running(*process_id)) {
+        if start.elapsed() >= grace_period {
+            for process_id in tree.iter().filter(|process_id| is_running(**process_id)) {
+                send_signal(*process_id, libc::SIGKILL)?;
+            }
+            break;
+        }
+        std::thread::sleep(EXIT_POLL_INTERVAL);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use crate::error::FoundationError;
+use crate::process_watcher::ProcessId;
+
+/// Get the fields of `/proc/<pid>/stat` that follow the command name. The command name is in
+/// parentheses and can contain spaces, so the fields start after the last closing parenthesis.
+///
+/// # Arguments
+///
+/// * `process_id` - The ID of the process.
+fn stat_fields(process_id: ProcessId) -> Option<Vec<String>> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", process_id)).ok()?;
+    let index = stat.rfind(')')?;
+    Some(
+        stat[index + 1..]
+            .split_whitespace()
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Get the direct children of a process by scanning the parent process IDs in `/proc`.
+///
+/// # Arguments
+///
+/// * `process_id` - The ID of the parent process.
+///
+/// # Returns
+///
+/// The IDs of the child processes.
+pub fn child_processes(process_id: ProcessId) -> Result<Vec<ProcessId>, FoundationError> {
+    let mut children = Vec::new();
+    for entry in std::fs::read_dir("/proc")? {
+        let entry = entry?;
+        let candidate = match entry.file_name().to_string_lossy().parse::<ProcessId>() {
+            Ok(candidate) => candidate,
+            Err(_) => continue,
+        };
+
+        // The parent process ID is the second field after the command name. Processes can exit
+        // while we scan, so skip the ones we can no longer read.
+        let parent = stat_fields(candidate).and_then(|fields| {
+            fields
+                .get(1)
+                .and_then(|ppid| ppid.parse::<ProcessId>().ok())
+        });
+        if parent == Some(process_id) {
+            children.push(candidate);
+        }
+    }
+    Ok(children)
+}
+
+/// Check whether a process is running. Zombie processes have exited and are not running.
+///
+/// # Arguments
+///
+/// * `process_id` - The ID of the process.
+pub fn is_running(process_id: ProcessId) -> bool {
+    match stat_fields(process_id) {
+        Some(fields) => !matches!(fields.first().map(String::as_str), Some("Z") | Some("X")),
+        None => false,
+    }
+}
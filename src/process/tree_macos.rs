@@ -0,0 +1,35 @@
+use crate::error::FoundationError;
+use crate::process_watcher::ProcessId;
+use std::process::Command;
+
+/// Get the direct children of a process with `pgrep -P`.
+///
+/// # Arguments
+///
+/// * `process_id` - The ID of the parent process.
+///
+/// # Returns
+///
+/// The IDs of the child processes.
+pub fn child_processes(process_id: ProcessId) -> Result<Vec<ProcessId>, FoundationError> {
+    // pgrep exits with status 1 when no processes match, so only the output matters.
+    let output = Command::new("pgrep")
+        .args(["-P", &process_id.to_string()])
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse::<ProcessId>().ok())
+        .collect())
+}
+
+/// Check whether a process is running.
+///
+/// # Arguments
+///
+/// * `process_id` - The ID of the process.
+pub fn is_running(process_id: ProcessId) -> bool {
+    // Sending the signal 0 to a process will check if the process is still alive. EPERM means
+    // the process exists but belongs to another user.
+    let result = unsafe { libc::kill(process_id as libc::pid_t, 0) };
+    result == 0 || errno::errno().0 == libc::EPERM
+}
@@ -0,0 +1,97 @@
+use crate::error::FoundationError;
+use crate::process::ResourceUsage;
+use crate::process_watcher::ProcessId;
+use std::time::Duration;
+
+/// Get the resource usage of a process from the `/proc` filesystem.
+///
+/// # Arguments
+///
+/// * `process_id` - The ID of the process.
+///
+/// # Returns
+///
+/// The resource usage of the process, or a `FoundationError` if the process does not exist or
+/// its usage could not be read.
+pub fn resource_usage_for_process(process_id: ProcessId) -> Result<ResourceUsage, FoundationError> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", process_id))?;
+    let statm = std::fs::read_to_string(format!("/proc/{}/statm", process_id))?;
+
+    // SAFETY: sysconf only reads system configuration values.
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if ticks_per_second <= 0 || page_size <= 0 {
+        return Err(FoundationError::OperationFailed(
+            "Unable to read the clock tick rate or page size".to_string(),
+        ));
+    }
+
+    let cpu_ticks = parse_cpu_ticks(&stat)?;
+    let resident_pages = parse_resident_pages(&statm)?;
+
+    Ok(ResourceUsage {
+        rss_bytes: resident_pages * page_size as u64,
+        cpu_time: Duration::from_secs_f64(cpu_ticks as f64 / ticks_per_second as f64),
+    })
+}
+
+/// Parse the user and system CPU time, in clock ticks, from the contents of `/proc/<pid>/stat`.
+///
+/// # Arguments
+///
+/// * `stat` - The contents of the stat file.
+fn parse_cpu_ticks(stat: &str) -> Result<u64, FoundationError> {
+    // The second field is the command name in parentheses and can contain spaces, so the
+    // remaining fields start after the last closing parenthesis.
+    let fields: Vec<&str> = match stat.rfind(')') {
+        Some(index) => stat[index + 1..].split_whitespace().collect(),
+        None => Vec::new(),
+    };
+
+    // After the command name, the fields start with the state (field 3), so utime (field 14) and
+    // stime (field 15) are at indexes 11 and 12.
+    match (fields.get(11), fields.get(12)) {
+        (Some(user), Some(system)) => Ok(user.parse::<u64>()? + system.parse::<u64>()?),
+        _ => Err(FoundationError::OperationFailed(format!(
+            "Unable to parse process stat: {}",
+            stat
+        ))),
+    }
+}
+
+/// Parse the number of resident pages from the contents of `/proc/<pid>/statm`.
+///
+/// # Arguments
+///
+/// * `statm` - The contents of the statm file.
+fn parse_resident_pages(statm: &str) -> Result<u64, FoundationError> {
+    match statm.split_whitespace().nth(1) {
+        Some(resident) => Ok(resident.parse::<u64>()?),
+        None => Err(FoundationError::OperationFailed(format!(
+            "Unable to parse process statm: {}",
+            statm
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_ticks() {
+        let stat = "1234 (my (odd) cmd) S 1 1234 1234 0 -1 4194560 100 0 0 0 25 17 0 0 20 0 1 0 \
+                    100 1000000 200 18446744073709551615";
+        assert_eq!(parse_cpu_ticks(stat).unwrap(), 42);
+        assert!(parse_cpu_ticks("garbage").is_err());
+    }
+
+    #[test]
+    fn test_parse_resident_pages() {
+        assert_eq!(
+            parse_resident_pages("1000 250 100 10 0 300 0\n").unwrap(),
+            250
+        );
+        assert!(parse_resident_pages("").is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use crate::error::FoundationError;
+use crate::process::ResourceUsage;
+use crate::process_watcher::ProcessId;
+use std::time::Duration;
+
+/// Get the resource usage of a process with `proc_pid_rusage`.
+///
+/// # Arguments
+///
+/// * `process_id` - The ID of the process.
+///
+/// # Returns
+///
+/// The resource usage of the process, or a `FoundationError` if the process does not exist or
+/// its usage could not be read.
+pub fn resource_usage_for_process(process_id: ProcessId) -> Result<ResourceUsage, FoundationError> {
+    // SAFETY: rusage_info_v2 is plain data, so a zeroed value is valid, and proc_pid_rusage
+    // writes at most one rusage_info_v2 into the buffer for the RUSAGE_INFO_V2 flavor.
+    let mut info: libc::rusage_info_v2 = unsafe { std::mem::zeroed() };
+    let result = unsafe {
+        libc::proc_pid_rusage(
+            process_id as libc::c_int,
+            libc::RUSAGE_INFO_V2,
+            &mut info as *mut libc::rusage_info_v2 as *mut libc::rusage_info_t,
+        )
+    };
+    if result != 0 {
+        return Err(FoundationError::IO(std::io::Error::last_os_error()));
+    }
+
+    // The CPU times are in Mach absolute time units, which are nanoseconds only on Intel.
+    let mut timebase = libc::mach_timebase_info { numer: 0, denom: 0 };
+    #[allow(deprecated)]
+    let timebase_result = unsafe { libc::mach_timebase_info(&mut timebase) };
+    if timebase_result != 0 || timebase.denom == 0 {
+        return Err(FoundationError::OperationFailed(
+            "Unable to read the Mach timebase".to_string(),
+        ));
+    }
+
+    let cpu_units = info.ri_user_time as u128 + info.ri_system_time as u128;
+    let cpu_nanoseconds = cpu_units * timebase.numer as u128 / timebase.denom as u128;
+
+    Ok(ResourceUsage {
+        rss_bytes: info.ri_resident_size,
+        cpu_time: Duration::from_nanos(cpu_nanoseconds as u64),
+    })
+}
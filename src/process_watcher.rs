@@ -1,13 +1,17 @@
 //! The process watcher module provides a way to watch processes for termination.
 //! The module provides `ProcessWatcher` which will monitor a set of process for termination and
-//! call a callback when the process terminates.
+//! call a callback when the process terminates. The watcher can also launch a command and
+//! restart it when it exits, according to a `RestartPolicy`.
 
 use crate::error::FoundationError;
 use crate::process::watch_processes_for_termination;
 use crate::threadcontroller::ThreadController;
+use log::{error, trace};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::thread::Builder;
+use std::time::Duration;
+use tokio::task::JoinHandle;
 
 /// Type for a process ID.
 pub type ProcessId = u32;
@@ -15,6 +19,79 @@ pub type ProcessId = u32;
 /// Type for a callback that is called when a process terminates.
 pub type Callback = Box<dyn FnMut(ProcessId) + Send + Sync + 'static>;
 
+/// Type for a callback that is called each time a supervised command is restarted. The callback
+/// receives the number of the restart, starting at 1, and the ID of the new process.
+pub type RestartCallback = Box<dyn FnMut(u32, ProcessId) + Send + Sync + 'static>;
+
+/// The longest delay between restarts of a supervised command.
+pub const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The policy that decides whether a supervised command is restarted when it exits.
+///
+/// The delay before the first restart is `backoff`, and the delay doubles after each restart, up
+/// to `MAX_RESTART_BACKOFF`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Never restart the command.
+    Never,
+
+    /// Restart the command whenever it exits.
+    Always {
+        /// The maximum number of restarts.
+        max_retries: u32,
+
+        /// The delay before the first restart.
+        backoff: Duration,
+    },
+
+    /// Restart the command when it exits with a non-zero status or is killed by a signal.
+    OnFailure {
+        /// The maximum number of restarts.
+        max_retries: u32,
+
+        /// The delay before the first restart.
+        backoff: Duration,
+    },
+}
+
+impl RestartPolicy {
+    /// Decide whether to restart a command that has exited.
+    ///
+    /// # Arguments
+    ///
+    /// * `succeeded` - Whether the command exited with a zero status.
+    /// * `restarts` - The number of times the command has already been restarted.
+    ///
+    /// # Returns
+    ///
+    /// The delay before the restart, or `None` if the command should not be restarted.
+    fn restart_delay(&self, succeeded: bool, restarts: u32) -> Option<Duration> {
+        let (max_retries, backoff) = match *self {
+            RestartPolicy::Never => return None,
+            RestartPolicy::Always {
+                max_retries,
+                backoff,
+            } => (max_retries, backoff),
+            RestartPolicy::OnFailure {
+                max_retries,
+                backoff,
+            } => {
+                if succeeded {
+                    return None;
+                }
+                (max_retries, backoff)
+            }
+        };
+
+        if restarts >= max_retries {
+            return None;
+        }
+
+        let factor = 2u32.saturating_pow(restarts);
+        Some(backoff.saturating_mul(factor).min(MAX_RESTART_BACKOFF))
+    }
+}
+
 /// A process watcher that can be used to watch processes for termination.
 pub struct ProcessWatcher {
     /// The callbacks that are called when a process terminates.
@@ -25,6 +102,9 @@ pub struct ProcessWatcher {
 
     /// The handle to the thread that watches the processes.
     thread_handle: Option<std::thread::JoinHandle<()>>,
+
+    /// The tasks that supervise commands launched by the watcher.
+    supervisors: Vec<JoinHandle<()>>,
 }
 
 impl ProcessWatcher {
@@ -34,6 +114,7 @@ impl ProcessWatcher {
             callbacks: Arc::new(Mutex::new(HashMap::new())),
             thread_controller: Arc::new(ThreadController::new(true)),
             thread_handle: None,
+            supervisors: Vec::new(),
         }
     }
 
@@ -74,8 +155,11 @@ impl ProcessWatcher {
         Ok(())
     }
 
-    /// Stop the process watcher.
+    /// Stop the process watcher. Supervised commands that are still running are killed.
     pub fn stop(&mut self) -> Result<(), FoundationError> {
+        for supervisor in self.supervisors.drain(..) {
+            supervisor.abort();
+        }
         self.thread_controller.signal_stop();
         if let Some(handle) = self.thread_handle.take() {
             if let Err(e) = handle.join() {
@@ -112,6 +196,85 @@ impl ProcessWatcher {
     pub fn remove_callback(&mut self, process_id: ProcessId) {
         self.callbacks.lock().unwrap().remove(&process_id);
     }
+
+    /// Launch a command and restart it according to a policy whenever it exits. The command runs
+    /// under a tokio task, so the function must be called from within a tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to run.
+    /// * `arguments` - The arguments for the command.
+    /// * `policy` - The policy that decides whether to restart the command when it exits.
+    /// * `on_restart` - The callback to call each time the command is restarted.
+    ///
+    /// # Returns
+    ///
+    /// The ID of the first process on success and a `FoundationError` if the command could not
+    /// be launched.
+    pub fn supervise(
+        &mut self,
+        command: &str,
+        arguments: Vec<String>,
+        policy: RestartPolicy,
+        mut on_restart: RestartCallback,
+    ) -> Result<ProcessId, FoundationError> {
+        let launch = {
+            let command = command.to_string();
+            move || {
+                tokio::process::Command::new(&command)
+                    .args(&arguments)
+                    .kill_on_drop(true)
+                    .spawn()
+            }
+        };
+
+        let mut child = launch()?;
+        let first_id = child.id().unwrap_or_default();
+        let command = command.to_string();
+
+        self.supervisors.push(tokio::spawn(async move {
+            let mut restarts: u32 = 0;
+            loop {
+                let succeeded = match child.wait().await {
+                    Ok(status) => status.success(),
+                    Err(e) => {
+                        error!("Failed to wait for supervised command {}: {}", command, e);
+                        return;
+                    }
+                };
+
+                let delay = match policy.restart_delay(succeeded, restarts) {
+                    Some(delay) => delay,
+                    None => {
+                        trace!("Supervised command {} will not be restarted", command);
+                        return;
+                    }
+                };
+                tokio::time::sleep(delay).await;
+
+                child = match launch() {
+                    Ok(child) => child,
+                    Err(e) => {
+                        error!("Failed to restart supervised command {}: {}", command, e);
+                        return;
+                    }
+                };
+                restarts += 1;
+                on_restart(restarts, child.id().unwrap_or_default());
+            }
+        }));
+
+        Ok(first_id)
+    }
+}
+
+impl Drop for ProcessWatcher {
+    fn drop(&mut self) {
+        // Abort the supervisors so supervised commands do not outlive the watcher.
+        if let Err(e) = self.stop() {
+            error!("Failed to stop process watcher: {}", e);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -149,4 +312,88 @@ mod tests {
         watcher.stop().unwrap();
         assert!(is_dead.lock().unwrap().clone());
     }
+
+    #[tokio::test]
+    async fn test_supervise_on_failure() {
+        let restarts = Arc::new(Mutex::new(Vec::new()));
+        let restarts_clone = restarts.clone();
+        let mut watcher = ProcessWatcher::new();
+        watcher
+            .supervise(
+                "sh",
+                vec!["-c".to_string(), "exit 1".to_string()],
+                RestartPolicy::OnFailure {
+                    max_retries: 3,
+                    backoff: Duration::from_millis(10),
+                },
+                Box::new(move |restart, _| restarts_clone.lock().unwrap().push(restart)),
+            )
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(*restarts.lock().unwrap(), vec![1, 2, 3]);
+        watcher.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_supervise_on_failure_success() {
+        let restarts = Arc::new(Mutex::new(0));
+        let restarts_clone = restarts.clone();
+        let mut watcher = ProcessWatcher::new();
+        watcher
+            .supervise(
+                "true",
+                Vec::new(),
+                RestartPolicy::OnFailure {
+                    max_retries: 3,
+                    backoff: Duration::from_millis(10),
+                },
+                Box::new(move |_, _| *restarts_clone.lock().unwrap() += 1),
+            )
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(*restarts.lock().unwrap(), 0);
+        watcher.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drop_stops_supervisors() {
+        let restarts = Arc::new(Mutex::new(0));
+        let restarts_clone = restarts.clone();
+        let mut watcher = ProcessWatcher::new();
+        watcher
+            .supervise(
+                "true",
+                Vec::new(),
+                RestartPolicy::Always {
+                    max_retries: u32::MAX,
+                    backoff: Duration::from_millis(10),
+                },
+                Box::new(move |_, _| *restarts_clone.lock().unwrap() += 1),
+            )
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(watcher);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let restarts_after_drop = *restarts.lock().unwrap();
+        assert!(restarts_after_drop > 0);
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(*restarts.lock().unwrap(), restarts_after_drop);
+    }
+
+    #[test]
+    fn test_restart_delay() {
+        let policy = RestartPolicy::Always {
+            max_retries: 10,
+            backoff: Duration::from_secs(1),
+        };
+        assert_eq!(policy.restart_delay(true, 0), Some(Duration::from_secs(1)));
+        assert_eq!(policy.restart_delay(false, 3), Some(Duration::from_secs(8)));
+        assert_eq!(policy.restart_delay(false, 9), Some(MAX_RESTART_BACKOFF));
+        assert_eq!(policy.restart_delay(false, 10), None);
+        assert_eq!(RestartPolicy::Never.restart_delay(false, 0), None);
+    }
 }
@@ -1,6 +1,34 @@
 //! The `progressmeter` module provides a simple progress meter for tracking the progress of a
 //! long-running task.
 
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The number of recent updates the progress meter uses to compute the rate of progress.
+const RATE_WINDOW: usize = 10;
+
+/// The `ProgressSnapshot` struct describes the state of a progress meter at the time of an update.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressSnapshot {
+    /// The current number of units that the progress meter has tracked.
+    pub current: u64,
+
+    /// The total number of units that the progress meter is tracking.
+    pub total: u64,
+
+    /// The rate of progress in units per second.
+    pub rate: f64,
+}
+
+/// The `UpdateCallback` type is a type alias for a boxed closure that receives a snapshot of the
+/// progress meter whenever the meter makes progress.
+pub type UpdateCallback = Box<dyn Fn(&ProgressSnapshot) + Send + Sync + 'static>;
+
+/// The `Clock` type is a type alias for a boxed closure that returns the current time. The progress
+/// meter uses the clock to timestamp updates when computing the rate of progress.
+type Clock = Box<dyn Fn() -> Instant + Send + Sync + 'static>;
+
 /// The `Notifier` type is a type alias for a boxed closure that receives notifications when the
 /// progress meter makes progress towards the total goal. The value passed to the function represents
 /// the current percent completed out of 100.
@@ -25,18 +53,28 @@ pub struct ProgressMeter {
 
     /// The last percentage that was notified to the user.
     last_percent: u8,
+
+    /// The clock used to timestamp updates.
+    clock: Clock,
+
+    /// The timestamped unit counts of the most recent updates, oldest first.
+    samples: VecDeque<(Instant, u64)>,
+
+    /// The callback that receives a snapshot of the meter when the meter makes progress.
+    update_callback: Option<UpdateCallback>,
+
+    /// The shortest time between two calls to the update callback.
+    update_interval: Duration,
+
+    /// The time of the last call to the update callback.
+    last_update: Option<Instant>,
 }
 
 impl ProgressMeter {
     /// Create a new `ProgressMeter` with the default notifier function and a total number of units
     /// to track of 1.
     pub fn new() -> ProgressMeter {
-        ProgressMeter {
-            notifier: Box::new(|_| {}),
-            meter_total: 1,
-            meter_current: 0,
-            last_percent: 0,
-        }
+        ProgressMeter::new_with_notifier_and_size(Box::new(|_| {}), 1)
     }
 
     /// Create a new `ProgressMeter` with the given notifier function and total number of units to
@@ -53,17 +91,26 @@ impl ProgressMeter {
     ///
     /// A new `ProgressMeter` with the given notifier function and total number of units to track.
     pub fn new_with_notifier_and_size(notifier: Notifier, meter_total: u64) -> ProgressMeter {
-        ProgressMeter {
+        let mut meter = ProgressMeter {
             notifier,
             meter_total,
             meter_current: 0,
             last_percent: 0,
-        }
+            clock: Box::new(Instant::now),
+            samples: VecDeque::with_capacity(RATE_WINDOW),
+            update_callback: None,
+            update_interval: Duration::ZERO,
+            last_update: None,
+        };
+        meter.record_sample();
+        meter
     }
 
     /// Increment the progress meter by one unit.
     pub fn increment(&mut self) {
         self.meter_current += 1;
+        self.record_sample();
+        self.send_update();
     }
 
     /// Increment the progress meter by the given amount.
@@ -73,11 +120,15 @@ impl ProgressMeter {
     /// * `increment` - The amount to increment the progress meter by.
     pub fn increment_by(&mut self, increment: u64) {
         self.meter_current += increment;
+        self.record_sample();
+        self.send_update();
     }
 
-    /// Reset the progress meter to zero.
+    /// Reset the progress meter to zero. Resetting the meter also resets the rate of progress.
     pub fn reset(&mut self) {
         self.meter_current = 0;
+        self.samples.clear();
+        self.record_sample();
     }
 
     /// Notify the user of the current progress of the progress meter. If the force flag is set to
@@ -114,6 +165,8 @@ impl ProgressMeter {
         } else {
             self.meter_current = current;
         }
+        self.record_sample();
+        self.send_update();
     }
 
     /// Set the total number of units that the progress meter is tracking.
@@ -121,6 +174,16 @@ impl ProgressMeter {
         self.meter_total = total;
     }
 
+    /// Get the current number of units that the progress meter has tracked.
+    pub fn current(&self) -> u64 {
+        self.meter_current
+    }
+
+    /// Get the total number of units that the progress meter is tracking.
+    pub fn total(&self) -> u64 {
+        self.meter_total
+    }
+
     /// Set the notifier function that receives calls when the progress meter makes progress towards
     /// the total goal (in percentage terms). The value passed to the function represents the current
     /// percent completed out of 100.
@@ -133,11 +196,238 @@ impl ProgressMeter {
     pub fn set_notifier(&mut self, notifier: Notifier) {
         self.notifier = notifier;
     }
+
+    /// Set the callback that receives a snapshot of the meter whenever the meter makes progress.
+    ///
+    /// The callback runs on the thread that updates the meter, while that thread has access to
+    /// the meter. When the meter is shared in an `Arc<Mutex<ProgressMeter>>`, the callback runs
+    /// with the mutex locked, so it must not try to lock the meter itself. Everything the
+    /// callback needs is in the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback to call with a snapshot of the meter.
+    pub fn on_update(&mut self, callback: UpdateCallback) {
+        self.update_callback = Some(callback);
+        self.last_update = None;
+    }
+
+    /// Set the shortest time between two calls to the update callback. Updates that arrive
+    /// sooner do not call the callback, except for the update that completes the meter. The
+    /// default interval is zero, which calls the callback on every update.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The shortest time between two calls to the update callback.
+    pub fn set_update_interval(&mut self, interval: Duration) {
+        self.update_interval = interval;
+    }
+
+    /// Get a snapshot of the current state of the meter.
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        ProgressSnapshot {
+            current: self.meter_current,
+            total: self.meter_total,
+            rate: self.rate(),
+        }
+    }
+
+    /// Get the rate of progress in units per second.
+    ///
+    /// The rate is averaged over the most recent updates to the meter so that it does not jump
+    /// around with every update.
+    ///
+    /// # Returns
+    ///
+    /// The rate of progress, or 0.0 if the meter has not seen enough updates to tell.
+    pub fn rate(&self) -> f64 {
+        let (Some((first_time, first_units)), Some((last_time, last_units))) =
+            (self.samples.front(), self.samples.back())
+        else {
+            return 0.0;
+        };
+
+        let elapsed = last_time.duration_since(*first_time).as_secs_f64();
+        if elapsed <= 0.0 || last_units <= first_units {
+            return 0.0;
+        }
+
+        (last_units - first_units) as f64 / elapsed
+    }
+
+    /// Get the estimated time remaining until the meter reaches its total.
+    ///
+    /// # Returns
+    ///
+    /// The estimated time remaining based on the current rate of progress, or `None` if the
+    /// meter is not making progress.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.meter_total.saturating_sub(self.meter_current);
+        if remaining == 0 {
+            return Some(Duration::ZERO);
+        }
+
+        let rate = self.rate();
+        if rate <= 0.0 {
+            return None;
+        }
+
+        Some(Duration::from_secs_f64(remaining as f64 / rate))
+    }
+
+    /// Call the update callback with a snapshot of the meter, unless the callback was called less
+    /// than the update interval ago and the meter is not yet complete.
+    fn send_update(&mut self) {
+        if self.update_callback.is_none() {
+            return;
+        }
+
+        let now = (self.clock)();
+        let complete = self.meter_current >= self.meter_total;
+        let throttled = self
+            .last_update
+            .is_some_and(|last_update| now.duration_since(last_update) < self.update_interval);
+        if throttled && !complete {
+            return;
+        }
+
+        let snapshot = self.snapshot();
+        if let Some(callback) = &self.update_callback {
+            callback(&snapshot);
+        }
+        self.last_update = Some(now);
+    }
+
+    /// Record the current unit count in the sample buffer, dropping the oldest sample if the
+    /// buffer is full.
+    fn record_sample(&mut self) {
+        if self.samples.len() == RATE_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(((self.clock)(), self.meter_current));
+    }
+
+    /// Replace the clock used to timestamp updates. Replacing the clock resets the rate of
+    /// progress.
+    #[cfg(test)]
+    fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+        self.samples.clear();
+        self.record_sample();
+    }
+}
+
+/// The `CompositeProgressMeter` struct tracks the overall progress of a task made up of many
+/// sub-tasks, such as hashing every file in a directory in parallel. Each sub-task gets its own
+/// child `ProgressMeter`, shared in an `Arc<Mutex<ProgressMeter>>` so that it can be handed to the
+/// hash functions. The composite meter reports the sum of its children.
+///
+/// ```
+/// use foundation::progressmeter::CompositeProgressMeter;
+///
+/// let mut composite = CompositeProgressMeter::new();
+/// let first = composite.add_child(100);
+/// let second = composite.add_child(300);
+///
+/// first.lock().unwrap().set_current(100);
+/// second.lock().unwrap().set_current(100);
+/// assert_eq!(composite.percent(), 50.0);
+/// ```
+#[derive(Default)]
+pub struct CompositeProgressMeter {
+    /// The child progress meters, one per sub-task.
+    children: Vec<Arc<Mutex<ProgressMeter>>>,
+}
+
+impl CompositeProgressMeter {
+    /// Create a new `CompositeProgressMeter` with no children.
+    pub fn new() -> CompositeProgressMeter {
+        CompositeProgressMeter {
+            children: Vec::new(),
+        }
+    }
+
+    /// Add a child progress meter for a sub-task. The total of the composite meter grows by the
+    /// total of the child.
+    ///
+    /// # Arguments
+    ///
+    /// * `total` - The total number of units that the sub-task will track.
+    ///
+    /// # Returns
+    ///
+    /// The child progress meter to hand to the sub-task.
+    pub fn add_child(&mut self, total: u64) -> Arc<Mutex<ProgressMeter>> {
+        let child = Arc::new(Mutex::new(ProgressMeter::new_with_notifier_and_size(
+            Box::new(|_| {}),
+            total,
+        )));
+        self.children.push(child.clone());
+        child
+    }
+
+    /// Get the number of child progress meters.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Check whether the composite meter has no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Get the current number of units tracked by all the children.
+    pub fn current(&self) -> u64 {
+        self.children
+            .iter()
+            .filter_map(|child| child.lock().ok().map(|child| child.current()))
+            .sum()
+    }
+
+    /// Get the total number of units the children are tracking.
+    pub fn total(&self) -> u64 {
+        self.children
+            .iter()
+            .filter_map(|child| child.lock().ok().map(|child| child.total()))
+            .sum()
+    }
+
+    /// Get the overall progress of the children as a percentage.
+    ///
+    /// # Returns
+    ///
+    /// The percentage of the total units tracked so far, from 0.0 to 100.0. A composite meter
+    /// with nothing to track reports 0.0.
+    pub fn percent(&self) -> f64 {
+        // Read each child once so that its current and total come from the same moment.
+        let (current, total) = self
+            .children
+            .iter()
+            .filter_map(|child| {
+                child
+                    .lock()
+                    .ok()
+                    .map(|child| (child.current().min(child.total()), child.total()))
+            })
+            .fold(
+                (0u64, 0u64),
+                |(current, total), (child_current, child_total)| {
+                    (current + child_current, total + child_total)
+                },
+            );
+
+        if total == 0 {
+            return 0.0;
+        }
+
+        (current as f64 / total as f64) * 100.0
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[tokio::test]
     async fn test_progress_meter() {
@@ -200,4 +490,135 @@ mod tests {
         progress_meter.notify(false);
         assert_eq!(rx.recv().await.unwrap(), 50);
     }
+
+    // Create a clock that only moves when the test advances it.
+    fn fake_clock() -> (Clock, Arc<Mutex<Instant>>) {
+        let now = Arc::new(Mutex::new(Instant::now()));
+        let clock_now = now.clone();
+        (Box::new(move || *clock_now.lock().unwrap()), now)
+    }
+
+    fn advance(now: &Arc<Mutex<Instant>>, duration: Duration) {
+        let mut now = now.lock().unwrap();
+        *now += duration;
+    }
+
+    #[test]
+    fn test_progress_meter_rate_and_eta() {
+        let mut progress_meter = ProgressMeter::new_with_notifier_and_size(Box::new(|_| {}), 1000);
+        let (clock, now) = fake_clock();
+        progress_meter.set_clock(clock);
+
+        assert_eq!(progress_meter.rate(), 0.0);
+        assert_eq!(progress_meter.eta(), None);
+
+        // 10 units every 100ms is 100 units per second.
+        for _ in 0..20 {
+            advance(&now, Duration::from_millis(100));
+            progress_meter.increment_by(10);
+        }
+
+        assert!((progress_meter.rate() - 100.0).abs() < 0.01);
+        let eta = progress_meter.eta().unwrap();
+        assert!((eta.as_secs_f64() - 8.0).abs() < 0.01);
+
+        // A burst of fast updates only moves the rate part way, because the rate is averaged
+        // over the recent updates.
+        advance(&now, Duration::from_millis(10));
+        progress_meter.increment_by(10);
+        let rate = progress_meter.rate();
+        assert!(rate > 100.0 && rate < 120.0);
+
+        progress_meter.set_current(1000);
+        assert_eq!(progress_meter.eta(), Some(Duration::ZERO));
+
+        progress_meter.reset();
+        assert_eq!(progress_meter.rate(), 0.0);
+    }
+
+    #[test]
+    fn test_progress_meter_update_callback() {
+        let mut progress_meter = ProgressMeter::new_with_notifier_and_size(Box::new(|_| {}), 2000);
+        let (clock, now) = fake_clock();
+        progress_meter.set_clock(clock);
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let last_snapshot = Arc::new(Mutex::new(None));
+        let count_c = count.clone();
+        let last_snapshot_c = last_snapshot.clone();
+        progress_meter.on_update(Box::new(move |snapshot| {
+            count_c.fetch_add(1, Ordering::SeqCst);
+            *last_snapshot_c.lock().unwrap() = Some(*snapshot);
+        }));
+        progress_meter.set_update_interval(Duration::from_millis(100));
+
+        // 1000 updates over one second with a 100ms interval.
+        for _ in 0..1000 {
+            advance(&now, Duration::from_millis(1));
+            progress_meter.increment();
+        }
+
+        let calls = count.load(Ordering::SeqCst);
+        assert!((9..=11).contains(&calls), "callback called {} times", calls);
+
+        // The update that completes the meter is never throttled.
+        advance(&now, Duration::from_millis(1));
+        progress_meter.set_current(2000);
+        assert_eq!(count.load(Ordering::SeqCst), calls + 1);
+        let snapshot = last_snapshot.lock().unwrap().unwrap();
+        assert_eq!(snapshot.current, 2000);
+        assert_eq!(snapshot.total, 2000);
+        assert!(snapshot.rate > 0.0);
+    }
+
+    #[test]
+    fn test_progress_meter_update_callback_shared() {
+        let progress_meter = Arc::new(Mutex::new(ProgressMeter::new_with_notifier_and_size(
+            Box::new(|_| {}),
+            10,
+        )));
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_c = count.clone();
+        progress_meter.lock().unwrap().on_update(Box::new(move |_| {
+            count_c.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        let meter = progress_meter.clone();
+        std::thread::spawn(move || {
+            for _ in 0..10 {
+                meter.lock().unwrap().increment();
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(count.load(Ordering::SeqCst), 10);
+    }
+
+    #[test]
+    fn test_composite_progress_meter() {
+        let mut composite = CompositeProgressMeter::new();
+        assert!(composite.is_empty());
+        assert_eq!(composite.percent(), 0.0);
+
+        let first = composite.add_child(100);
+        let second = composite.add_child(200);
+        let third = composite.add_child(700);
+        assert_eq!(composite.len(), 3);
+        assert_eq!(composite.total(), 1000);
+
+        first.lock().unwrap().set_current(50);
+        second.lock().unwrap().increment_by(200);
+        assert_eq!(composite.current(), 250);
+        assert_eq!(composite.percent(), 25.0);
+
+        // Completing a child shows up right away.
+        third.lock().unwrap().set_current(700);
+        assert_eq!(composite.percent(), 95.0);
+
+        // Adding a child grows the total.
+        composite.add_child(1000);
+        assert_eq!(composite.total(), 2000);
+        assert_eq!(composite.percent(), 47.5);
+    }
 }
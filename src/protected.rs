@@ -1,6 +1,15 @@
 //! The `protected` module provides a simple wrapper around `Arc<Mutex<T>>` to allow for safe
 //! sharing of data between threads.
+//!
+//! With the `serde` feature, `Protected<T>` implements `Serialize` and `Deserialize` when `T`
+//! does. Serializing locks the inner value and serializes it transparently, so a `Protected<T>`
+//! has the same representation as `T`. If the lock is poisoned, serialization fails with a serde
+//! error rather than panicking. Deserializing creates a new, unshared lock around the decoded
+//! value.
 
+use crate::sync::error::SyncError;
+#[cfg(feature = "serde")]
+use serde::{ser::Error, Deserialize, Deserializer, Serialize, Serializer};
 use std::sync::{Arc, Mutex, MutexGuard};
 
 /// A simple wrapper around `Arc<Mutex<T>>` to allow for safe sharing of data between threads.
@@ -38,6 +47,82 @@ impl<T> Protected<T> {
     pub fn lock(&self) -> MutexGuard<T> {
         self.item.lock().unwrap()
     }
+
+    /// Lock the protected item for the duration of a call. This is where `with`, `with_mut`, and
+    /// `map` apply the poison-handling policy: a poisoned lock is reported as an error instead of
+    /// handing out data a panicking thread may have left half-updated.
+    fn try_lock(&self) -> Result<MutexGuard<'_, T>, SyncError> {
+        self.item.lock().map_err(|_| SyncError::Poisoned)
+    }
+
+    /// Call a function with a reference to the protected item.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function to call while the item is locked.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`, or `SyncError::Poisoned` if a thread panicked while holding the
+    /// lock.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, SyncError> {
+        let item = self.try_lock()?;
+        Ok(f(&item))
+    }
+
+    /// Call a function with a mutable reference to the protected item.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function to call while the item is locked.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`, or `SyncError::Poisoned` if a thread panicked while holding the
+    /// lock.
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, SyncError> {
+        let mut item = self.try_lock()?;
+        Ok(f(&mut item))
+    }
+
+    /// Derive a new protected value from the protected item.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function that computes the new value from the locked item.
+    ///
+    /// # Returns
+    ///
+    /// A new `Protected<U>`, independent of this one, holding the value returned by `f`, or
+    /// `SyncError::Poisoned` if a thread panicked while holding the lock.
+    pub fn map<U>(&self, f: impl FnOnce(&T) -> U) -> Result<Protected<U>, SyncError> {
+        self.with(f).map(Protected::new)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for Protected<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.item.lock() {
+            Ok(item) => item.serialize(serializer),
+            Err(_) => Err(S::Error::custom(
+                "unable to serialize protected value: lock poisoned",
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Protected<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Protected::new)
+    }
 }
 
 #[cfg(test)]
@@ -50,4 +135,114 @@ mod tests {
         let protected_int = Protected::new(32);
         assert_eq!(protected_int.lock().deref(), &32);
     }
+
+    #[test]
+    fn test_with() {
+        let protected = Protected::new(vec![1, 2, 3]);
+        assert_eq!(protected.with(|v| v.len()).unwrap(), 3);
+        assert_eq!(protected.with(|v| v.iter().sum::<i32>()).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_with_mut() {
+        let protected = Protected::new(vec![1, 2, 3]);
+        let shared = protected.clone();
+
+        let length = protected
+            .with_mut(|v| {
+                v.push(4);
+                v.len()
+            })
+            .unwrap();
+        assert_eq!(length, 4);
+
+        // The mutation is visible through every handle.
+        assert_eq!(shared.with(|v| v.clone()).unwrap(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_map() {
+        let protected = Protected::new(vec![1, 2, 3]);
+        let doubled = protected
+            .map(|v| v.iter().map(|i| i * 2).collect::<Vec<i32>>())
+            .unwrap();
+        assert_eq!(*doubled.lock(), vec![2, 4, 6]);
+
+        // The derived value does not share the original lock.
+        doubled.with_mut(|v| v.clear()).unwrap();
+        assert_eq!(*protected.lock(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_combinators_poisoned() {
+        let protected = Protected::new(vec![1, 2, 3]);
+
+        let poison_protected = protected.clone();
+        let _ = std::thread::spawn(move || {
+            poison_protected
+                .with_mut(|v| {
+                    v.push(4);
+                    panic!("poison the lock");
+                })
+                .unwrap();
+        })
+        .join();
+
+        assert_eq!(protected.with(|v| v.len()), Err(SyncError::Poisoned));
+        assert_eq!(protected.with_mut(|v| v.clear()), Err(SyncError::Poisoned));
+        assert!(matches!(
+            protected.map(|v| v.len()),
+            Err(SyncError::Poisoned)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use crate::network::networkconfiguration::{AddressMode, NetworkConfiguration};
+        use crate::network::networkinterface::NetworkInterface;
+        use crate::network::route::Route;
+
+        let interface = NetworkInterface {
+            name: "eth0".to_string(),
+            ..NetworkInterface::default()
+        };
+
+        let configuration = NetworkConfiguration {
+            address_mode: AddressMode::Static,
+            interface,
+            enabled: true,
+            routes: vec![Route::default_route(
+                "192.168.1.1".parse().unwrap(),
+                Some(100),
+            )],
+            mtu: Some(1400),
+            ..NetworkConfiguration::default()
+        };
+
+        let protected = Protected::new(configuration.clone());
+        let json = serde_json::to_string(&protected).unwrap();
+
+        // The lock is transparent in the serialized form.
+        assert_eq!(json, serde_json::to_string(&configuration).unwrap());
+
+        let decoded: Protected<NetworkConfiguration> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*decoded.lock(), configuration);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_poisoned() {
+        let protected = Protected::new(32);
+
+        let poison_protected = protected.clone();
+        let _ = std::thread::spawn(move || {
+            let _item = poison_protected.lock();
+            panic!("poison the lock");
+        })
+        .join();
+
+        let error = serde_json::to_string(&protected).unwrap_err();
+        assert!(error.to_string().contains("lock poisoned"));
+    }
 }
@@ -1,7 +1,36 @@
 //! The `shell` module contains code for interacting with a shell sub-process.
 
 use crate::error::FoundationError;
-use std::process::{Child, Command, Output};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How often `execute_with_timeout` checks whether the command has finished.
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The `CommandOutput` struct holds the captured output of a command run by
+/// `Shell::execute_async`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// The output the command wrote to stdout.
+    pub stdout: String,
+
+    /// The output the command wrote to stderr.
+    pub stderr: String,
+
+    /// The exit code of the command, or -1 if the command was ended by a signal.
+    pub status: i32,
+}
+
+/// The `StreamLine` enum is a line of output from a command run by `Shell::execute_streaming`,
+/// tagged with the stream the line came from. The line does not include the line ending.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamLine {
+    Stdout(String),
+    Stderr(String),
+}
 
 /// The `Shell` struct represents a shell sub-process.
 pub struct Shell {}
@@ -22,17 +51,9 @@ impl Shell {
         arguments: Vec<String>,
     ) -> Result<Output, FoundationError> {
         let args: Vec<&str> = arguments.iter().map(|s| s.as_str()).collect();
-        let output = if cfg!(target_os = "windows") {
-            Command::new("cmd")
-                .arg("/C")
-                .arg(command)
-                .args(args.iter().map(|arg| arg.to_string()))
-                .output()
-        } else {
-            Command::new(command)
-                .args(args.iter().map(|arg| arg.to_string()))
-                .output()
-        };
+        let output = base_command(command)
+            .args(args.iter().map(|arg| arg.to_string()))
+            .output();
 
         match output {
             Ok(o) => Ok(o),
@@ -56,6 +77,167 @@ impl Shell {
         }
     }
 
+    /// Executes a command with the given arguments, killing the command if it runs longer than
+    /// `timeout`.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to execute.
+    /// * `arguments` - The arguments to pass to the command.
+    /// * `timeout` - The longest time to let the command run.
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the stdout output, the stderr output, and a flag that is `true` if the
+    /// command was killed because it timed out. As with `execute`, the stdout output is `None` if
+    /// the command failed, and both outputs are `None` if the command could not be run or timed
+    /// out.
+    pub fn execute_with_timeout(
+        command: &str,
+        arguments: Vec<String>,
+        timeout: Duration,
+    ) -> (Option<String>, Option<String>, bool) {
+        let child = base_command(command)
+            .args(arguments)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_) => return (None, None, false),
+        };
+
+        // Read the output on separate threads so that a command writing more than the pipe
+        // buffer holds does not block waiting for us.
+        let stdout_reader = read_pipe(child.stdout.take());
+        let stderr_reader = read_pipe(child.stderr.take());
+
+        let deadline = Instant::now() + timeout;
+        let mut timed_out = false;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) => {
+                    if Instant::now() >= deadline {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        timed_out = true;
+                        break None;
+                    }
+                    thread::sleep(TIMEOUT_POLL_INTERVAL);
+                }
+                Err(_) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break None;
+                }
+            }
+        };
+
+        // Processes started by a killed command may still hold the output pipes open, so do not
+        // wait for the output of a command that timed out.
+        if timed_out {
+            return (None, None, true);
+        }
+
+        let stdout = collect_pipe(stdout_reader);
+        let stderr = collect_pipe(stderr_reader);
+
+        match status {
+            Some(status) if status.success() => (Some(stdout), Some(stderr), false),
+            _ => (None, Some(stderr), false),
+        }
+    }
+
+    /// Executes a command with the given arguments without blocking the current thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to execute.
+    /// * `arguments` - The arguments to pass to the command.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the captured output and exit code of the command if the command ran,
+    /// or a `FoundationError` if the command could not be run. A command that runs but fails
+    /// still returns its output, with a non-zero status.
+    pub async fn execute_async(
+        command: &str,
+        arguments: Vec<String>,
+    ) -> Result<CommandOutput, FoundationError> {
+        let output = base_async_command(command).args(arguments).output().await?;
+
+        Ok(CommandOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            status: output.status.code().unwrap_or(-1),
+        })
+    }
+
+    /// Executes a command with the given arguments, passing each line of output to a callback
+    /// as soon as the command writes it.
+    ///
+    /// Lines from the same stream arrive in order. Lines from stdout and stderr are interleaved
+    /// in roughly the order the command wrote them, but the order between the two streams is not
+    /// guaranteed.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to execute.
+    /// * `arguments` - The arguments to pass to the command.
+    /// * `on_line` - The callback to call with each line of output.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the exit code of the command, or -1 if the command was ended by a
+    /// signal, or a `FoundationError` if the command could not be run.
+    pub fn execute_streaming<F>(
+        command: &str,
+        arguments: Vec<String>,
+        mut on_line: F,
+    ) -> Result<i32, FoundationError>
+    where
+        F: FnMut(StreamLine),
+    {
+        let mut child = base_command(command)
+            .args(arguments)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Read each pipe on its own thread and send the lines back to this thread, which calls
+        // the callback. The channel closes once both pipes have closed.
+        let (line_sender, line_receiver) = mpsc::channel::<StreamLine>();
+        if let Some(stdout) = child.stdout.take() {
+            let sender = line_sender.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if sender.send(StreamLine::Stdout(line)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let sender = line_sender.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    if sender.send(StreamLine::Stderr(line)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(line_sender);
+
+        for line in line_receiver {
+            on_line(line);
+        }
+
+        let status = child.wait()?;
+        Ok(status.code().unwrap_or(-1))
+    }
+
     /// Runs a command with the given arguments. The command will launch as a child
     /// of the currently running process.
     ///
@@ -69,17 +251,9 @@ impl Shell {
     /// A `Child` object on success or a `FoundationError` if an error occurs.
     pub fn spawn_command(command: &str, arguments: Vec<String>) -> Result<Child, FoundationError> {
         let args: Vec<&str> = arguments.iter().map(|s| s.as_str()).collect();
-        let output = if cfg!(target_os = "windows") {
-            Command::new("cmd")
-                .arg("/C")
-                .arg(command)
-                .args(args.iter().map(|arg| arg.to_string()))
-                .spawn()
-        } else {
-            Command::new(command)
-                .args(args.iter().map(|arg| arg.to_string()))
-                .spawn()
-        };
+        let output = base_command(command)
+            .args(args.iter().map(|arg| arg.to_string()))
+            .spawn();
 
         match output {
             Ok(child) => Ok(child),
@@ -87,3 +261,249 @@ impl Shell {
         }
     }
 }
+
+// Create the `Command` that runs `command`. On Windows the command runs through `cmd /C`.
+fn base_command(command: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut shell_command = Command::new("cmd");
+        shell_command.arg("/C").arg(command);
+        shell_command
+    } else {
+        Command::new(command)
+    }
+}
+
+// The tokio counterpart of `base_command`.
+fn base_async_command(command: &str) -> tokio::process::Command {
+    if cfg!(target_os = "windows") {
+        let mut shell_command = tokio::process::Command::new("cmd");
+        shell_command.arg("/C").arg(command);
+        shell_command
+    } else {
+        tokio::process::Command::new(command)
+    }
+}
+
+// Read everything from a child process pipe on a separate thread.
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> Option<thread::JoinHandle<Vec<u8>>> {
+    pipe.map(|mut pipe| {
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = pipe.read_to_end(&mut output);
+            output
+        })
+    })
+}
+
+// Wait for a pipe reader thread and convert what it read to a string.
+fn collect_pipe(reader: Option<thread::JoinHandle<Vec<u8>>>) -> String {
+    reader
+        .and_then(|reader| reader.join().ok())
+        .map(|output| String::from_utf8_lossy(&output).to_string())
+        .unwrap_or_default()
+}
+
+/// The `ShellCommand` struct builds a command to run with environment variables and input.
+///
+/// ```no_run
+/// use foundation::shell::ShellCommand;
+///
+/// let output = ShellCommand::new("openssl")
+///     .args(["x509", "-noout", "-subject"])
+///     .env("PATH", "/usr/bin")
+///     .stdin("-----BEGIN CERTIFICATE-----\n...")
+///     .run()
+///     .unwrap();
+/// println!("{}", output.stdout);
+/// ```
+pub struct ShellCommand {
+    // The command to run.
+    command: String,
+
+    // The arguments to pass to the command.
+    arguments: Vec<String>,
+
+    // The environment variables to set for the command.
+    environment: Vec<(String, String)>,
+
+    // The bytes to write to the command's stdin.
+    input: Option<Vec<u8>>,
+}
+
+impl ShellCommand {
+    /// Create a new `ShellCommand` object.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command to run.
+    pub fn new(command: &str) -> ShellCommand {
+        ShellCommand {
+            command: command.to_string(),
+            arguments: Vec::new(),
+            environment: Vec::new(),
+            input: None,
+        }
+    }
+
+    /// Add an argument to the command.
+    pub fn arg(mut self, argument: &str) -> ShellCommand {
+        self.arguments.push(argument.to_string());
+        self
+    }
+
+    /// Add several arguments to the command.
+    pub fn args<I, S>(mut self, arguments: I) -> ShellCommand
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.arguments.extend(
+            arguments
+                .into_iter()
+                .map(|argument| argument.as_ref().to_string()),
+        );
+        self
+    }
+
+    /// Set an environment variable for the command. The command also inherits the environment
+    /// of the current process.
+    pub fn env(mut self, key: &str, value: &str) -> ShellCommand {
+        self.environment.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Set the bytes to write to the command's stdin. Stdin is closed once the bytes are
+    /// written. Without input the command's stdin is empty.
+    pub fn stdin<B: Into<Vec<u8>>>(mut self, input: B) -> ShellCommand {
+        self.input = Some(input.into());
+        self
+    }
+
+    /// Run the command and wait for it to finish.
+    ///
+    /// # Returns
+    ///
+    /// A Result containing the captured output and exit code of the command if the command ran,
+    /// or a `FoundationError` if the command could not be run. A command that runs but fails
+    /// still returns its output, with a non-zero status.
+    pub fn run(self) -> Result<CommandOutput, FoundationError> {
+        let mut child = base_command(&self.command)
+            .args(&self.arguments)
+            .envs(self.environment)
+            .stdin(if self.input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Write the input and read the output on separate threads so that a command that
+        // writes a lot of output before it finishes reading its input cannot deadlock with us.
+        let writer = match (child.stdin.take(), self.input) {
+            (Some(mut stdin), Some(input)) => Some(thread::spawn(move || {
+                // The command may exit without reading all of its input, which is not an error
+                // for us. Dropping stdin at the end of the thread closes it.
+                let _ = stdin.write_all(&input);
+            })),
+            _ => None,
+        };
+        let stdout_reader = read_pipe(child.stdout.take());
+        let stderr_reader = read_pipe(child.stderr.take());
+
+        let status = child.wait()?;
+        if let Some(writer) = writer {
+            let _ = writer.join();
+        }
+
+        Ok(CommandOutput {
+            stdout: collect_pipe(stdout_reader),
+            stderr: collect_pipe(stderr_reader),
+            status: status.code().unwrap_or(-1),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_with_timeout() {
+        let start = Instant::now();
+        let (stdout, _, timed_out) =
+            Shell::execute_with_timeout("sleep", vec!["5".to_string()], Duration::from_millis(200));
+        assert!(timed_out);
+        assert!(stdout.is_none());
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        let (stdout, _, timed_out) =
+            Shell::execute_with_timeout("echo", vec!["hello".to_string()], Duration::from_secs(5));
+        assert!(!timed_out);
+        assert_eq!(stdout, Some("hello\n".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_command_stdin() {
+        let output = ShellCommand::new("cat")
+            .stdin("hello\nworld\n")
+            .run()
+            .unwrap();
+        assert_eq!(output.stdout, "hello\nworld\n");
+        assert_eq!(output.status, 0);
+
+        // Input larger than a pipe buffer must not deadlock against the output.
+        let input = "0123456789abcdef\n".repeat(64 * 1024);
+        let output = ShellCommand::new("cat").stdin(input.clone()).run().unwrap();
+        assert_eq!(output.stdout, input);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_command_env() {
+        let output = ShellCommand::new("sh")
+            .args(["-c", "echo $FOUNDATION_SHELL_TEST"])
+            .env("FOUNDATION_SHELL_TEST", "value")
+            .run()
+            .unwrap();
+        assert_eq!(output.stdout, "value\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_streaming() {
+        let script = "echo out1; echo err1 >&2; echo out2; echo err2 >&2; echo out3";
+        let mut stdout_lines = Vec::new();
+        let mut stderr_lines = Vec::new();
+
+        let status =
+            Shell::execute_streaming("sh", vec!["-c".to_string(), script.to_string()], |line| {
+                match line {
+                    StreamLine::Stdout(line) => stdout_lines.push(line),
+                    StreamLine::Stderr(line) => stderr_lines.push(line),
+                }
+            })
+            .unwrap();
+
+        assert_eq!(status, 0);
+        assert_eq!(stdout_lines, vec!["out1", "out2", "out3"]);
+        assert_eq!(stderr_lines, vec!["err1", "err2"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_async() {
+        let output = Shell::execute_async("echo", vec!["hello".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(output.stdout, "hello\n");
+        assert_eq!(output.stderr, "");
+        assert_eq!(output.status, 0);
+
+        let output = Shell::execute_async("false", vec![]).await.unwrap();
+        assert_ne!(output.status, 0);
+    }
+}
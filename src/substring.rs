@@ -1,5 +1,5 @@
 //! The `substring` module provides the Substring trait that allows for extracting a substring
-//! from a string.
+//! from a string, and functions for searching and fuzzy matching strings.
 
 pub trait Substring {
     /// Extracts a substring from the string.
@@ -23,6 +23,143 @@ impl Substring for String {
     }
 }
 
+/// Find every non-overlapping occurrence of a needle in a haystack.
+///
+/// # Arguments
+///
+/// * `haystack` - The string to search.
+/// * `needle` - The string to search for. An empty needle matches nothing.
+///
+/// # Returns
+///
+/// The byte offsets of the matches in `haystack`, in order. Each offset is a char boundary, so
+/// it can be used to slice `haystack`.
+pub fn find_all(haystack: &str, needle: &str) -> Vec<usize> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    haystack
+        .match_indices(needle)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Check whether the lowercase form of `text` starts with the lowercase characters in `needle`,
+/// with the match ending on a character boundary of `text`.
+fn starts_with_lowercase(text: &str, needle: &[char]) -> bool {
+    let mut position = 0;
+    for c in text.chars() {
+        if position == needle.len() {
+            return true;
+        }
+        for lowercase in c.to_lowercase() {
+            if position >= needle.len() || needle[position] != lowercase {
+                return false;
+            }
+            position += 1;
+        }
+    }
+    position == needle.len()
+}
+
+/// Find the first occurrence of a needle in a haystack, ignoring case.
+///
+/// The comparison lowercases both strings with Unicode case rules, so it handles letters outside
+/// ASCII, such as accented characters.
+///
+/// # Arguments
+///
+/// * `haystack` - The string to search.
+/// * `needle` - The string to search for.
+///
+/// # Returns
+///
+/// The byte offset of the first match in `haystack`, or `None` if there is no match. The offset
+/// is a char boundary of `haystack`, even when lowercasing changes the length of a character.
+/// Like `str::find`, an empty needle matches at offset 0.
+pub fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return Some(0);
+    }
+
+    haystack
+        .char_indices()
+        .map(|(index, _)| index)
+        .find(|index| starts_with_lowercase(&haystack[*index..], &needle))
+}
+
+/// Check whether a haystack contains a needle, ignoring case.
+///
+/// # Arguments
+///
+/// * `haystack` - The string to search.
+/// * `needle` - The string to search for.
+///
+/// # Returns
+///
+/// True if `haystack` contains `needle` when case is ignored, false otherwise.
+pub fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    find_ignore_case(haystack, needle).is_some()
+}
+
+/// Compute the Levenshtein edit distance between two strings.
+///
+/// The distance counts Unicode scalar values rather than bytes, so a substituted accented
+/// character counts as one edit.
+///
+/// # Arguments
+///
+/// * `a` - The first string.
+/// * `b` - The second string.
+///
+/// # Returns
+///
+/// The minimum number of single character insertions, deletions, and substitutions needed to
+/// turn `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Find the candidate closest to an input string, such as the interface name or configuration
+/// key the user most likely meant.
+///
+/// A candidate only matches if its edit distance from `input` is at most a third of the length
+/// of `input` (and at least 1), so unrelated strings are not suggested.
+///
+/// # Arguments
+///
+/// * `input` - The string to match.
+/// * `candidates` - The strings to choose from.
+///
+/// # Returns
+///
+/// The candidate with the smallest edit distance from `input`, or `None` if no candidate is
+/// within the threshold. If several candidates have the same distance, the first one wins.
+pub fn closest_match<'a>(input: &str, candidates: &'a [&str]) -> Option<&'a str> {
+    let threshold = (input.chars().count() / 3).max(1);
+
+    candidates
+        .iter()
+        .map(|candidate| (levenshtein(input, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -33,4 +170,77 @@ mod tests {
         assert_eq!(s.substring(0, 5), "Hello");
         assert_eq!(s.substring(7, 12), "world");
     }
+
+    #[test]
+    fn test_find_all() {
+        assert_eq!(find_all("one two one two one", "one"), vec![0, 8, 16]);
+
+        // Matches do not overlap.
+        assert_eq!(find_all("aaaa", "aa"), vec![0, 2]);
+        assert_eq!(find_all("aaa", "aa"), vec![0]);
+
+        assert!(find_all("hello", "").is_empty());
+        assert!(find_all("hello", "world").is_empty());
+
+        // The offsets are byte offsets on char boundaries.
+        let haystack = "café crème café";
+        let offsets = find_all(haystack, "café");
+        assert_eq!(offsets, vec![0, 13]);
+        assert_eq!(&haystack[offsets[1]..], "café");
+    }
+
+    #[test]
+    fn test_find_ignore_case() {
+        assert_eq!(find_ignore_case("Hello, World!", "world"), Some(7));
+        assert_eq!(find_ignore_case("Hello, World!", "planet"), None);
+        assert_eq!(find_ignore_case("Hello", ""), Some(0));
+
+        // The match spans accented characters.
+        let haystack = "Crème Brûlée";
+        let offset = find_ignore_case(haystack, "BRÛLÉE").unwrap();
+        assert_eq!(offset, 7);
+        assert_eq!(&haystack[offset..], "Brûlée");
+
+        // Lowercasing 'İ' produces two characters, but the offset still lands on a char
+        // boundary of the original string.
+        let haystack = "İstanbul İzmir";
+        let offset = find_ignore_case(haystack, "i\u{307}zmir").unwrap();
+        assert_eq!(&haystack[offset..], "İzmir");
+    }
+
+    #[test]
+    fn test_contains_ignore_case() {
+        assert!(contains_ignore_case("Wireless LAN", "wireless lan"));
+        assert!(contains_ignore_case("ÉCOLE", "école"));
+        assert!(!contains_ignore_case("wlan0", "eth0"));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("wlan0", "wlan0"), 0);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+        assert_eq!(levenshtein("wlen0", "wlan0"), 1);
+
+        // Distances count characters, not bytes.
+        assert_eq!(levenshtein("café", "cafe"), 1);
+        assert_eq!(levenshtein("naïve", "naive"), 1);
+    }
+
+    #[test]
+    fn test_closest_match() {
+        let candidates = ["eth0", "wlan0", "wlan1", "lo"];
+        assert_eq!(closest_match("wlen0", &candidates), Some("wlan0"));
+        assert_eq!(closest_match("eth1", &candidates), Some("eth0"));
+        assert_eq!(closest_match("wlan1", &candidates), Some("wlan1"));
+
+        // Ties go to the first candidate.
+        assert_eq!(closest_match("wlan2", &candidates), Some("wlan0"));
+
+        assert_eq!(closest_match("bridge", &candidates), None);
+        assert_eq!(closest_match("wlan0", &[]), None);
+    }
 }
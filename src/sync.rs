@@ -1,4 +1,7 @@
-//! The `sync` module provides a multi producer, multi consumer messaging channel.
+//! The `sync` module provides a multi producer, multi consumer messaging channel and locking
+//! primitives.
 
 pub mod error;
 pub mod mpmc;
+pub mod shared_rw;
+pub mod timed_mutex;
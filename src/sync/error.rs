@@ -1,4 +1,4 @@
-//! The `error` module provides error types used by channels in the sync module.
+//! The `error` module provides error types used by channels and locks in the sync module.
 
 use std::error::Error;
 use std::fmt;
@@ -20,3 +20,28 @@ impl<T> fmt::Display for SendError<T> {
 }
 
 impl<T> Error for SendError<T> {}
+
+/// Error returned when acquiring a lock fails.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SyncError {
+    /// The lock did not become available before the timeout expired.
+    Timeout,
+
+    /// The lock is held by another thread and the caller asked not to block.
+    WouldBlock,
+
+    /// A thread panicked while holding the lock.
+    Poisoned,
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Timeout => write!(f, "timed out waiting for lock"),
+            SyncError::WouldBlock => write!(f, "lock is held by another thread"),
+            SyncError::Poisoned => write!(f, "lock poisoned"),
+        }
+    }
+}
+
+impl Error for SyncError {}
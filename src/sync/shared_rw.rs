@@ -0,0 +1,138 @@
+//! The `shared_rw` module provides a wrapper around `Arc<RwLock<T>>` for sharing read-heavy data
+//! between threads.
+
+use log::error;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A wrapper around `Arc<RwLock<T>>` that lets many readers access the data at the same time
+/// while writers get exclusive access.
+///
+/// If a thread panics while holding the lock, the lock is poisoned. Rather than panicking in
+/// every later caller, `SharedRw` logs the poisoning, clears it, and hands out the data as the
+/// panicking thread left it.
+#[derive(Debug, Default)]
+pub struct SharedRw<T> {
+    /// The `Arc<RwLock<T>>` that holds the data.
+    item: Arc<RwLock<T>>,
+}
+
+impl<T> Clone for SharedRw<T> {
+    fn clone(&self) -> Self {
+        SharedRw {
+            item: self.item.clone(),
+        }
+    }
+}
+
+impl<T> SharedRw<T> {
+    /// Create a new `SharedRw<T>` with the given item.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The item to share.
+    ///
+    /// # Returns
+    ///
+    /// A new `SharedRw<T>` containing the given item.
+    pub fn new(item: T) -> SharedRw<T> {
+        SharedRw {
+            item: Arc::new(RwLock::new(item)),
+        }
+    }
+
+    /// Lock the shared item for reading. Other readers may hold the lock at the same time.
+    ///
+    /// # Returns
+    ///
+    /// A `RwLockReadGuard<T>` that allows read access to the shared item.
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.item.read().unwrap_or_else(|poisoned| {
+            error!("SharedRw lock poisoned, recovering data for reading");
+            self.item.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+
+    /// Lock the shared item for writing. The caller has exclusive access until the guard drops.
+    ///
+    /// # Returns
+    ///
+    /// A `RwLockWriteGuard<T>` that allows write access to the shared item.
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.item.write().unwrap_or_else(|poisoned| {
+            error!("SharedRw lock poisoned, recovering data for writing");
+            self.item.clear_poison();
+            poisoned.into_inner()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_concurrent_readers_and_writer() {
+        // The writer keeps both values equal, so a reader that sees them differ has observed a
+        // partial write.
+        let shared = SharedRw::new((0u64, 0u64));
+
+        let writer_shared = shared.clone();
+        let writer = thread::spawn(move || {
+            for _ in 0..1000 {
+                let mut guard = writer_shared.write();
+                guard.0 += 1;
+                thread::yield_now();
+                guard.1 += 1;
+            }
+        });
+
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let reader_shared = shared.clone();
+                thread::spawn(move || {
+                    let mut last = 0;
+                    for _ in 0..1000 {
+                        let guard = reader_shared.read();
+                        assert_eq!(guard.0, guard.1);
+                        assert!(guard.0 >= last);
+                        last = guard.0;
+                    }
+                })
+            })
+            .collect();
+
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        assert_eq!(*shared.read(), (1000, 1000));
+    }
+
+    #[test]
+    fn test_multiple_readers_at_once() {
+        let shared = SharedRw::new(5);
+        let first = shared.read();
+        let second = shared.read();
+        assert_eq!(*first + *second, 10);
+    }
+
+    #[test]
+    fn test_poison_recovery() {
+        let shared = SharedRw::new(vec![1, 2]);
+
+        let poison_shared = shared.clone();
+        let _ = thread::spawn(move || {
+            let mut guard = poison_shared.write();
+            guard.push(3);
+            panic!("poison the lock");
+        })
+        .join();
+
+        assert_eq!(*shared.read(), vec![1, 2, 3]);
+        shared.write().push(4);
+        assert_eq!(*shared.read(), vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,152 @@
+//! The `timed_mutex` module provides a mutex wrapper whose lock operation can give up after a
+//! timeout instead of blocking indefinitely.
+
+use crate::sync::error::SyncError;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The longest time `lock_timeout` sleeps between attempts to take the lock.
+const MAX_RETRY_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A `std::sync::Mutex` wrapper that supports acquiring the lock with a timeout.
+#[derive(Debug, Default)]
+pub struct TimedMutex<T> {
+    inner: Mutex<T>,
+}
+
+impl<T> TimedMutex<T> {
+    /// Create a new `TimedMutex` holding a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value protected by the mutex.
+    ///
+    /// # Returns
+    ///
+    /// The new `TimedMutex`.
+    pub fn new(value: T) -> TimedMutex<T> {
+        TimedMutex {
+            inner: Mutex::new(value),
+        }
+    }
+
+    /// Acquire the lock, blocking until it is available.
+    ///
+    /// # Returns
+    ///
+    /// A guard for the locked value, or `SyncError::Poisoned` if a thread panicked while holding
+    /// the lock.
+    pub fn lock(&self) -> Result<MutexGuard<'_, T>, SyncError> {
+        self.inner.lock().map_err(|_| SyncError::Poisoned)
+    }
+
+    /// Try to acquire the lock without blocking.
+    ///
+    /// # Returns
+    ///
+    /// A guard for the locked value, `SyncError::WouldBlock` if another thread holds the lock, or
+    /// `SyncError::Poisoned` if a thread panicked while holding the lock.
+    pub fn try_lock(&self) -> Result<MutexGuard<'_, T>, SyncError> {
+        match self.inner.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::WouldBlock) => Err(SyncError::WouldBlock),
+            Err(TryLockError::Poisoned(_)) => Err(SyncError::Poisoned),
+        }
+    }
+
+    /// Acquire the lock, giving up if it is not available within a duration.
+    ///
+    /// The method retries the lock with short sleeps between attempts, so the caller may wait
+    /// slightly longer than `duration` before getting a timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The maximum time to wait for the lock.
+    ///
+    /// # Returns
+    ///
+    /// A guard for the locked value, `SyncError::Timeout` if the lock did not become available in
+    /// time, or `SyncError::Poisoned` if a thread panicked while holding the lock.
+    pub fn lock_timeout(&self, duration: Duration) -> Result<MutexGuard<'_, T>, SyncError> {
+        let deadline = Instant::now() + duration;
+        let mut interval = Duration::from_micros(50);
+
+        loop {
+            match self.try_lock() {
+                Err(SyncError::WouldBlock) => {}
+                result => return result,
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(SyncError::Timeout);
+            }
+
+            thread::sleep(interval.min(deadline - now));
+            interval = (interval * 2).min(MAX_RETRY_INTERVAL);
+        }
+    }
+
+    /// Consume the mutex and return the value it protects.
+    ///
+    /// # Returns
+    ///
+    /// The protected value, or `SyncError::Poisoned` if a thread panicked while holding the lock.
+    pub fn into_inner(self) -> Result<T, SyncError> {
+        self.inner.into_inner().map_err(|_| SyncError::Poisoned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_lock_timeout() {
+        let mutex = Arc::new(TimedMutex::new(0));
+        let (locked_sender, locked_receiver) = mpsc::channel();
+        let (release_sender, release_receiver) = mpsc::channel::<()>();
+
+        let holder_mutex = mutex.clone();
+        let holder = thread::spawn(move || {
+            let mut guard = holder_mutex.lock().unwrap();
+            *guard += 1;
+            locked_sender.send(()).unwrap();
+            release_receiver.recv().unwrap();
+        });
+
+        locked_receiver.recv().unwrap();
+
+        let start = Instant::now();
+        let result = mutex.lock_timeout(Duration::from_millis(50));
+        assert!(matches!(result, Err(SyncError::Timeout)));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(matches!(mutex.try_lock(), Err(SyncError::WouldBlock)));
+
+        release_sender.send(()).unwrap();
+        holder.join().unwrap();
+
+        let guard = mutex.lock_timeout(Duration::from_millis(50)).unwrap();
+        assert_eq!(*guard, 1);
+    }
+
+    #[test]
+    fn test_lock_timeout_poisoned() {
+        let mutex = Arc::new(TimedMutex::new(0));
+
+        let poison_mutex = mutex.clone();
+        let _ = thread::spawn(move || {
+            let _guard = poison_mutex.lock().unwrap();
+            panic!("poison the mutex");
+        })
+        .join();
+
+        assert!(matches!(
+            mutex.lock_timeout(Duration::from_millis(10)),
+            Err(SyncError::Poisoned)
+        ));
+    }
+}
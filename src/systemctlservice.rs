@@ -2,12 +2,62 @@
 //! machine.
 
 use crate::error::FoundationError;
-use std::process::Command;
+use std::process::{Command, Output};
 
-/// The `SystemCTLService` object is used to start, stop, and restart services on a Linux machine.
+/// The `CommandRunner` type is a type alias for a boxed closure that runs `systemctl` with the
+/// given arguments and returns its output.
+type CommandRunner = Box<dyn Fn(&[&str]) -> std::io::Result<Output> + Send + Sync + 'static>;
+
+/// Run `systemctl` with the given arguments.
+fn run_systemctl(arguments: &[&str]) -> std::io::Result<Output> {
+    Command::new("systemctl").args(arguments).output()
+}
+
+/// The `ServiceStatus` enum represents the runtime state of a service as reported by
+/// `systemctl is-active`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ServiceStatus {
+    /// The service is running.
+    Active,
+
+    /// The service is not running.
+    Inactive,
+
+    /// The service stopped because it failed.
+    Failed,
+
+    /// The state could not be determined, or the service is changing state.
+    Unknown,
+}
+
+impl ServiceStatus {
+    /// Parse the state printed by `systemctl is-active`.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state printed by `systemctl is-active`.
+    ///
+    /// # Returns
+    ///
+    /// The matching `ServiceStatus`. Transitional states such as `activating` map to `Unknown`.
+    fn from_is_active(state: &str) -> ServiceStatus {
+        match state.trim() {
+            "active" | "reloading" => ServiceStatus::Active,
+            "inactive" => ServiceStatus::Inactive,
+            "failed" => ServiceStatus::Failed,
+            _ => ServiceStatus::Unknown,
+        }
+    }
+}
+
+/// The `SystemCTLService` object is used to start, stop, restart, enable, and disable services on a
+/// Linux machine.
 pub struct SystemCTLService {
     /// The name of the service.
     service_name: String,
+
+    /// The function used to run `systemctl`.
+    runner: CommandRunner,
 }
 
 impl SystemCTLService {
@@ -17,7 +67,32 @@ impl SystemCTLService {
     ///
     /// * `service_name` - The name of the service.
     pub fn new(service_name: String) -> SystemCTLService {
-        SystemCTLService { service_name }
+        SystemCTLService {
+            service_name,
+            runner: Box::new(run_systemctl),
+        }
+    }
+
+    /// Replace the function used to run `systemctl`.
+    #[cfg(test)]
+    fn set_runner(&mut self, runner: CommandRunner) {
+        self.runner = runner;
+    }
+
+    /// Run a `systemctl` command on the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The `systemctl` command and options to run, such as `["start"]`. The service
+    ///   name is appended to the arguments.
+    ///
+    /// # Returns
+    ///
+    /// The output of `systemctl`, whether or not it succeeded.
+    fn run(&self, command: &[&str]) -> Result<Output, FoundationError> {
+        let mut arguments = command.to_vec();
+        arguments.push(&self.service_name);
+        Ok((self.runner)(&arguments)?)
     }
 
     /// Start the service.
@@ -26,10 +101,7 @@ impl SystemCTLService {
     ///
     /// Returns `Ok(())` if the service was started successfully, otherwise returns a `FoundationError`.
     pub fn start(&self) -> Result<(), FoundationError> {
-        let output = Command::new("systemctl")
-            .arg("start")
-            .arg(&self.service_name)
-            .output()?;
+        let output = self.run(&["start"])?;
         if !output.status.success() {
             return Err(FoundationError::OperationFailed(format!(
                 "Failed to start service: {}",
@@ -45,10 +117,7 @@ impl SystemCTLService {
     ///
     /// Returns `Ok(())` if the service was stopped successfully, otherwise returns a `FoundationError`.
     pub fn stop(&self) -> Result<(), FoundationError> {
-        let output = Command::new("systemctl")
-            .arg("stop")
-            .arg(&self.service_name)
-            .output()?;
+        let output = self.run(&["stop"])?;
         if !output.status.success() {
             return Err(FoundationError::OperationFailed(format!(
                 "Failed to stop service: {}",
@@ -64,10 +133,7 @@ impl SystemCTLService {
     ///
     /// Returns `Ok(())` if the service was restarted successfully, otherwise returns a `FoundationError`.
     pub fn restart(&self) -> Result<(), FoundationError> {
-        let output = Command::new("systemctl")
-            .arg("restart")
-            .arg(&self.service_name)
-            .output()?;
+        let output = self.run(&["restart"])?;
         if !output.status.success() {
             return Err(FoundationError::OperationFailed(format!(
                 "Failed to restart service: {}",
@@ -76,4 +142,265 @@ impl SystemCTLService {
         }
         Ok(())
     }
+
+    /// Run `systemctl enable` or `systemctl disable` on the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - Either `enable` or `disable`.
+    /// * `now` - Whether to pass `--now` so the service also starts or stops immediately.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the command succeeded, otherwise returns a `FoundationError`.
+    fn set_enabled(&self, command: &str, now: bool) -> Result<(), FoundationError> {
+        let output = if now {
+            self.run(&[command, "--now"])?
+        } else {
+            self.run(&[command])?
+        };
+        if !output.status.success() {
+            return Err(FoundationError::OperationFailed(format!(
+                "Failed to {} service: {}",
+                command,
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Enable the service so that it starts at boot. The service's current state is unchanged.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the service was enabled successfully, otherwise returns a `FoundationError`.
+    pub fn enable(&self) -> Result<(), FoundationError> {
+        self.set_enabled("enable", false)
+    }
+
+    /// Enable the service so that it starts at boot, and start it now.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the service was enabled and started successfully, otherwise returns a
+    /// `FoundationError`.
+    pub fn enable_now(&self) -> Result<(), FoundationError> {
+        self.set_enabled("enable", true)
+    }
+
+    /// Disable the service so that it no longer starts at boot. The service's current state is
+    /// unchanged.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the service was disabled successfully, otherwise returns a `FoundationError`.
+    pub fn disable(&self) -> Result<(), FoundationError> {
+        self.set_enabled("disable", false)
+    }
+
+    /// Disable the service so that it no longer starts at boot, and stop it now.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the service was disabled and stopped successfully, otherwise returns a
+    /// `FoundationError`.
+    pub fn disable_now(&self) -> Result<(), FoundationError> {
+        self.set_enabled("disable", true)
+    }
+
+    /// Run a `systemctl` query command and return the state it prints. Query commands exit with
+    /// a non-zero status for states such as `inactive`, so the exit status alone is not an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The query command to run, such as `is-active`.
+    ///
+    /// # Returns
+    ///
+    /// The state printed by `systemctl`, or a `FoundationError` if it printed nothing.
+    fn query(&self, command: &str) -> Result<String, FoundationError> {
+        let output = self.run(&[command])?;
+        let state = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if state.is_empty() {
+            return Err(FoundationError::OperationFailed(format!(
+                "Failed to query service with {}: {}",
+                command,
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(state)
+    }
+
+    /// Check whether the service is running.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if `systemctl is-active` reports the service as active, `Ok(false)` if it
+    /// reports any other state, otherwise returns a `FoundationError`.
+    pub fn is_active(&self) -> Result<bool, FoundationError> {
+        Ok(ServiceStatus::from_is_active(&self.query("is-active")?) == ServiceStatus::Active)
+    }
+
+    /// Check whether the service starts at boot.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if `systemctl is-enabled` reports the service as enabled, `Ok(false)` if
+    /// it reports any other state such as `disabled` or `masked`, otherwise returns a
+    /// `FoundationError`.
+    pub fn is_enabled(&self) -> Result<bool, FoundationError> {
+        let state = self.query("is-enabled")?;
+        Ok(matches!(state.as_str(), "enabled" | "enabled-runtime"))
+    }
+
+    /// Get the runtime state of the service.
+    ///
+    /// # Returns
+    ///
+    /// The `ServiceStatus` reported by `systemctl is-active`, or `ServiceStatus::Unknown` if
+    /// `systemctl` could not be queried.
+    pub fn status(&self) -> ServiceStatus {
+        match self.query("is-active") {
+            Ok(state) => ServiceStatus::from_is_active(&state),
+            Err(_) => ServiceStatus::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+    use std::sync::{Arc, Mutex};
+
+    // Create a service whose runner records its arguments and returns canned output.
+    fn fake_service(
+        code: i32,
+        stdout: &'static str,
+        stderr: &'static str,
+    ) -> (SystemCTLService, Arc<Mutex<Vec<Vec<String>>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let runner_calls = calls.clone();
+        let mut service = SystemCTLService::new("dnsmasq".to_string());
+        service.set_runner(Box::new(move |arguments: &[&str]| {
+            runner_calls
+                .lock()
+                .unwrap()
+                .push(arguments.iter().map(|a| a.to_string()).collect());
+            Ok(Output {
+                status: ExitStatus::from_raw(code << 8),
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: stderr.as_bytes().to_vec(),
+            })
+        }));
+        (service, calls)
+    }
+
+    #[test]
+    fn test_status() {
+        let cases = [
+            (0, "active\n", ServiceStatus::Active),
+            (0, "reloading\n", ServiceStatus::Active),
+            (3, "inactive\n", ServiceStatus::Inactive),
+            (3, "failed\n", ServiceStatus::Failed),
+            (3, "activating\n", ServiceStatus::Unknown),
+            (4, "", ServiceStatus::Unknown),
+        ];
+
+        for (code, stdout, expected) in cases {
+            let (service, calls) = fake_service(code, stdout, "");
+            assert_eq!(service.status(), expected, "output {:?}", stdout);
+            assert_eq!(
+                *calls.lock().unwrap(),
+                vec![vec!["is-active".to_string(), "dnsmasq".to_string()]]
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_active() {
+        let (service, _) = fake_service(0, "active\n", "");
+        assert!(service.is_active().unwrap());
+
+        let (service, _) = fake_service(3, "inactive\n", "");
+        assert!(!service.is_active().unwrap());
+
+        let (service, _) = fake_service(3, "failed\n", "");
+        assert!(!service.is_active().unwrap());
+
+        let (service, _) = fake_service(1, "", "Failed to connect to bus");
+        assert!(service.is_active().is_err());
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        let (service, calls) = fake_service(0, "enabled\n", "");
+        assert!(service.is_enabled().unwrap());
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![vec!["is-enabled".to_string(), "dnsmasq".to_string()]]
+        );
+
+        let (service, _) = fake_service(0, "enabled-runtime\n", "");
+        assert!(service.is_enabled().unwrap());
+
+        let (service, _) = fake_service(1, "disabled\n", "");
+        assert!(!service.is_enabled().unwrap());
+
+        let (service, _) = fake_service(1, "masked\n", "");
+        assert!(!service.is_enabled().unwrap());
+
+        let (service, _) = fake_service(1, "", "Failed to get unit file state");
+        assert!(service.is_enabled().is_err());
+    }
+
+    // Convert a list of arguments to the form recorded by the fake runner.
+    fn arguments(arguments: &[&str]) -> Vec<Vec<String>> {
+        vec![arguments.iter().map(|a| a.to_string()).collect()]
+    }
+
+    #[test]
+    fn test_enable_and_disable() {
+        let (service, calls) = fake_service(0, "", "");
+        service.enable().unwrap();
+        assert_eq!(*calls.lock().unwrap(), arguments(&["enable", "dnsmasq"]));
+
+        let (service, calls) = fake_service(0, "", "");
+        service.enable_now().unwrap();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            arguments(&["enable", "--now", "dnsmasq"])
+        );
+
+        let (service, calls) = fake_service(0, "", "");
+        service.disable().unwrap();
+        assert_eq!(*calls.lock().unwrap(), arguments(&["disable", "dnsmasq"]));
+
+        let (service, calls) = fake_service(0, "", "");
+        service.disable_now().unwrap();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            arguments(&["disable", "--now", "dnsmasq"])
+        );
+    }
+
+    #[test]
+    fn test_enable_failure() {
+        let (service, _) = fake_service(1, "", "Unit file dnsmasq.service does not exist.");
+        match service.enable() {
+            Err(FoundationError::OperationFailed(message)) => {
+                assert!(message.contains("Unit file dnsmasq.service does not exist."))
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let (service, _) = fake_service(1, "", "Access denied");
+        match service.disable_now() {
+            Err(FoundationError::OperationFailed(message)) => {
+                assert!(message.contains("Access denied"))
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
 }
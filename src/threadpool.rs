@@ -1,17 +1,28 @@
 //! The `threadpool` module provides an asynchronous thread pool for running tasks.
 
 use crate::error::FoundationError;
-use crate::result::DynResult;
+use crate::result::{DynResult, DynResultError};
+use futures::FutureExt;
 use log::{debug, error};
 use std::collections::HashMap;
 use std::future::Future;
+use std::marker::PhantomData;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 use std::pin::Pin;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll};
 
 use tokio::{
-    spawn,
-    sync::mpsc::{error::TryRecvError, unbounded_channel, UnboundedSender},
-    task::JoinHandle,
+    runtime::{Handle, RuntimeFlavor},
+    select, spawn,
+    sync::mpsc::{
+        channel, error::TryRecvError, unbounded_channel, Receiver, Sender, UnboundedReceiver,
+        UnboundedSender,
+    },
+    sync::oneshot,
+    sync::Notify,
+    task::{block_in_place, JoinHandle},
 };
 
 /// The `Task` type is the basic closure type that encapsulates the work to be done in the thread pool.
@@ -24,6 +35,12 @@ pub type Task = Pin<Box<dyn Future<Output = DynResult<()>> + Send + Sync + 'stat
 pub struct ThreadJob {
     // The list of tasks to be executed in the thread pool thread.
     job_list: Vec<Task>,
+
+    // The sender for reporting the job's result to a `JobHandle`, if one was requested.
+    result_sender: Option<oneshot::Sender<DynResult<()>>>,
+
+    // The label used to identify the job in logs and in its `JobHandle`.
+    name: Option<String>,
 }
 
 impl ThreadJob {
@@ -31,9 +48,37 @@ impl ThreadJob {
     pub fn new() -> ThreadJob {
         ThreadJob {
             job_list: Vec::new(),
+            result_sender: None,
+            name: None,
         }
     }
 
+    /// Create a new `ThreadJob` object with a name. The worker includes the name in its log
+    /// messages about the job, and a `JobHandle` for the job reports it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the job.
+    ///
+    /// # Returns
+    ///
+    /// A new `ThreadJob` object.
+    pub fn with_name(name: impl Into<String>) -> ThreadJob {
+        ThreadJob {
+            name: Some(name.into()),
+            ..ThreadJob::new()
+        }
+    }
+
+    /// Get the name of the job.
+    ///
+    /// # Returns
+    ///
+    /// The name given to `with_name`, or `None` if the job has no name.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
     /// Add a task to the `ThreadJob` object.
     pub fn add_task(&mut self, task: Task) {
         self.job_list.push(task);
@@ -51,6 +96,296 @@ impl ThreadJob {
     }
 }
 
+/// The `JobHandle` type lets the caller wait for the result of a single job added to a
+/// `ThreadPool` with `ThreadPool::add_job_with_handle`.
+pub struct JobHandle {
+    // The receiver for the job's result.
+    result_receiver: oneshot::Receiver<DynResult<()>>,
+
+    // The name of the job, if it has one.
+    name: Option<String>,
+}
+
+impl JobHandle {
+    /// Get the name of the job.
+    ///
+    /// # Returns
+    ///
+    /// The name the job was created with by `ThreadJob::with_name`, or `None` if the job has no
+    /// name.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Wait for the job to finish.
+    ///
+    /// # Returns
+    ///
+    /// The result of the job. If a task in the job fails, the job stops at that task and the
+    /// task's error is returned. If the job never runs to completion, for example because the
+    /// pool was stopped, an error is returned.
+    pub async fn wait(self) -> DynResult<()> {
+        match self.result_receiver.await {
+            Ok(result) => result,
+            Err(_) => Err(Box::new(FoundationError::ThreadTaskError(
+                "job ended without reporting a result".to_string(),
+            ))),
+        }
+    }
+}
+
+// The `JobSender` type is the sending half of the queue of jobs waiting for the scheduler. The
+// queue is unbounded unless the pool was created with `ThreadPool::with_capacity`.
+enum JobSender {
+    Unbounded(UnboundedSender<ThreadJob>),
+    Bounded(Sender<ThreadJob>),
+}
+
+impl JobSender {
+    /// Send a job without waiting. Sending to a full bounded queue fails.
+    pub fn send(&self, job: ThreadJob) -> Result<(), FoundationError> {
+        match self {
+            JobSender::Unbounded(sender) => sender
+                .send(job)
+                .map_err(|e| FoundationError::TokioMpscSend(e.to_string())),
+            JobSender::Bounded(sender) => sender
+                .try_send(job)
+                .map_err(|e| FoundationError::TokioMpscSend(e.to_string())),
+        }
+    }
+
+    /// Send a job, waiting for room in the queue if the queue is bounded and full.
+    pub async fn send_async(&self, job: ThreadJob) -> Result<(), FoundationError> {
+        match self {
+            JobSender::Unbounded(sender) => sender
+                .send(job)
+                .map_err(|e| FoundationError::TokioMpscSend(e.to_string())),
+            JobSender::Bounded(sender) => sender
+                .send(job)
+                .await
+                .map_err(|e| FoundationError::TokioMpscSend(e.to_string())),
+        }
+    }
+}
+
+// The `JobReceiver` type is the receiving half of the queue of jobs waiting for the scheduler.
+enum JobReceiver {
+    Unbounded(UnboundedReceiver<ThreadJob>),
+    Bounded(Receiver<ThreadJob>),
+}
+
+impl JobReceiver {
+    /// Wait for the next job. Returns `None` once the queue is closed and empty.
+    pub async fn recv(&mut self) -> Option<ThreadJob> {
+        match self {
+            JobReceiver::Unbounded(receiver) => receiver.recv().await,
+            JobReceiver::Bounded(receiver) => receiver.recv().await,
+        }
+    }
+
+    /// Take the next job if one is waiting.
+    pub fn try_recv(&mut self) -> Option<ThreadJob> {
+        match self {
+            JobReceiver::Unbounded(receiver) => receiver.try_recv().ok(),
+            JobReceiver::Bounded(receiver) => receiver.try_recv().ok(),
+        }
+    }
+}
+
+/// The `Priority` type is the scheduling priority of a job added to a `ThreadPool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+// The `JobSenders` type holds the sending half of the job queue for each priority.
+struct JobSenders {
+    high: JobSender,
+    normal: JobSender,
+    low: JobSender,
+}
+
+impl JobSenders {
+    /// Get the sender for the queue of the given priority.
+    pub fn get(&self, priority: Priority) -> &JobSender {
+        match priority {
+            Priority::High => &self.high,
+            Priority::Normal => &self.normal,
+            Priority::Low => &self.low,
+        }
+    }
+}
+
+// The `JobReceivers` type holds the receiving half of the job queue for each priority.
+struct JobReceivers {
+    high: JobReceiver,
+    normal: JobReceiver,
+    low: JobReceiver,
+}
+
+impl JobReceivers {
+    /// Wait for the next job, taking jobs from higher priority queues first. Returns `None` once
+    /// every queue is closed and empty.
+    pub async fn recv(&mut self) -> Option<(Priority, ThreadJob)> {
+        select! {
+            biased;
+            Some(job) = self.high.recv() => Some((Priority::High, job)),
+            Some(job) = self.normal.recv() => Some((Priority::Normal, job)),
+            Some(job) = self.low.recv() => Some((Priority::Low, job)),
+            else => None,
+        }
+    }
+
+    /// Take the next waiting job with a priority higher than `priority`, if there is one.
+    pub fn try_recv_above(&mut self, priority: Priority) -> Option<ThreadJob> {
+        if priority < Priority::High {
+            if let Some(job) = self.high.try_recv() {
+                return Some(job);
+            }
+        }
+        if priority < Priority::Normal {
+            if let Some(job) = self.normal.try_recv() {
+                return Some(job);
+            }
+        }
+        None
+    }
+}
+
+// Create a job queue for each priority, using `make_channel` to create each queue.
+fn job_channels<F>(make_channel: F) -> (JobSenders, JobReceivers)
+where
+    F: Fn() -> (JobSender, JobReceiver),
+{
+    let (high_sender, high_receiver) = make_channel();
+    let (normal_sender, normal_receiver) = make_channel();
+    let (low_sender, low_receiver) = make_channel();
+    (
+        JobSenders {
+            high: high_sender,
+            normal: normal_sender,
+            low: low_sender,
+        },
+        JobReceivers {
+            high: high_receiver,
+            normal: normal_receiver,
+            low: low_receiver,
+        },
+    )
+}
+
+/// The `PoolMetrics` type is a snapshot of how busy a `ThreadPool` is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// The number of workers the pool has started.
+    pub current_workers: WorkerId,
+
+    /// The maximum number of workers the pool may start.
+    pub max_workers: WorkerId,
+
+    /// The number of started workers waiting for a job.
+    pub idle_workers: usize,
+
+    /// The approximate number of jobs added to the pool that have not yet been handed to a
+    /// worker.
+    pub queued_jobs: usize,
+}
+
+// The `JobTracker` type keeps count of the jobs submitted to the pool that have not yet finished.
+// The count includes jobs still waiting in the scheduler or worker queues as well as jobs that are
+// currently running. The tracker also counts the jobs that have not yet been handed to a worker and
+// the workers that are waiting for a job.
+struct JobTracker {
+    // The number of outstanding jobs.
+    outstanding: AtomicUsize,
+
+    // The number of jobs that have not yet been handed to a worker.
+    queued: AtomicUsize,
+
+    // The number of worker ids sitting in the idle channel.
+    idle_workers: AtomicUsize,
+
+    // Notifies waiters when the outstanding count drops to zero.
+    notify: Notify,
+}
+
+impl JobTracker {
+    pub fn new() -> JobTracker {
+        JobTracker {
+            outstanding: AtomicUsize::new(0),
+            queued: AtomicUsize::new(0),
+            idle_workers: AtomicUsize::new(0),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Record that a new job has been submitted to the pool.
+    pub fn job_added(&self) {
+        self.outstanding.fetch_add(1, Ordering::SeqCst);
+        self.queued.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Record that the scheduler has handed a job to a worker.
+    pub fn job_dispatched(&self) {
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Record that a job was dropped before reaching a worker.
+    pub fn job_dropped(&self) {
+        self.job_dispatched();
+        self.job_finished();
+    }
+
+    /// Record that a worker is about to send its id to the idle channel.
+    pub fn worker_idle(&self) {
+        self.idle_workers.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Record that a worker id has been taken out of the idle channel, or that it never made
+    /// it into the channel.
+    pub fn worker_busy(&self) {
+        self.idle_workers.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Forget all idle workers, used when the workers have been shut down.
+    pub fn clear_idle_workers(&self) {
+        self.idle_workers.store(0, Ordering::SeqCst);
+    }
+
+    /// The number of jobs that have not yet been handed to a worker.
+    pub fn queued_jobs(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// The number of workers waiting for a job.
+    pub fn idle_workers(&self) -> usize {
+        self.idle_workers.load(Ordering::SeqCst)
+    }
+
+    /// Record that a job has finished (or has been dropped) and wake any waiters if that was
+    /// the last outstanding job.
+    pub fn job_finished(&self) {
+        if self.outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.notify.notify_waiters();
+        }
+    }
+
+    /// Wait until there are no outstanding jobs.
+    pub async fn wait(&self) {
+        loop {
+            // Create the notification future before checking the count so that we cannot miss
+            // a notification sent between the check and the await.
+            let notified = self.notify.notified();
+            if self.outstanding.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
 // The `WorkerId` type is a unique identifier for a worker in the thread pool.
 pub type WorkerId = u16;
 
@@ -60,8 +395,8 @@ struct Worker {
     // The sender channel for sending jobs to the worker thread.
     job_sender: UnboundedSender<ThreadJob>,
 
-    // The stopper function for stopping the worker thread.
-    stopper: Box<dyn Fn() -> DynResult<()> + Send + Sync + 'static>,
+    // The handle for the worker thread.
+    thread: JoinHandle<DynResult<()>>,
 }
 
 impl Worker {
@@ -73,15 +408,21 @@ impl Worker {
     /// * `idle_sender` - The sender channel for sending idle worker notifications.
     /// The idle worker notifications are just the worker's unique identifier sent back to the
     /// idle channel.
+    /// * `tracker` - The pool's job tracker, notified each time the worker finishes a job.
     ///
     /// # Returns
     ///
     /// A new `Worker` object.
-    pub fn new(id: WorkerId, idle_sender: UnboundedSender<WorkerId>) -> Worker {
+    pub fn new(
+        id: WorkerId,
+        idle_sender: UnboundedSender<WorkerId>,
+        tracker: Arc<JobTracker>,
+    ) -> Worker {
         let (job_sender, mut job_receiver) = unbounded_channel::<ThreadJob>();
 
         let worker_id = id;
         let worker_idle_sender = idle_sender.clone();
+        let worker_tracker = tracker.clone();
 
         let thread: JoinHandle<DynResult<()>> = spawn(async move {
             debug!("Starting thread pool worker {}", worker_id);
@@ -90,17 +431,46 @@ impl Worker {
                 let job = job_receiver.recv().await;
                 if let Some(mut job) = job {
                     loop {
-                        // Execute all the tasks in the job.
+                        if let Some(name) = &job.name {
+                            debug!("Worker {} running job {}", worker_id, name);
+                        }
+
+                        // Execute all the tasks in the job. A failing task abandons the rest of
+                        // the job, but the worker stays alive to run the next job. Panics are
+                        // caught too, so a panicking task does not take the worker down with it.
+                        let mut result: DynResult<()> = Ok(());
                         for task in job.job_list {
-                            match task.await {
-                                Ok(_) => {}
-                                Err(e) => {
-                                    error!("Error executing task in worker {}: {}", worker_id, e);
-                                    break;
+                            let task_result = match AssertUnwindSafe(task).catch_unwind().await {
+                                Ok(task_result) => task_result,
+                                Err(_) => Err(Box::new(FoundationError::ThreadTaskError(
+                                    "task panicked".to_string(),
+                                )) as DynResultError),
+                            };
+                            if let Err(e) = task_result {
+                                match &job.name {
+                                    Some(name) => error!(
+                                        "Error executing task in job {} in worker {}: {}",
+                                        name, worker_id, e
+                                    ),
+                                    None => error!(
+                                        "Error executing task in worker {}: {}",
+                                        worker_id, e
+                                    ),
                                 }
+                                result = Err(e);
+                                break;
                             }
                         }
 
+                        // Report the result to the job's handle. The handle may have been
+                        // dropped, in which case nobody is interested in the result.
+                        if let Some(result_sender) = job.result_sender {
+                            let _ = result_sender.send(result);
+                        }
+
+                        // The job is done, whether it succeeded or not.
+                        worker_tracker.job_finished();
+
                         // Now check to see if we have another job in the channel.
                         match job_receiver.try_recv() {
                             Ok(new_job) => {
@@ -114,7 +484,17 @@ impl Worker {
                                         // We do not have any more jobs, so we are now idle. Send
                                         // the idle channel our id so that the scheduler can schedule
                                         // more work for us when the scheduler has more jobs.
-                                        worker_idle_sender.send(worker_id)?;
+                                        worker_tracker.worker_idle();
+                                        if worker_idle_sender.send(worker_id).is_err() {
+                                            worker_tracker.worker_busy();
+                                            // The scheduler has exited, so no more work will
+                                            // arrive.
+                                            debug!(
+                                                "Worker {} has no scheduler to report to, exiting.",
+                                                id
+                                            );
+                                            return Ok(());
+                                        }
                                         break;
                                     }
                                     TryRecvError::Disconnected => {
@@ -128,27 +508,25 @@ impl Worker {
                             }
                         }
                     }
+                } else {
+                    // The job channel has closed and every queued job has run.
+                    debug!("Worker {} job channel closed, exiting.", worker_id);
+                    return Ok(());
                 }
             }
         });
 
         // TODO: Should we return an error, instead of logging an error?
+        tracker.worker_idle();
         if let Err(e) = idle_sender.send(id) {
+            tracker.worker_busy();
             error!(
                 "Unable to send initial idle message for worker {} to scheduler: {}",
                 id, e
             );
         }
 
-        Worker {
-            job_sender,
-            // We use a closure to stop the thread worker because storing the JoinHandle in the
-            // Worker structure is problematic when we want to call the stopper function.
-            stopper: Box::new(move || {
-                thread.abort();
-                Ok(())
-            }),
-        }
+        Worker { job_sender, thread }
     }
 
     /// Add a job to the worker.
@@ -173,7 +551,22 @@ impl Worker {
     ///
     /// A result indicating success or failure.
     pub fn stop(&mut self) -> DynResult<()> {
-        (self.stopper)()
+        self.thread.abort();
+        Ok(())
+    }
+
+    /// Close the worker's job channel and wait for the worker to finish the jobs already in
+    /// its queue.
+    ///
+    /// # Returns
+    ///
+    /// A result indicating success or failure.
+    pub async fn finish(self) -> DynResult<()> {
+        drop(self.job_sender);
+        match self.thread.await {
+            Ok(result) => result,
+            Err(e) => Err(Box::new(e)),
+        }
     }
 }
 
@@ -202,33 +595,228 @@ impl WorkerManager {
     }
 }
 
+// The `ScopeState` type tracks the tasks spawned in a `Scope` that have not yet been dropped, and
+// the first failure among them.
+struct ScopeState {
+    // The number of spawned tasks that have not been dropped.
+    running: Mutex<usize>,
+
+    // Notified when the running count drops to zero.
+    finished: Condvar,
+
+    // The first error reported by a task, or by adding a task to the pool.
+    error: Mutex<Option<FoundationError>>,
+}
+
+impl ScopeState {
+    pub fn new() -> ScopeState {
+        ScopeState {
+            running: Mutex::new(0),
+            finished: Condvar::new(),
+            error: Mutex::new(None),
+        }
+    }
+
+    /// Record that a task has been spawned.
+    pub fn task_started(&self) {
+        *self.running.lock().unwrap() += 1;
+    }
+
+    /// Record that a task has been dropped, whether or not it ran to completion.
+    pub fn task_finished(&self) {
+        let mut running = self.running.lock().unwrap();
+        *running -= 1;
+        if *running == 0 {
+            self.finished.notify_all();
+        }
+    }
+
+    /// Record a failure, keeping only the first one.
+    pub fn record_error(&self, message: String) {
+        let mut error = self.error.lock().unwrap();
+        if error.is_none() {
+            *error = Some(FoundationError::ThreadTaskError(message));
+        }
+    }
+
+    /// Block the calling thread until every spawned task has been dropped.
+    pub fn wait(&self) {
+        let mut running = self.running.lock().unwrap();
+        while *running > 0 {
+            running = self.finished.wait(running).unwrap();
+        }
+    }
+
+    /// Take the first recorded failure, if any.
+    pub fn take_error(&self) -> Option<FoundationError> {
+        self.error.lock().unwrap().take()
+    }
+}
+
+// The `ScopedTask` type wraps a future spawned in a `Scope`. It records failures in the scope's
+// state, and tells the scope when the future has been dropped so that the scope knows the
+// future no longer borrows any of the scope's data.
+struct ScopedTask<'scope> {
+    // The wrapped future, taken out when the task is dropped.
+    future: Option<Pin<Box<dyn Future<Output = DynResult<()>> + Send + Sync + 'scope>>>,
+
+    // The state of the scope that spawned the task.
+    state: Arc<ScopeState>,
+}
+
+impl Future for ScopedTask<'_> {
+    type Output = DynResult<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let Some(future) = this.future.as_mut() else {
+            return Poll::Ready(Ok(()));
+        };
+        match catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+            Ok(Poll::Ready(Err(e))) => {
+                this.state.record_error(e.to_string());
+                Poll::Ready(Err(e))
+            }
+            Ok(poll) => poll,
+            Err(panic) => {
+                this.state.record_error("task panicked".to_string());
+                resume_unwind(panic)
+            }
+        }
+    }
+}
+
+impl Drop for ScopedTask<'_> {
+    fn drop(&mut self) {
+        // Drop the future before telling the scope, so that its borrows have ended by the time
+        // the scope can return.
+        self.future.take();
+        self.state.task_finished();
+    }
+}
+
+/// The `Scope` type lets tasks borrow data from the caller of `ThreadPool::scope`. See
+/// `ThreadPool::scope` for details.
+pub struct Scope<'scope, 'env: 'scope> {
+    // The pool that runs the scope's tasks.
+    pool: &'scope ThreadPool,
+
+    // The state shared with the scope's tasks.
+    state: Arc<ScopeState>,
+
+    // Make `'scope` and `'env` invariant, as `std::thread::Scope` does.
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    /// Spawn a task in the scope. The task runs in the thread pool as a job of its own and may
+    /// borrow anything that outlives the scope.
+    ///
+    /// If the task fails, or cannot be added to the pool, `ThreadPool::scope` returns an error
+    /// once every task in the scope has finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `future` - The task to run.
+    pub fn spawn<F>(&'scope self, future: F)
+    where
+        F: Future<Output = DynResult<()>> + Send + Sync + 'scope,
+    {
+        self.state.task_started();
+        let task: Pin<Box<dyn Future<Output = DynResult<()>> + Send + Sync + 'scope>> =
+            Box::pin(ScopedTask {
+                future: Some(Box::pin(future)),
+                state: self.state.clone(),
+            });
+
+        // SAFETY: `ThreadPool::scope` does not return until every `ScopedTask` it spawned has
+        // been dropped, and a `ScopedTask` drops its future before reporting that it has been
+        // dropped. The future therefore never outlives the data it borrows, even though the pool
+        // sees it as `'static`.
+        let task: Task = unsafe { std::mem::transmute(task) };
+
+        let mut job = ThreadJob::new();
+        job.add_task(task);
+        if let Err(e) = self.pool.send_job(job, Priority::Normal) {
+            self.state.record_error(e.to_string());
+        }
+    }
+}
+
 // The `ThreadPool` type is the main thread pool object. It is responsible for managing the
 // scheduler thread and the worker threads.
 pub struct ThreadPool {
-    // The sender channel for sending jobs to the scheduler thread.
-    job_sender: UnboundedSender<ThreadJob>,
+    // The sender channels for sending jobs to the scheduler thread. The senders are dropped when
+    // the pool shuts down so that no new jobs can be added.
+    job_senders: Option<JobSenders>,
 
     // The worker manager.
     worker_manager: Arc<Mutex<WorkerManager>>,
 
-    // The stopper function for stopping the scheduler thread.
-    stopper: Box<dyn Fn() -> () + Send + Sync + 'static>,
+    // The tracker for jobs that have been added to the pool but have not yet finished.
+    tracker: Arc<JobTracker>,
+
+    // The handle for the scheduler thread.
+    scheduler: Option<JoinHandle<Result<(), FoundationError>>>,
 }
 
 impl ThreadPool {
-    /// Create a new `ThreadPool` object.
+    /// Create a new `ThreadPool` object with an unbounded job queue.
     ///
     /// # Arguments
     ///
-    /// * `idle_receiver` - The receiver channel for receiving idle worker notifications.
+    /// * `max_workers` - The maximum number of workers in the pool.
     ///
     /// # Returns
     ///
     /// A new `ThreadPool` object.
     pub fn new(max_workers: WorkerId) -> ThreadPool {
-        // Create the channe for sending ThreadJobs to the scheduler thread.
-        let (job_sender, mut job_receiver) = unbounded_channel::<ThreadJob>();
+        // Create the channels for sending ThreadJobs to the scheduler thread.
+        let (job_senders, job_receivers) = job_channels(|| {
+            let (sender, receiver) = unbounded_channel::<ThreadJob>();
+            (
+                JobSender::Unbounded(sender),
+                JobReceiver::Unbounded(receiver),
+            )
+        });
+        ThreadPool::start(max_workers, job_senders, job_receivers)
+    }
 
+    /// Create a new `ThreadPool` object with a bounded job queue.
+    ///
+    /// Each priority level has its own queue. Once `queue_capacity` jobs are waiting in a queue,
+    /// `add_job` fails and `add_job_async` waits until the scheduler takes a job off that queue.
+    /// The scheduler holds on to a job or two while it waits for an idle worker, so slightly more
+    /// than `queue_capacity` jobs may be waiting to run.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_workers` - The maximum number of workers in the pool.
+    /// * `queue_capacity` - The maximum number of jobs waiting in each priority queue. A capacity
+    ///   of zero is treated as one.
+    ///
+    /// # Returns
+    ///
+    /// A new `ThreadPool` object.
+    pub fn with_capacity(max_workers: WorkerId, queue_capacity: usize) -> ThreadPool {
+        // A bounded tokio channel panics if created with a capacity of zero.
+        let queue_capacity = queue_capacity.max(1);
+
+        // Create the channels for sending ThreadJobs to the scheduler thread.
+        let (job_senders, job_receivers) = job_channels(|| {
+            let (sender, receiver) = channel::<ThreadJob>(queue_capacity);
+            (JobSender::Bounded(sender), JobReceiver::Bounded(receiver))
+        });
+        ThreadPool::start(max_workers, job_senders, job_receivers)
+    }
+
+    // Start the scheduler thread and create the `ThreadPool` object around it.
+    fn start(
+        max_workers: WorkerId,
+        job_senders: JobSenders,
+        mut job_receivers: JobReceivers,
+    ) -> ThreadPool {
         // Create the map of workers in the thread pool.
         // The map is a shared resource between the scheduler and the `ThreadPool`.
         let worker_manager: Arc<Mutex<WorkerManager>> =
@@ -237,95 +825,113 @@ impl ThreadPool {
         // Clone the manager, so we can use it in the scheduler thread.
         let scheduler_worker_manager = worker_manager.clone();
 
+        // Create the job tracker shared by the pool and the workers.
+        let tracker = Arc::new(JobTracker::new());
+        let scheduler_tracker = tracker.clone();
+
         // Create the channel for sending idle worker notifications.
         let (idle_sender, mut idle_receiver) = unbounded_channel::<WorkerId>();
 
         let scheduler: JoinHandle<Result<(), FoundationError>> = spawn(async move {
             debug!("Starting thread pool scheduler");
+
+            // A job taken off the queues that lost its worker to a higher priority job.
+            let mut held_job: Option<(Priority, ThreadJob)> = None;
+
             loop {
-                // Wait for the next job.
-                let job = job_receiver.recv().await;
-                if let Some(job) = job {
-                    // Try to get the next idle worker.  We try here and do not just wait in
-                    // the recv() call because we may be able to add a new worker to the pool
-                    // if we have not reached the maximum number of workers.
-                    match idle_receiver.try_recv() {
-                        Ok(idle_worker) => {
-                            // Get the worker object, so we can add the job to the worker thread
-                            // channel.
-                            if let Some(worker) = scheduler_worker_manager
-                                .lock()
-                                .unwrap()
-                                .workers
-                                .get_mut(&idle_worker)
+                // Wait for the next job, unless we are still holding one from the last pass.
+                let (priority, job) = match held_job.take() {
+                    Some(held) => held,
+                    None => match job_receivers.recv().await {
+                        Some(next) => next,
+                        None => {
+                            // The job channels have closed, so the pool is shutting down.
+                            debug!("ThreadPool scheduler job channels closed, exiting.");
+                            return Ok(());
+                        }
+                    },
+                };
+
+                // Try to get the next idle worker.  We try here and do not just wait in
+                // the recv() call because we may be able to add a new worker to the pool
+                // if we have not reached the maximum number of workers.
+                let idle_worker = match idle_receiver.try_recv() {
+                    Ok(idle_worker) => idle_worker,
+                    Err(TryRecvError::Empty) => {
+                        if let Ok(mut scheduler_worker_manager) = scheduler_worker_manager.lock() {
+                            if scheduler_worker_manager.current_workers
+                                < scheduler_worker_manager.max_workers
                             {
-                                worker.add_job(job)?;
-                            } else {
-                                // TODO: Do we want to drop the job?
-                                error!(
-                                    "ThreadPool could not find worker {}, dropping job.",
-                                    idle_worker
+                                let next_worker_id = scheduler_worker_manager.next_worker_id;
+                                let worker = Worker::new(
+                                    next_worker_id,
+                                    idle_sender.clone(),
+                                    scheduler_tracker.clone(),
                                 );
+                                scheduler_worker_manager
+                                    .workers
+                                    .insert(next_worker_id, worker);
+                                scheduler_worker_manager.next_worker_id += 1;
+                                scheduler_worker_manager.current_workers += 1;
                             }
                         }
-                        Err(e) => {
-                            match e {
-                                TryRecvError::Empty => {
-                                    if let Ok(mut scheduler_worker_manager) =
-                                        scheduler_worker_manager.lock()
-                                    {
-                                        if scheduler_worker_manager.current_workers
-                                            < scheduler_worker_manager.max_workers
-                                        {
-                                            let next_worker_id =
-                                                scheduler_worker_manager.next_worker_id;
-                                            let worker =
-                                                Worker::new(next_worker_id, idle_sender.clone());
-                                            scheduler_worker_manager
-                                                .workers
-                                                .insert(next_worker_id, worker);
-                                            scheduler_worker_manager.next_worker_id += 1;
-                                            scheduler_worker_manager.current_workers += 1;
-                                        }
-                                    }
 
-                                    // We may have added a worker to the pool, so now we just wait till we get an
-                                    // idle worker. The crucial bit here is that we now block waiting for the next
-                                    // idle worker to be available. We block here to avoid spinning on try_recv() in
-                                    // the main part of the loop.
-                                    let idle_worker = idle_receiver.recv().await;
-                                    if let Some(idle_worker) = idle_worker {
-                                        // Get the worker object, so we can add the job to the worker thread
-                                        // channel.
-                                        if let Some(worker) = scheduler_worker_manager
-                                            .lock()
-                                            .unwrap()
-                                            .workers
-                                            .get_mut(&idle_worker)
-                                        {
-                                            worker.add_job(job)?;
-                                        } else {
-                                            error!("ThreadPool could not find worker {}, dropping job.", idle_worker);
-                                        }
-                                    }
-                                }
-                                TryRecvError::Disconnected => {
-                                    debug!("ThreadPool received a disconnect from the idle worker sender.");
-                                    return Ok(());
-                                }
+                        // We may have added a worker to the pool, so now we just wait till we get an
+                        // idle worker. The crucial bit here is that we now block waiting for the next
+                        // idle worker to be available. We block here to avoid spinning on try_recv() in
+                        // the main part of the loop.
+                        match idle_receiver.recv().await {
+                            Some(idle_worker) => idle_worker,
+                            None => {
+                                debug!(
+                                    "ThreadPool received a disconnect from the idle worker sender."
+                                );
+                                return Ok(());
                             }
                         }
                     }
+                    Err(TryRecvError::Disconnected) => {
+                        debug!("ThreadPool received a disconnect from the idle worker sender.");
+                        return Ok(());
+                    }
+                };
+                scheduler_tracker.worker_busy();
+
+                // A higher priority job may have arrived while we waited for the worker. If so,
+                // that job gets the worker and we hold on to the current job for the next pass.
+                let job = match job_receivers.try_recv_above(priority) {
+                    Some(urgent_job) => {
+                        held_job = Some((priority, job));
+                        urgent_job
+                    }
+                    None => job,
+                };
+
+                // Get the worker object, so we can add the job to the worker thread channel.
+                if let Some(worker) = scheduler_worker_manager
+                    .lock()
+                    .unwrap()
+                    .workers
+                    .get_mut(&idle_worker)
+                {
+                    scheduler_tracker.job_dispatched();
+                    worker.add_job(job)?;
+                } else {
+                    // TODO: Do we want to drop the job?
+                    error!(
+                        "ThreadPool could not find worker {}, dropping job.",
+                        idle_worker
+                    );
+                    scheduler_tracker.job_dropped();
                 }
             }
         });
 
         ThreadPool {
-            job_sender,
+            job_senders: Some(job_senders),
             worker_manager,
-            stopper: Box::new(move || {
-                scheduler.abort();
-            }),
+            tracker,
+            scheduler: Some(scheduler),
         }
     }
 
@@ -339,21 +945,257 @@ impl ThreadPool {
     ///
     /// A result indicating success or failure.
     pub fn add_job(&mut self, job: ThreadJob) -> Result<(), FoundationError> {
-        match self.job_sender.send(job) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(FoundationError::TokioMpscSend(e.to_string())),
+        self.add_job_with_priority(job, Priority::Normal)
+    }
+
+    /// Add a job to the pool with a priority.
+    ///
+    /// When a worker becomes idle, the scheduler gives it the oldest job of the highest priority
+    /// that is waiting. Jobs with the same priority run in the order they were added.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job to add to the pool.
+    /// * `priority` - The priority of the job.
+    ///
+    /// # Returns
+    ///
+    /// A result indicating success or failure.
+    pub fn add_job_with_priority(
+        &mut self,
+        job: ThreadJob,
+        priority: Priority,
+    ) -> Result<(), FoundationError> {
+        self.send_job(job, priority)
+    }
+
+    // Send a job to the scheduler's queue for the given priority.
+    fn send_job(&self, job: ThreadJob, priority: Priority) -> Result<(), FoundationError> {
+        let Some(job_senders) = &self.job_senders else {
+            return Err(FoundationError::TokioMpscSend(
+                "thread pool has been shut down".to_string(),
+            ));
+        };
+        self.tracker.job_added();
+        let result = job_senders.get(priority).send(job);
+        if result.is_err() {
+            self.tracker.job_dropped();
         }
+        result
+    }
+
+    /// Add a job to the pool, waiting for room in the job queue if the pool was created with
+    /// `with_capacity` and the queue is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job to add to the pool.
+    ///
+    /// # Returns
+    ///
+    /// A result indicating success or failure.
+    pub async fn add_job_async(&mut self, job: ThreadJob) -> Result<(), FoundationError> {
+        let Some(job_senders) = &self.job_senders else {
+            return Err(FoundationError::TokioMpscSend(
+                "thread pool has been shut down".to_string(),
+            ));
+        };
+        self.tracker.job_added();
+        let result = job_senders.get(Priority::Normal).send_async(job).await;
+        if result.is_err() {
+            self.tracker.job_dropped();
+        }
+        result
+    }
+
+    /// Add a job to the pool and get a handle for waiting on the job's result.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job to add to the pool.
+    ///
+    /// # Returns
+    ///
+    /// A `JobHandle` that resolves to the job's result, or an error if the job could not be
+    /// added to the pool.
+    pub fn add_job_with_handle(
+        &mut self,
+        mut job: ThreadJob,
+    ) -> Result<JobHandle, FoundationError> {
+        let (result_sender, result_receiver) = oneshot::channel::<DynResult<()>>();
+        job.result_sender = Some(result_sender);
+        let name = job.name.clone();
+        self.add_job(job)?;
+        Ok(JobHandle {
+            result_receiver,
+            name,
+        })
+    }
+
+    /// Run tasks in the pool that borrow data from the caller.
+    ///
+    /// The closure receives a `Scope`, and each task passed to `Scope::spawn` runs in the pool
+    /// as a job of its own. Unlike jobs added with `add_job`, scoped tasks do not need to be
+    /// `'static`, so they can borrow local variables instead of sharing them through
+    /// `Arc<Mutex<_>>`. The call blocks until every task spawned in the scope has finished.
+    ///
+    /// Blocking needs a thread the pool's workers do not depend on, so `scope` must be called
+    /// from a multi-threaded tokio runtime, where it uses `block_in_place`, or from outside any
+    /// runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The closure that spawns the scoped tasks.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `f`, or an error if a scoped task failed or panicked, a task could
+    /// not be added to the pool, or `scope` was called from a current-thread runtime. If `f`
+    /// panics, the panic resumes once the tasks it spawned have finished.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use foundation::threadpool::ThreadPool;
+    /// use std::sync::atomic::{AtomicU32, Ordering};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut pool = ThreadPool::new(4);
+    ///     let numbers = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    ///     let total = AtomicU32::new(0);
+    ///
+    ///     pool.scope(|scope| {
+    ///         for chunk in numbers.chunks(2) {
+    ///             let total = &total;
+    ///             scope.spawn(async move {
+    ///                 total.fetch_add(chunk.iter().sum(), Ordering::SeqCst);
+    ///                 Ok(())
+    ///             });
+    ///         }
+    ///     })
+    ///     .unwrap();
+    ///
+    ///     assert_eq!(total.load(Ordering::SeqCst), 36);
+    /// }
+    /// ```
+    pub fn scope<'env, F, R>(&mut self, f: F) -> Result<R, FoundationError>
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R,
+    {
+        // Check the runtime before spawning anything, since after that the only safe way out is
+        // to wait for the tasks.
+        let in_runtime = match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::CurrentThread => {
+                return Err(FoundationError::OperationFailed(
+                    "ThreadPool::scope cannot block a current-thread runtime".to_string(),
+                ));
+            }
+            Ok(_) => true,
+            Err(_) => false,
+        };
+
+        let state = Arc::new(ScopeState::new());
+        let scope = Scope {
+            pool: self,
+            state: state.clone(),
+            scope: PhantomData,
+            env: PhantomData,
+        };
+
+        let result = catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+        if in_runtime {
+            block_in_place(|| state.wait());
+        } else {
+            state.wait();
+        }
+
+        match result {
+            Ok(value) => match state.take_error() {
+                Some(e) => Err(e),
+                None => Ok(value),
+            },
+            Err(panic) => resume_unwind(panic),
+        }
+    }
+
+    /// Get a snapshot of how busy the pool is.
+    ///
+    /// # Returns
+    ///
+    /// The pool's current `PoolMetrics`. The counts are read without stopping the pool, so they
+    /// may be slightly out of date by the time the caller looks at them.
+    pub fn metrics(&self) -> PoolMetrics {
+        let (current_workers, max_workers) = {
+            let worker_manager = self.worker_manager.lock().unwrap();
+            (worker_manager.current_workers, worker_manager.max_workers)
+        };
+        PoolMetrics {
+            current_workers,
+            max_workers,
+            idle_workers: self.tracker.idle_workers(),
+            queued_jobs: self.tracker.queued_jobs(),
+        }
+    }
+
+    /// Wait for every job added to the pool to finish.
+    ///
+    /// The returned future completes once there are no jobs waiting in the pool's queues and no
+    /// jobs currently running. Jobs added while waiting extend the wait.
+    pub async fn join(&mut self) {
+        self.tracker.wait().await;
     }
 
     /// Stop the pool.
+    ///
+    /// Stopping the pool aborts the scheduler and the workers immediately, which may interrupt
+    /// a task part way through. Use `shutdown` to let the pool finish its jobs first.
     pub fn stop(&mut self) {
-        (self.stopper)();
+        if let Some(scheduler) = &self.scheduler {
+            scheduler.abort();
+        }
         for worker in self.worker_manager.lock().unwrap().workers.values_mut() {
             if let Err(e) = worker.stop() {
                 error!("Error stopping worker: {}", e);
             }
         }
     }
+
+    /// Shut down the pool gracefully.
+    ///
+    /// The pool stops accepting new jobs, the jobs already added to the pool run to completion,
+    /// and then the scheduler and the workers exit. Adding a job after calling `shutdown` returns
+    /// an error.
+    pub async fn shutdown(&mut self) {
+        // Dropping the senders closes the job channels. The scheduler hands out the jobs it
+        // already has and then exits.
+        self.job_senders.take();
+        if let Some(scheduler) = self.scheduler.take() {
+            match scheduler.await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => error!("Thread pool scheduler failed: {}", e),
+                Err(e) => error!("Error waiting for thread pool scheduler: {}", e),
+            }
+        }
+
+        // Now close each worker's job channel and wait for the worker to drain its queue.
+        let workers: Vec<Worker> = {
+            let mut worker_manager = self.worker_manager.lock().unwrap();
+            worker_manager.current_workers = 0;
+            worker_manager
+                .workers
+                .drain()
+                .map(|(_, worker)| worker)
+                .collect()
+        };
+        for worker in workers {
+            if let Err(e) = worker.finish().await {
+                error!("Error shutting down worker: {}", e);
+            }
+        }
+        self.tracker.clear_idle_workers();
+    }
 }
 
 #[cfg(test)]
@@ -361,7 +1203,9 @@ mod tests {
     use super::*;
     use crate::result::DynResultError;
     use std::sync::{Arc, Mutex};
-    use tokio::time::{sleep, Duration};
+    use tokio::io::AsyncWriteExt;
+    use tokio::sync::Notify;
+    use tokio::time::{sleep, timeout, Duration};
 
     #[tokio::test]
     async fn test_multiple_tasks() {
@@ -635,4 +1479,418 @@ mod tests {
 
         thread_pool.stop();
     }
+
+    #[tokio::test]
+    async fn test_join() {
+        let mut thread_pool = ThreadPool::new(4);
+        let counter = Arc::new(Mutex::new(0));
+
+        for _ in 0..50 {
+            let counter_c = counter.clone();
+            let mut thread_job = ThreadJob::new();
+            thread_job.add_task(Box::pin(async move {
+                sleep(Duration::from_millis(5)).await;
+                *counter_c.lock().unwrap() += 1;
+                Ok(())
+            }));
+            if let Err(e) = thread_pool.add_job(thread_job) {
+                panic!("Error adding job to thread pool: {}", e);
+            }
+        }
+
+        thread_pool.join().await;
+
+        assert_eq!(*counter.lock().unwrap(), 50);
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test]
+    async fn test_job_handle_reports_error() {
+        let mut thread_pool = ThreadPool::new(2);
+
+        let mut failing_job = ThreadJob::new();
+        failing_job.add_task(Box::pin(async move {
+            let error = Box::new(FoundationError::ThreadTaskError(
+                "Error in task".to_string(),
+            ));
+            Err(error as DynResultError)
+        }));
+
+        let mut passing_job = ThreadJob::new();
+        passing_job.add_task(Box::pin(async move { Ok(()) }));
+
+        let failing_handle = match thread_pool.add_job_with_handle(failing_job) {
+            Ok(handle) => handle,
+            Err(e) => panic!("Error adding job to thread pool: {}", e),
+        };
+        let passing_handle = match thread_pool.add_job_with_handle(passing_job) {
+            Ok(handle) => handle,
+            Err(e) => panic!("Error adding job to thread pool: {}", e),
+        };
+
+        match failing_handle.wait().await {
+            Ok(_) => panic!("Expected the failing job to report an error"),
+            Err(e) => assert_eq!(e.to_string(), "Thread task error: Error in task"),
+        }
+        assert!(passing_handle.wait().await.is_ok());
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test]
+    async fn test_named_job_handle() {
+        let mut thread_pool = ThreadPool::new(2);
+
+        let mut failing_job = ThreadJob::with_name("refresh-interfaces");
+        assert_eq!(failing_job.name(), Some("refresh-interfaces"));
+        failing_job.add_task(Box::pin(async move {
+            let error = Box::new(FoundationError::ThreadTaskError(
+                "Error in task".to_string(),
+            ));
+            Err(error as DynResultError)
+        }));
+
+        let unnamed_job = ThreadJob::new();
+        assert_eq!(unnamed_job.name(), None);
+
+        let failing_handle = match thread_pool.add_job_with_handle(failing_job) {
+            Ok(handle) => handle,
+            Err(e) => panic!("Error adding job to thread pool: {}", e),
+        };
+        let unnamed_handle = match thread_pool.add_job_with_handle(unnamed_job) {
+            Ok(handle) => handle,
+            Err(e) => panic!("Error adding job to thread pool: {}", e),
+        };
+
+        // The handle identifies which job failed.
+        assert_eq!(failing_handle.name(), Some("refresh-interfaces"));
+        assert_eq!(unnamed_handle.name(), None);
+
+        match failing_handle.wait().await {
+            Ok(_) => panic!("Expected the failing job to report an error"),
+            Err(e) => assert_eq!(e.to_string(), "Thread task error: Error in task"),
+        }
+        assert!(unnamed_handle.wait().await.is_ok());
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scope_borrows_data() {
+        let mut thread_pool = ThreadPool::new(4);
+        let numbers: Vec<u64> = (1..=1000).collect();
+        let mut totals = [0u64; 10];
+
+        thread_pool
+            .scope(|scope| {
+                for (chunk, total) in numbers.chunks(100).zip(totals.iter_mut()) {
+                    scope.spawn(async move {
+                        *total = chunk.iter().sum();
+                        Ok(())
+                    });
+                }
+            })
+            .unwrap();
+
+        assert_eq!(totals.iter().sum::<u64>(), 500500);
+        assert_eq!(totals[0], 5050);
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scope_reports_errors() {
+        let mut thread_pool = ThreadPool::new(2);
+        let finished = AtomicUsize::new(0);
+
+        let result = thread_pool.scope(|scope| {
+            scope.spawn(async {
+                Err(Box::new(FoundationError::ThreadTaskError(
+                    "Error in scoped task".to_string(),
+                )) as DynResultError)
+            });
+            scope.spawn(async {
+                sleep(Duration::from_millis(100)).await;
+                finished.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            });
+        });
+
+        // The scope waited for the slow task even though the other one failed.
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+        match result {
+            Err(FoundationError::ThreadTaskError(message)) => {
+                assert_eq!(message, "Thread task error: Error in scoped task")
+            }
+            _ => panic!("Expected the scope to report the task error"),
+        }
+
+        let result = thread_pool.scope(|scope| {
+            scope.spawn(async { panic!("Panic in scoped task") });
+        });
+        assert!(result.is_err());
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test]
+    async fn test_scope_rejects_current_thread_runtime() {
+        let mut thread_pool = ThreadPool::new(2);
+        let result = thread_pool.scope(|_| ());
+        assert!(matches!(result, Err(FoundationError::OperationFailed(_))));
+        thread_pool.stop();
+    }
+
+    #[tokio::test]
+    async fn test_worker_survives_failing_job() {
+        let mut thread_pool = ThreadPool::new(1);
+
+        let mut failing_job = ThreadJob::new();
+        failing_job.add_task(Box::pin(async move {
+            let error = Box::new(FoundationError::ThreadTaskError(
+                "Error in task".to_string(),
+            ));
+            Err(error as DynResultError)
+        }));
+
+        let mut panicking_job = ThreadJob::new();
+        panicking_job.add_task(Box::pin(async move { panic!("Panic in task") }));
+
+        let control = Arc::new(Mutex::new(false));
+        let control_c = control.clone();
+        let mut passing_job = ThreadJob::new();
+        passing_job.add_task(Box::pin(async move {
+            *control_c.lock().unwrap() = true;
+            Ok(())
+        }));
+
+        if let Err(e) = thread_pool.add_job(failing_job) {
+            panic!("Error adding job 1 to thread pool: {}", e);
+        }
+        if let Err(e) = thread_pool.add_job(panicking_job) {
+            panic!("Error adding job 2 to thread pool: {}", e);
+        }
+        if let Err(e) = thread_pool.add_job(passing_job) {
+            panic!("Error adding job 3 to thread pool: {}", e);
+        }
+
+        thread_pool.join().await;
+
+        assert_eq!(*control.lock().unwrap(), true);
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let mut thread_pool = ThreadPool::new(2);
+        let path = std::env::temp_dir().join("threadpool_shutdown_test.txt");
+        let _ = std::fs::remove_file(&path);
+
+        let task_path = path.clone();
+        let mut thread_job = ThreadJob::new();
+        thread_job.add_task(Box::pin(async move {
+            let mut file = tokio::fs::File::create(&task_path).await?;
+            for i in 0..10 {
+                file.write_all(format!("line {}\n", i).as_bytes()).await?;
+                sleep(Duration::from_millis(20)).await;
+            }
+            file.flush().await?;
+            Ok(())
+        }));
+        if let Err(e) = thread_pool.add_job(thread_job) {
+            panic!("Error adding job to thread pool: {}", e);
+        }
+
+        thread_pool.shutdown().await;
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let expected: String = (0..10).map(|i| format!("line {}\n", i)).collect();
+        assert_eq!(contents, expected);
+
+        assert!(thread_pool.add_job(ThreadJob::new()).is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_bounded_queue_back_pressure() {
+        let mut thread_pool = ThreadPool::with_capacity(1, 1);
+        let started = Arc::new(Notify::new());
+        let release = Arc::new(Notify::new());
+        let counter = Arc::new(Mutex::new(0));
+
+        // Block the only worker until we release it.
+        let started_c = started.clone();
+        let release_c = release.clone();
+        let counter_c = counter.clone();
+        let mut blocking_job = ThreadJob::new();
+        blocking_job.add_task(Box::pin(async move {
+            started_c.notify_one();
+            release_c.notified().await;
+            *counter_c.lock().unwrap() += 1;
+            Ok(())
+        }));
+        if let Err(e) = thread_pool.add_job_async(blocking_job).await {
+            panic!("Error adding job to thread pool: {}", e);
+        }
+        started.notified().await;
+
+        let make_job = |counter: Arc<Mutex<i32>>| {
+            let mut thread_job = ThreadJob::new();
+            thread_job.add_task(Box::pin(async move {
+                *counter.lock().unwrap() += 1;
+                Ok(())
+            }));
+            thread_job
+        };
+
+        // The scheduler holds the first waiting job and the queue holds the second.
+        if let Err(e) = thread_pool.add_job_async(make_job(counter.clone())).await {
+            panic!("Error adding job to thread pool: {}", e);
+        }
+        if let Err(e) = thread_pool.add_job_async(make_job(counter.clone())).await {
+            panic!("Error adding job to thread pool: {}", e);
+        }
+
+        // The queue is now full, so the next add must wait for the worker.
+        {
+            let add = thread_pool.add_job_async(make_job(counter.clone()));
+            tokio::pin!(add);
+            assert!(timeout(Duration::from_millis(200), &mut add).await.is_err());
+
+            release.notify_one();
+            match timeout(Duration::from_secs(2), &mut add).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => panic!("Error adding job to thread pool: {}", e),
+                Err(_) => panic!("Adding a job did not complete after the queue drained"),
+            }
+        }
+
+        thread_pool.join().await;
+        assert_eq!(*counter.lock().unwrap(), 4);
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test]
+    async fn test_zero_queue_capacity() {
+        let mut thread_pool = ThreadPool::with_capacity(1, 0);
+        let counter = Arc::new(Mutex::new(0));
+
+        let counter_c = counter.clone();
+        let mut thread_job = ThreadJob::new();
+        thread_job.add_task(Box::pin(async move {
+            *counter_c.lock().unwrap() += 1;
+            Ok(())
+        }));
+        if let Err(e) = thread_pool.add_job_async(thread_job).await {
+            panic!("Error adding job to thread pool: {}", e);
+        }
+
+        thread_pool.join().await;
+        assert_eq!(*counter.lock().unwrap(), 1);
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let mut thread_pool = ThreadPool::new(1);
+
+        assert_eq!(
+            thread_pool.metrics(),
+            PoolMetrics {
+                current_workers: 0,
+                max_workers: 1,
+                idle_workers: 0,
+                queued_jobs: 0,
+            }
+        );
+
+        let started = Arc::new(Notify::new());
+        let release = Arc::new(Notify::new());
+
+        let started_c = started.clone();
+        let release_c = release.clone();
+        let mut long_job = ThreadJob::new();
+        long_job.add_task(Box::pin(async move {
+            started_c.notify_one();
+            release_c.notified().await;
+            Ok(())
+        }));
+        let mut waiting_job = ThreadJob::new();
+        waiting_job.add_task(Box::pin(async move { Ok(()) }));
+
+        if let Err(e) = thread_pool.add_job(long_job) {
+            panic!("Error adding job 1 to thread pool: {}", e);
+        }
+        started.notified().await;
+        if let Err(e) = thread_pool.add_job(waiting_job) {
+            panic!("Error adding job 2 to thread pool: {}", e);
+        }
+
+        let metrics = thread_pool.metrics();
+        assert_eq!(metrics.current_workers, 1);
+        assert_eq!(metrics.idle_workers, 0);
+        assert_eq!(metrics.queued_jobs, 1);
+
+        release.notify_one();
+        thread_pool.join().await;
+
+        assert_eq!(thread_pool.metrics().queued_jobs, 0);
+
+        thread_pool.stop();
+    }
+
+    #[tokio::test]
+    async fn test_priority() {
+        let mut thread_pool = ThreadPool::new(1);
+        let started = Arc::new(Notify::new());
+        let release = Arc::new(Notify::new());
+        let completed = Arc::new(Mutex::new(Vec::new()));
+
+        // Block the only worker so that the next jobs have to wait in the queues.
+        let started_c = started.clone();
+        let release_c = release.clone();
+        let mut blocking_job = ThreadJob::new();
+        blocking_job.add_task(Box::pin(async move {
+            started_c.notify_one();
+            release_c.notified().await;
+            Ok(())
+        }));
+        if let Err(e) = thread_pool.add_job(blocking_job) {
+            panic!("Error adding job 1 to thread pool: {}", e);
+        }
+        started.notified().await;
+
+        let completed_c = completed.clone();
+        let mut low_job = ThreadJob::new();
+        low_job.add_task(Box::pin(async move {
+            sleep(Duration::from_millis(100)).await;
+            completed_c.lock().unwrap().push("low");
+            Ok(())
+        }));
+        let completed_c = completed.clone();
+        let mut high_job = ThreadJob::new();
+        high_job.add_task(Box::pin(async move {
+            completed_c.lock().unwrap().push("high");
+            Ok(())
+        }));
+
+        if let Err(e) = thread_pool.add_job_with_priority(low_job, Priority::Low) {
+            panic!("Error adding job 2 to thread pool: {}", e);
+        }
+        if let Err(e) = thread_pool.add_job_with_priority(high_job, Priority::High) {
+            panic!("Error adding job 3 to thread pool: {}", e);
+        }
+
+        release.notify_one();
+        thread_pool.join().await;
+
+        assert_eq!(*completed.lock().unwrap(), vec!["high", "low"]);
+
+        thread_pool.stop();
+    }
 }
@@ -0,0 +1,23 @@
+//! Installing the signal handler replaces the default SIGINT and SIGTERM behavior for the whole
+//! process, so the test lives in its own test binary to keep Ctrl-C working for the rest of the
+//! test suite.
+
+#[cfg(unix)]
+#[test]
+fn test_signal_handler() {
+    use foundation::interrupter::Interrupter;
+
+    let interrupter = Interrupter::new();
+    interrupter.install_signal_handler().unwrap();
+    interrupter.clone().install_signal_handler().unwrap();
+    assert!(!interrupter.is_interrupted());
+
+    // SAFETY: raise only delivers the signal to this process, which now handles it.
+    assert_eq!(unsafe { libc::raise(libc::SIGTERM) }, 0);
+
+    let start = std::time::Instant::now();
+    while !interrupter.is_interrupted() && start.elapsed() < std::time::Duration::from_secs(5) {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(interrupter.is_interrupted());
+}